@@ -7,6 +7,10 @@ MKL_INTEL_THREAD="${MKLROOT}/lib/libmkl_intel_thread.a"
 MKL_CORE="${MKLROOT}/lib/libmkl_core.a"
 MKL_IOMP5="${MKLROOT}/../compiler/lib/libiomp5.a"
 echo Using MKLROOT "$MKLROOT".
+SUITESPARSE_ROOT=${SUITESPARSE_ROOT:-"/usr/local"}
+SUITESPARSE_INCLUDE=${SUITESPARSE_INCLUDE:-"${SUITESPARSE_ROOT}/include/suitesparse"}
+SUITESPARSE_LIB=${SUITESPARSE_LIB:-"${SUITESPARSE_ROOT}/lib"}
+echo Using SUITESPARSE_ROOT "$SUITESPARSE_ROOT".
 
 if [ ! -d "$PROJHOME/lib/mkl/" ]; then
   echo "Copying MKL library files into project."
@@ -16,6 +20,14 @@ else
   echo "MKL libraries already present in project."
 fi
 
+if [ ! -d "$PROJHOME/lib/umfpack/" ]; then
+  echo "Copying UMFPACK library files into project."
+  mkdir -p "$PROJHOME/lib/umfpack/";
+  cp "${SUITESPARSE_LIB}/libumfpack.a" "${SUITESPARSE_LIB}/libamd.a" "${SUITESPARSE_LIB}/libsuitesparseconfig.a" "$PROJHOME/lib/umfpack/"
+else
+  echo "UMFPACK libraries already present in project."
+fi
+
 echo "Compiling linear algebra wrapper functions."
-gcc -m64 -w -I"${MKLROOT}/include" -c linear_algebra.c -o lib/linear_algebra.o 
+gcc -m64 -w -I"${MKLROOT}/include" -I"${SUITESPARSE_INCLUDE}" -c linear_algebra.c -o lib/linear_algebra.o 
 
@@ -7,6 +7,7 @@ use std::num::abs;
 
 pub type R = f64;
 pub static R_NaN: R = f64::NAN;
+pub static R_EPSILON: R = f64::EPSILON;
 
 #[deriving(Eq, IterBytes, TotalOrd, TotalEq, Clone, Ord)]
 pub struct Deg(u8);
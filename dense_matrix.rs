@@ -3,6 +3,8 @@ use la;
 
 use std::libc::{c_ulong};
 use std::ptr;
+use std::vec;
+use std::num::{abs, sqrt};
 use std::iter::{range_inclusive};
 use std::cast::transmute;
 use extra::c_vec::CVec;
@@ -190,6 +192,46 @@ impl DenseMatrix {
     }
   }
 
+  /// Copy the given column of the matrix into a new vector. Columns are contiguous in the column-major storage.
+  pub fn col(&self, c: uint) -> ~[R] {
+    if c >= self.num_cols { fail!("Column out of range."); }
+    let first = c * self.num_rows;
+    vec::from_fn(self.num_rows, |r| unsafe { unsafe_get(&self.data, first + r) })
+  }
+
+  /// Copy the given row of the matrix into a new vector.
+  pub fn row(&self, r: uint) -> ~[R] {
+    if r >= self.num_rows { fail!("Row out of range."); }
+    vec::from_fn(self.num_cols, |c| unsafe { unsafe_get(&self.data, c * self.num_rows + r) })
+  }
+
+  /// Compute the Frobenius norm of the matrix, the square root of the sum of squares of its entries.
+  pub fn frobenius_norm(&self) -> R {
+    let n = self.num_rows * self.num_cols;
+    sqrt(range(0u, n).fold(0 as R, |sum, i| sum + sq(unsafe { unsafe_get(&self.data, i) })))
+  }
+
+  /// Get the maximum absolute value of the entries of the matrix.
+  pub fn max_abs(&self) -> R {
+    let n = self.num_rows * self.num_cols;
+    range(0u, n).fold(0 as R, |max, i| { let a = abs(unsafe { unsafe_get(&self.data, i) }); if a > max { a } else { max } })
+  }
+
+  /// Return a new matrix with the entries of this matrix multiplied by the given scalar.
+  pub fn scaled(&self, c: R) -> DenseMatrix {
+    let n = self.num_rows * self.num_cols;
+    let mut data = unsafe { alloc_data(n) };
+    for i in range(0u, n) {
+      unsafe { unsafe_set(&mut data, i, c * unsafe_get(&self.data, i)); }
+    }
+    DenseMatrix {
+      data: data,
+      num_rows: self.num_rows,
+      num_cols: self.num_cols,
+      capacity_cols: self.num_cols,
+    }
+  }
+
   #[inline(always)]
   pub unsafe fn col_maj_data_ptr(&self) -> *R {
     transmute(self.data.get(0))
@@ -219,6 +261,41 @@ impl DenseMatrix {
 } // DenseMatrix impl
 
 
+impl Neg<DenseMatrix> for DenseMatrix {
+  fn neg(&self) -> DenseMatrix {
+    self.scaled(-1 as R)
+  }
+}
+
+impl Add<DenseMatrix, DenseMatrix> for DenseMatrix {
+  fn add(&self, other: &DenseMatrix) -> DenseMatrix {
+    combine_elementwise(self, other, |a, b| a + b)
+  }
+}
+
+impl Sub<DenseMatrix, DenseMatrix> for DenseMatrix {
+  fn sub(&self, other: &DenseMatrix) -> DenseMatrix {
+    combine_elementwise(self, other, |a, b| a - b)
+  }
+}
+
+fn combine_elementwise(m1: &DenseMatrix, m2: &DenseMatrix, f: |R, R| -> R) -> DenseMatrix {
+  if m1.num_rows != m2.num_rows || m1.num_cols != m2.num_cols {
+    fail!("Matrix dimensions do not match for elementwise dense matrix operation.");
+  }
+  let n = m1.num_rows * m1.num_cols;
+  let mut data = unsafe { alloc_data(n) };
+  for i in range(0u, n) {
+    unsafe { unsafe_set(&mut data, i, f(unsafe_get(&m1.data, i), unsafe_get(&m2.data, i))); }
+  }
+  DenseMatrix {
+    data: data,
+    num_rows: m1.num_rows,
+    num_cols: m1.num_cols,
+    capacity_cols: m1.num_cols,
+  }
+}
+
 #[inline(always)]
 unsafe fn unsafe_set(v: &mut CVec<R>, i: uint, value: R) {
   *ptr::mut_offset(transmute(v.get(0)), i as int) = value;
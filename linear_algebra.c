@@ -6,7 +6,7 @@
 #include "mkl_pardiso.h"
 #include "mkl_types.h"
 #include "i_malloc.h"
-/*TODO: Setup umfpack on OS X #include "suitesparse/umfpack.h"*/
+#include "umfpack.h"
 
 void init_allocator(void* malloc_fn, void* calloc_fn, void* realloc_fn, void* free_fn) {
   i_malloc = malloc_fn;
@@ -76,12 +76,18 @@ lapack_int solve_symmetric_as_col_maj_with_ut_sys(double* a, lapack_int n, doubl
   return LAPACKE_dsysv(LAPACK_COL_MAJOR, 'U', n, nrhs, a, n, ipiv, b, n);
 } 
 
+/* Dense general matrix system solver, by LU factorization with partial pivoting. */
+lapack_int solve_general_as_col_maj(double* a, lapack_int n, double* b, lapack_int nrhs, lapack_int* ipiv) {
+  return LAPACKE_dgesv(LAPACK_COL_MAJOR, n, nrhs, a, n, ipiv, b, n);
+}
+
 
 /* Sparse symmetric matrix system solver. */
 MKL_INT mkl_solve_sparse_symmetric_as_ut_csr3(MKL_INT n, const MKL_INT* ia, const MKL_INT* ja, const double* a,
                                               const double* b, MKL_INT nrhs,
                                               double* x,
-                                              unsigned num_cpu_cores) {
+                                              unsigned num_cpu_cores,
+                                              MKL_INT* stats_out) {
 
   MKL_INT mtype = -2; /* symmetric indefinite */
   void *pt[64];
@@ -101,6 +107,7 @@ MKL_INT mkl_solve_sparse_symmetric_as_ut_csr3(MKL_INT n, const MKL_INT* ia, cons
   iparm[20] = 1; /* pivoting method, Bunch-Kaufman is recommended for symmetric indefinite matrices */
   iparm[23] = num_cpu_cores > 8 ? 1 : 0; /* Use two level parallel factorization algorithm. */
   iparm[26] = 1; /* Check matrix. TODO: Unset after testing. */
+  iparm[17] = -1; /* Report the number of nonzeros in the factors. */
   iparm[34] = 1; /* Use 0-based row and column numbers within ia and ja arrays. */
  
   maxfct = 1;    /* Leave this at 1. Number of numerical factorizations to keep in memory */
@@ -123,6 +130,12 @@ MKL_INT mkl_solve_sparse_symmetric_as_ut_csr3(MKL_INT n, const MKL_INT* ia, cons
   
   if (error != 0) { fprintf (stderr, "\nERROR during numerical factorization: %d", error); return error; }
 
+  /* Report factorization statistics if requested: factor nonzeros, and peak memory in KB over the solver phases. */
+  if (stats_out != NULL) {
+    stats_out[0] = iparm[17];
+    stats_out[1] = iparm[14] > iparm[15] + iparm[16] ? iparm[14] : iparm[15] + iparm[16];
+  }
+
   /* Back substitution and iterative refinement. */
   phase = 33;
   PARDISO(pt, &maxfct, &mnum, &mtype, &phase, &n, a, ia, ja, &i_un, &nrhs, iparm, &msglvl, b, x, &error);
@@ -139,7 +152,8 @@ MKL_INT mkl_solve_sparse_symmetric_as_ut_csr3(MKL_INT n, const MKL_INT* ia, cons
 MKL_INT mkl_solve_sparse_structurally_symmetric_csr3(MKL_INT n, const MKL_INT* ia, const MKL_INT* ja, const double* a,
                                                      const double* b, MKL_INT nrhs,
                                                      double* x,
-                                                     unsigned num_cpu_cores) {
+                                                     unsigned num_cpu_cores,
+                                                     MKL_INT* stats_out) {
 
   MKL_INT mtype = 1; /*  1 == Real structurally symmetric matrix. */
   void *pt[64];
@@ -158,6 +172,7 @@ MKL_INT mkl_solve_sparse_structurally_symmetric_csr3(MKL_INT n, const MKL_INT* i
   iparm[12] = 1; /* Maximum weighted matching algorithm is switched-on (default for nonsymmetric matrices). */
   iparm[23] = num_cpu_cores > 8 ? 1 : 0; /* Use two level parallel factorization algorithm. */
   iparm[26] = 1; /* Check matrix. TODO: Unset after testing. */
+  iparm[17] = -1; /* Report the number of nonzeros in the factors. */
   iparm[34] = 1; /* Use 0-based row and column numbers within ia and ja arrays. */
 
   maxfct = 1;    /* Leave this at 1. Number of numerical factorizations to keep in memory. */
@@ -180,6 +195,12 @@ MKL_INT mkl_solve_sparse_structurally_symmetric_csr3(MKL_INT n, const MKL_INT* i
  
   if (error != 0) { fprintf ("\nERROR during numerical factorization: %d", error); return error; }
 
+  /* Report factorization statistics if requested: factor nonzeros, and peak memory in KB over the solver phases. */
+  if (stats_out != NULL) {
+    stats_out[0] = iparm[17];
+    stats_out[1] = iparm[14] > iparm[15] + iparm[16] ? iparm[14] : iparm[15] + iparm[16];
+  }
+
   /* Back substitution and iterative refinement. */
   phase = 33;
 
@@ -192,33 +213,34 @@ MKL_INT mkl_solve_sparse_structurally_symmetric_csr3(MKL_INT n, const MKL_INT* i
   PARDISO(pt, &maxfct, &mnum, &mtype, &phase, &n, &d_un, ia, ja, &i_un, &nrhs, iparm, &msglvl, &d_un, &d_un, &error);
   return 0;
 }
-/*
-int umf_solve_sparse_csr3(int n, const int* ia, const int* ja, const double* a, const double* b, double* x) {
-  int status;
+/* Solve the general sparse system given in CSR3 format for nrhs right hand side columns stored consecutively in b,
+   writing the solutions consecutively into x. */
+int umf_solve_sparse_csr3(int n, const int* ia, const int* ja, const double* a, const double* b, int nrhs, double* x) {
+  int status, col;
   double *null = (double*)NULL;
   void *Symbolic, *Numeric;
 
   status = umfpack_di_symbolic(n, n, ia, ja, a, &Symbolic, null, null);
-  if (status != 0) { fprintf ("\nERROR during umf symbolic phase: %d", status); return status; }
+  if (status != UMFPACK_OK) { fprintf(stderr, "\nERROR during umf symbolic phase: %d", status); return status; }
   
   status = umfpack_di_numeric(ia, ja, a, Symbolic, &Numeric, null, null);
-  if (status != 0) { fprintf ("\nERROR during umf numeric phase: %d", status); return status; }
-  
   umfpack_di_free_symbolic(&Symbolic);
+  if (status != UMFPACK_OK) { fprintf(stderr, "\nERROR during umf numeric phase: %d", status); return status; }
   
-  status = umfpack_di_solve(UMFPACK_Aat, // Solve transposed system (equiv. to converting passed CSR to the required CSC format).
-                            ia, // column begin indexes in a for the transpose
-                            ja, // row indexes of corresponding values in a for the transpose
-                            a,  // values for the transpose
-                            x, 
-                            b,
-                            Numeric,
-                            null,
-                            null);
-  if (status != 0) { fprintf ("\nERROR during umf solve phase: %d", status); return status; }
+  for (col = 0; col < nrhs; ++col) {
+    status = umfpack_di_solve(UMFPACK_Aat, // Solve transposed system (equiv. to converting passed CSR to the required CSC format).
+                              ia, // column begin indexes in a for the transpose
+                              ja, // row indexes of corresponding values in a for the transpose
+                              a,  // values for the transpose
+                              x + col*n,
+                              b + col*n,
+                              Numeric,
+                              null,
+                              null);
+    if (status != UMFPACK_OK) { fprintf(stderr, "\nERROR during umf solve phase: %d", status); break; }
+  }
   
-  umfpack_di_free_numeric (&Numeric);
+  umfpack_di_free_numeric(&Numeric);
 
-  return 0;
+  return status;
 }
-*/
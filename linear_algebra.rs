@@ -1,14 +1,24 @@
-use common::{R, vec_with_len};
+use common::{R, vec_with_len, sq};
 use dense_matrix::DenseMatrix;
-use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric};
+use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric, General};
 
 use std::libc::{c_double, c_ulong, c_int, c_uint, c_void, malloc, calloc, realloc, free};
 use std::cast;
+use std::ptr;
 use std::libc;
+use std::num::{log2, sqrt};
+use std::vec;
+use std::f64;
+use std::io;
+use std::io::fs;
+use std::io::File;
+use std::io::buffered::BufferedReader;
+use extra::time::precise_time_s;
 
 
 pub type lapack_int = c_int; // Adjust according to whether LP64 or ILP64 libraries are being linked.
 pub type mkl_int = c_int;    // Adjust according to whether LP64 or ILP64 libraries are being linked.
+pub type umf_int = c_int;    // UMFPACK's "di" routines, used for the general sparse solver, take C ints.
 
 #[inline(never)]
 pub fn init() {
@@ -17,42 +27,362 @@ pub fn init() {
   }
 }
 
+/// Statistics reported by the direct sparse solver.
+pub struct SolveStats {
+  factor_nnz: uint,      // number of nonzeros in the matrix factors
+  peak_memory_kb: uint,  // peak memory used by the solver over its phases, in kilobytes
+  elapsed_secs: R,       // wall time for the solve
+}
+
+impl SolveStats {
+  /// Get the number of nonzeros in the factors of the system matrix, as reported by the solver. This is at least the
+  /// number of stored values of the matrix, with the excess being the fill-in from the factorization.
+  pub fn factor_nnz(&self) -> uint { self.factor_nnz }
+}
+
 #[inline(never)]
 pub fn solve_sparse(sys: &SparseMatrix, rhs: &DenseMatrix) -> ~[R] {
+  match try_solve_sparse(sys, rhs) {
+    Ok(sol) => sol,
+    Err(e) => fail!(e.to_str())
+  }
+}
+
+/// Solve the sparse system as in solve_sparse, but giving an error describing the solver backend and status instead
+/// of failing when the direct solver does not succeed, so that callers solving many systems can recover and report.
+#[inline(never)]
+pub fn try_solve_sparse(sys: &SparseMatrix, rhs: &DenseMatrix) -> Result<~[R], SolveError> {
+  unsafe { solve_sparse_impl(sys, rhs, ptr::mut_null()) }
+}
+
+/// Solve the sparse system as in solve_sparse, also returning statistics for the factorization and solve.
+#[inline(never)]
+pub fn solve_sparse_with_stats(sys: &SparseMatrix, rhs: &DenseMatrix) -> (~[R], SolveStats) {
+  let mut stats_buf = [0 as mkl_int, 0 as mkl_int];
+  let start_secs = precise_time_s();
+  let sol = match unsafe { solve_sparse_impl(sys, rhs, stats_buf.as_mut_ptr()) } {
+    Ok(sol) => sol,
+    Err(e) => fail!(e.to_str())
+  };
+  let elapsed_secs = precise_time_s() - start_secs;
+  (sol, SolveStats { factor_nnz: stats_buf[0] as uint, peak_memory_kb: stats_buf[1] as uint, elapsed_secs: elapsed_secs as R })
+}
+
+/// Solve the dense general (not necessarily symmetric) square system A X = B for X, by LU factorization with
+/// partial pivoting. The passed matrices are not modified. Fails if A is singular.
+pub fn solve_dense_general(a: &DenseMatrix, b: &DenseMatrix) -> DenseMatrix {
+  let n = a.num_rows();
+  if a.num_cols() != n { fail!("Dense general solve requires a square system matrix."); }
+  if b.num_rows() != n {
+    fail!(format!("Right hand side has {} rows, but the dense system has {} rows.", b.num_rows(), n));
+  }
+  let mut lu = DenseMatrix::of_size(n, n);
+  a.copy_into(&mut lu);
+  let mut x = DenseMatrix::of_size(n, b.num_cols());
+  b.copy_into(&mut x);
+  let mut pivots = vec::from_elem(n, 0 as lapack_int);
+  let info = unsafe {
+    solve_general_as_col_maj(lu.mut_col_maj_data_ptr(), n as lapack_int,
+                             x.mut_col_maj_data_ptr(), b.num_cols() as lapack_int,
+                             pivots.as_mut_ptr())
+  };
+  if info > 0 { fail!("Dense general system matrix is singular.") }
+  else if info < 0 { fail!(format!("Dense general solve received an invalid argument at position {}.", -info)) }
+  x
+}
+
+unsafe fn solve_sparse_impl(sys: &SparseMatrix, rhs: &DenseMatrix, stats_out: *mut mkl_int) -> Result<~[R], SolveError> {
   let n = sys.num_rows();
+  if rhs.num_rows() != n {
+    fail!(format!("Right hand side has {} rows, but the sparse system has {} rows.", rhs.num_rows(), n));
+  }
 
-  unsafe {
-    let (a, ia, ja) = sys.csr3_ptrs();  
-    let mut sol = vec_with_len(n);
-    let cpu_cores = num_cpus() as c_uint;
-
-    let stat = match sys.matrix_type() {
-      Symmetric => 
-        mkl_solve_sparse_symmetric_as_ut_csr3(n as mkl_int, ia, ja, a,
-                                              rhs.col_maj_data_ptr(), rhs.num_cols() as mkl_int,
-                                              sol.as_mut_ptr(),
-                                              cpu_cores),
-      StructurallySymmetric =>
-        mkl_solve_sparse_structurally_symmetric_csr3(n as mkl_int, ia, ja, a,
-                                                     rhs.col_maj_data_ptr(), rhs.num_cols() as mkl_int,
-                                                     sol.as_mut_ptr(),
-                                                     cpu_cores),
-      _ => 
-        fail!("TODO: Support umfpack here on OS X."),
-        //umf_solve_sparse_csr3(n as umf_int, ia, ja, a, rhs.col_maj_data_ptr(), sol.as_mut_ptr()),
-    };
-
-    if stat != 0 {
-      fail!(format!("solve_sparse_symmetric_as_ut_csr3 failed with error {:d}", stat));
+  let (a, ia, ja) = sys.csr3_ptrs();  
+  let mut sol = vec_with_len(n * rhs.num_cols());
+  let cpu_cores = num_cpus() as c_uint;
+
+  let (backend, stat) = match sys.matrix_type() {
+    Symmetric => 
+      (MklPardiso,
+       mkl_solve_sparse_symmetric_as_ut_csr3(n as mkl_int, ia, ja, a,
+                                             rhs.col_maj_data_ptr(), rhs.num_cols() as mkl_int,
+                                             sol.as_mut_ptr(),
+                                             cpu_cores,
+                                             stats_out) as int),
+    StructurallySymmetric =>
+      (MklPardiso,
+       mkl_solve_sparse_structurally_symmetric_csr3(n as mkl_int, ia, ja, a,
+                                                    rhs.col_maj_data_ptr(), rhs.num_cols() as mkl_int,
+                                                    sol.as_mut_ptr(),
+                                                    cpu_cores,
+                                                    stats_out) as int),
+    General =>
+      (Umfpack,
+       umf_solve_sparse_csr3(n as umf_int, ia, ja, a,
+                             rhs.col_maj_data_ptr(), rhs.num_cols() as umf_int,
+                             sol.as_mut_ptr()) as int),
+  };
+
+  if stat != 0 { Err(BackendFailure(backend, stat)) } else { Ok(sol) }
+}
+
+/// Compute the Euclidean norm of the residual sys x - rhs, for a single column right hand side.
+pub fn residual_norm(sys: &SparseMatrix, x: &[R], rhs: &DenseMatrix) -> R {
+  if rhs.num_cols() != 1 { fail!("Residual norm requires a single column right hand side."); }
+  let sys_x = sys.mat_vec(x);
+  sqrt(sys_x.iter().enumerate().fold(0 as R, |sum, (i, &v)| sum + sq(v - rhs.get(i,0))))
+}
+
+/// Write the sparse system matrix and single column right hand side to the given directory, which is created if
+/// necessary, so that a problematic solve can be reloaded by load_system and repeated in isolation. The matrix is
+/// written in MatrixMarket format to system.mtx, and the right hand side to rhs.txt as one full precision value per
+/// line. Existing files of these names are replaced.
+pub fn dump_system(sys: &SparseMatrix, rhs: &[R], dir: &Path) {
+  if rhs.len() != sys.num_rows() { fail!("Right hand side length does not match the system dimensions."); }
+  if !dir.exists() { fs::mkdir_recursive(dir, io::UserRWX); }
+  let mut sys_file = create_dump_file(&dir.join(DUMP_SYSTEM_FILE_NAME));
+  sys.write_matrix_market(&mut sys_file);
+  let mut rhs_file = create_dump_file(&dir.join(DUMP_RHS_FILE_NAME));
+  for &v in rhs.iter() {
+    rhs_file.write_line(f64::to_str_exp_digits(v, 17, false));
+  }
+}
+
+/// Read a sparse system matrix and right hand side previously written to the given directory by dump_system.
+pub fn load_system(dir: &Path) -> (SparseMatrix, ~[R]) {
+  let sys = SparseMatrix::read_matrix_market(&mut BufferedReader::new(open_dump_file(&dir.join(DUMP_SYSTEM_FILE_NAME))));
+  let mut rhs_rdr = BufferedReader::new(open_dump_file(&dir.join(DUMP_RHS_FILE_NAME)));
+  let rhs: ~[R] = rhs_rdr.lines().filter(|line| !line.trim().is_empty()).map(|line| {
+    match from_str::<R>(line.trim()) {
+      Some(v) => v,
+      None => fail!(format!("Invalid right hand side value {} in system dump.", line.trim()))
+    }
+  }).collect();
+  if rhs.len() != sys.num_rows() {
+    fail!(format!("System dump has {} right hand side values for {} rows.", rhs.len(), sys.num_rows()));
+  }
+  (sys, rhs)
+}
+
+static DUMP_SYSTEM_FILE_NAME: &'static str = "system.mtx";
+static DUMP_RHS_FILE_NAME: &'static str = "rhs.txt";
+
+fn create_dump_file(path: &Path) -> File {
+  match File::create(path) {
+    Some(f) => f,
+    None => fail!(format!("Could not create system dump file {}.", path.display()))
+  }
+}
+
+fn open_dump_file(path: &Path) -> File {
+  match File::open(path) {
+    Some(f) => f,
+    None => fail!(format!("Could not open system dump file {}.", path.display()))
+  }
+}
+
+/// Backends used by the direct sparse solver, by matrix type.
+#[deriving(Eq, Clone)]
+pub enum SparseSolverBackend {
+  MklPardiso, // Symmetric and StructurallySymmetric matrices
+  Umfpack,    // General matrices
+}
+
+impl ToStr for SparseSolverBackend {
+  fn to_str(&self) -> ~str {
+    match *self { MklPardiso => ~"MKL PARDISO", Umfpack => ~"UMFPACK" }
+  }
+}
+
+/// Reasons for failure of the direct and iterative solvers.
+#[deriving(Eq, Clone)]
+pub enum SolveError {
+  ZeroDiagonal(uint),    // row number of a zero or missing diagonal entry, which the iteration divides by
+  NotConverged(uint, R), // number of iterations performed, and the relative residual norm after the last of them
+  IndefiniteSystem(uint), // iteration at which a direction of non-positive curvature showed the system is not positive definite
+  BackendFailure(SparseSolverBackend, int), // direct solver backend and the nonzero status code it returned
+}
+
+impl ToStr for SolveError {
+  fn to_str(&self) -> ~str {
+    match *self {
+      ZeroDiagonal(r) => format!("The diagonal entry of row {} is zero.", r),
+      NotConverged(iters, rel_res) =>
+        format!("Not converged after {} iterations, with relative residual norm {}.", iters, rel_res),
+      IndefiniteSystem(iter) => format!("The system was found not to be positive definite at iteration {}.", iter),
+      BackendFailure(backend, stat) => format!("The {} sparse solver failed with status {}.", backend.to_str(), stat),
+    }
+  }
+}
+
+/// Solve the sparse system for a single column right hand side by successive over-relaxation (SOR) with relaxation
+/// factor omega in (0,2), starting from the zero vector, which for omega = 1 is the Gauss-Seidel iteration. The
+/// iteration stops when the residual norm is at most tol times the norm of the right hand side, giving NotConverged
+/// if this is not achieved in max_iters sweeps. Convergence is guaranteed for strictly diagonally dominant systems
+/// and, for all omega in (0,2), for symmetric positive definite systems.
+pub fn solve_sparse_sor(sys: &SparseMatrix, rhs: &DenseMatrix, omega: R, tol: R, max_iters: uint) -> Result<~[R], SolveError> {
+  let n = sys.num_rows();
+  if rhs.num_cols() != 1 { fail!("SOR requires a single column right hand side."); }
+  if rhs.num_rows() != n {
+    fail!(format!("Right hand side has {} rows, but the sparse system has {} rows.", rhs.num_rows(), n));
+  }
+  if !(0 as R < omega && omega < 2 as R) { fail!(format!("SOR relaxation factor {} is not in (0,2).", omega)); }
+
+  let (diag, off_diag_rows) = diag_and_off_diag_rows(sys);
+  match range(0, n).find(|&i| diag[i] == 0 as R) {
+    Some(i) => { return Err(ZeroDiagonal(i)); }
+    None => {}
+  }
+
+  let b = rhs.col(0);
+  let b_norm = sqrt(b.iter().fold(0 as R, |sum, &v| sum + sq(v)));
+  let mut x = vec::from_elem(n, 0 as R);
+  if b_norm == 0 as R { return Ok(x); }
+
+  let mut rel_res = 1 as R;
+  for _ in range(0, max_iters) {
+    for i in range(0, n) {
+      let s = off_diag_rows[i].iter().fold(b[i], |s, &(c, v)| s - v * x[c]);
+      x[i] = (1 as R - omega) * x[i] + omega * s / diag[i];
+    }
+    rel_res = residual_norm(sys, x.as_slice(), rhs) / b_norm;
+    if rel_res <= tol { return Ok(x); }
+  }
+  Err(NotConverged(max_iters, rel_res))
+}
+
+/// Solve the symmetric positive definite sparse system for a single column right hand side by the conjugate gradient
+/// method, starting from the zero vector. The iteration stops when the residual norm is at most tol times the norm of
+/// the right hand side, giving NotConverged if this is not achieved in max_iters iterations. In exact arithmetic the
+/// method converges in at most as many iterations as the system has distinct eigenvalues.
+pub fn solve_sparse_cg(sys: &SparseMatrix, rhs: &DenseMatrix, tol: R, max_iters: uint) -> Result<~[R], SolveError> {
+  pcg_impl(sys, rhs, None, tol, max_iters)
+}
+
+/// Solve the symmetric positive definite sparse system as in solve_sparse_cg, preconditioned by the inverse of the
+/// diagonal of the system (Jacobi preconditioning). This reduces the number of iterations for systems with badly
+/// scaled rows, such as the diagonally dominant mass-like matrices of WG assembly on graded meshes.
+pub fn solve_sparse_pcg_jacobi(sys: &SparseMatrix, rhs: &DenseMatrix, tol: R, max_iters: uint) -> Result<~[R], SolveError> {
+  let diag = sys.diagonal();
+  match range(0, diag.len()).find(|&i| diag[i] == 0 as R) {
+    Some(i) => { return Err(ZeroDiagonal(i)); }
+    None => {}
+  }
+  pcg_impl(sys, rhs, Some(diag.map(|&d| 1 as R / d)), tol, max_iters)
+}
+
+// Preconditioned conjugate gradient iteration, with the preconditioner applied as an element-wise multiplication by
+// the passed inverse diagonal, if any.
+fn pcg_impl(sys: &SparseMatrix, rhs: &DenseMatrix, inv_diag: Option<~[R]>, tol: R, max_iters: uint) -> Result<~[R], SolveError> {
+  let n = sys.num_rows();
+  if rhs.num_cols() != 1 { fail!("Conjugate gradient requires a single column right hand side."); }
+  if rhs.num_rows() != n {
+    fail!(format!("Right hand side has {} rows, but the sparse system has {} rows.", rhs.num_rows(), n));
+  }
+  let dot = |u: &[R], v: &[R]| range(0, u.len()).fold(0 as R, |sum, i| sum + u[i] * v[i]);
+  let precond = |r: &[R]| -> ~[R] {
+    match inv_diag {
+      Some(ref d) => vec::from_fn(r.len(), |i| d[i] * r[i]),
+      None => r.to_owned()
     }
+  };
 
-    sol
+  let mut r = rhs.col(0);
+  let b_norm = sqrt(dot(r.as_slice(), r.as_slice()));
+  let mut x = vec::from_elem(n, 0 as R);
+  if b_norm == 0 as R { return Ok(x); }
+
+  let mut z = precond(r.as_slice());
+  let mut p = z.clone();
+  let mut rz = dot(r.as_slice(), z.as_slice());
+  let mut rel_res = 1 as R;
+  for iter in range(0, max_iters) {
+    let ap = sys.mat_vec(p.as_slice());
+    let pap = dot(p.as_slice(), ap.as_slice());
+    if !(pap > 0 as R) { return Err(IndefiniteSystem(iter)); }
+    let alpha = rz / pap;
+    for i in range(0, n) {
+      x[i] += alpha * p[i];
+      r[i] -= alpha * ap[i];
+    }
+    rel_res = sqrt(dot(r.as_slice(), r.as_slice())) / b_norm;
+    if rel_res <= tol { return Ok(x); }
+    z = precond(r.as_slice());
+    let rz_next = dot(r.as_slice(), z.as_slice());
+    let beta = rz_next / rz;
+    for i in range(0, n) {
+      p[i] = z[i] + beta * p[i];
+    }
+    rz = rz_next;
+  }
+  Err(NotConverged(max_iters, rel_res))
+}
+
+// Split the rows of the (square) matrix into the diagonal entries and the off-diagonal (column, value) entries of each
+// row, with the stored upper triangle entries of a Symmetric matrix also included in the rows of their mirrors.
+fn diag_and_off_diag_rows(sys: &SparseMatrix) -> (~[R], ~[~[(uint, R)]]) {
+  let n = sys.num_rows();
+  let sym = match sys.matrix_type() { Symmetric => true, _ => false };
+  let mut diag = vec::from_elem(n, 0 as R);
+  let mut rows: ~[~[(uint, R)]] = vec::from_elem(n, ~[]);
+  for (r, c, v) in sys.entries() {
+    if r == c { diag[r] = v; }
+    else {
+      rows[r].push((c, v));
+      if sym { rows[c].push((r, v)); }
+    }
+  }
+  (diag, rows)
+}
+
+/// A linear operator on R^n which can be applied to vectors without necessarily having a stored matrix, for
+/// matrix-free iterations.
+pub trait LinearOperator {
+
+  /// The dimension n of the space on which the operator acts.
+  fn dim(&self) -> uint;
+
+  /// Apply the operator to the passed vector of length dim(), returning the image vector.
+  fn apply(&self, x: &[R]) -> ~[R];
+}
+
+impl LinearOperator for SparseMatrix {
+
+  fn dim(&self) -> uint { self.num_rows() }
+
+  fn apply(&self, x: &[R]) -> ~[R] { self.mat_vec(x) }
+}
+
+/// Estimate the spectral radius of the operator, the largest absolute value of its eigenvalues, by the given number
+/// of power iterations from the vector of all ones. The estimate is the norm of the image of the last normalized
+/// iterate, which converges to the spectral radius at a rate depending on the ratio of the two largest eigenvalue
+/// magnitudes, provided the initial vector is not orthogonal to the dominant eigenvectors.
+pub fn estimate_spectral_radius(op: &LinearOperator, iters: uint) -> R {
+  if iters == 0 { fail!("At least one power iteration is required."); }
+  let n = op.dim();
+  let norm = |v: &[R]| sqrt(v.iter().fold(0 as R, |sum, &x| sum + x*x));
+  let mut x = vec::from_elem(n, 1 as R / sqrt(n as R));
+  let mut est = 0 as R;
+  for _ in range(0, iters) {
+    let y = op.apply(x);
+    est = norm(y);
+    if est == 0 as R { return 0 as R; }
+    x = y.map(|&v| v / est);
   }
+  est
+}
+
+/// Estimate convergence rates from a sequence of errors obtained under successive refinements, where the mesh
+/// size is reduced by the given factor at each refinement. The i^th rate is log(e_i/e_{i+1})/log(refinement_factor).
+pub fn estimate_convergence_rate(errors: &[R], refinement_factor: R) -> ~[R] {
+  assert!(refinement_factor > 1 as R);
+  let log_refinement_factor = log2(refinement_factor);
+  errors.windows(2).map(|errs| (log2(errs[0]) - log2(errs[1])) / log_refinement_factor).collect()
 }
 
 /* TODO: This isn't the preferred way to link anymore (too platform specific), so requires feature gate in wgfem.rs.
          I'm not sure how to specify the -L option otherwise though. */
-#[link_args = "lib/linear_algebra.o -Llib/mkl -lmkl_intel_lp64 -lmkl_core -lmkl_intel_thread -lmkl_core -lmkl_intel_thread -lmkl_core -liomp5 -lpthread"] // -lumfpack
+#[link_args = "lib/linear_algebra.o -Llib/mkl -lmkl_intel_lp64 -lmkl_core -lmkl_intel_thread -lmkl_core -lmkl_intel_thread -lmkl_core -liomp5 -lpthread -Llib/umfpack -lumfpack -lamd -lsuitesparseconfig"]
 extern {
 
   pub fn init_allocator(malloc_fn: *c_void, calloc_fn: *c_void, realloc_fn: *c_void, free_fn: *c_void);
@@ -76,22 +406,32 @@ extern {
                                                 b: *mut c_double,
                                                 nrhs: lapack_int,
                                                 ipiv: *mut lapack_int) -> lapack_int;
+
+  /* Dense general matrix system solver, by LU factorization with partial pivoting. */
+  pub fn solve_general_as_col_maj(a: *mut c_double,
+                                  n: lapack_int,
+                                  b: *mut c_double,
+                                  nrhs: lapack_int,
+                                  ipiv: *mut lapack_int) -> lapack_int;
   
   /* MKL sparse symmetric matrix system solver. */
   pub fn mkl_solve_sparse_symmetric_as_ut_csr3(n: mkl_int, ia: *mkl_int, ja: *mkl_int, a: *c_double,
                                                b: *c_double, nrhs: mkl_int,
                                                x: *mut c_double,
-                                               num_cpu_cores: c_uint) -> mkl_int;
+                                               num_cpu_cores: c_uint,
+                                               stats_out: *mut mkl_int) -> mkl_int;
 
   /* MKL sparse structurally symmetric matrix system solver. */
   pub fn mkl_solve_sparse_structurally_symmetric_csr3(n: mkl_int, ia: *mkl_int, ja: *mkl_int, a: *c_double,
                                                       b: *c_double, nrhs: mkl_int,
                                                       x: *mut c_double,
-                                                      num_cpu_cores: c_uint) -> mkl_int;
+                                                      num_cpu_cores: c_uint,
+                                                      stats_out: *mut mkl_int) -> mkl_int;
   
   /* UMFPACK general sparse matrix system solver. */
-  // Works, commented out for now for convenience on OS X.
-  //pub fn umf_solve_sparse_csr3(n: umf_int, ia: *umf_int, ja: *umf_int, a: *c_double, b: *c_double, x: *mut c_double) -> umf_int;
+  pub fn umf_solve_sparse_csr3(n: umf_int, ia: *umf_int, ja: *umf_int, a: *c_double,
+                               b: *c_double, nrhs: umf_int,
+                               x: *mut c_double) -> umf_int;
 }
 
 fn num_cpus() -> uint {
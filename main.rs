@@ -45,7 +45,8 @@ fn main() {
     let vbf = &VBFLaplace::new(None, basis);
  
     println("Solving system...");
-    let wg_sol = wg_solver::solve(vbf, f, g);
+    let (wg_sol, residual_norm) = wg_solver::solve(vbf, f, g);
+    println!("system residual norm: {}", residual_norm);
 
     println("Computing L2 error...");
     let err = err_L2_norm(u, &wg_sol);
@@ -97,7 +98,8 @@ fn main() {
     let vbf = &VBFLaplace::new(None, basis);
  
     println("Solving system...");
-    let wg_sol = wg_solver::solve(vbf, f, g);
+    let (wg_sol, residual_norm) = wg_solver::solve(vbf, f, g);
+    println!("system residual norm: {}", residual_norm);
 
     println("Computing L2 error...");
     let err = err_L2_norm(u, &wg_sol);
@@ -15,6 +15,14 @@ pub struct NBSideNum(uint);
 #[deriving(Eq,TotalEq,Ord,TotalOrd,Clone,IterBytes)]
 pub struct SideFace(uint);
 
+// A Face identifies either the interior or one of the sides of a single oriented shape
+// or finite element.
+#[deriving(Eq,TotalEq,Ord,TotalOrd,Clone)]
+pub enum Face {
+  Interior,
+  Side(SideFace)
+}
+
 /// An identifier for the shape of a finite element together with the orientation
 /// of the shape (rotation will yield a different oriented shape). Many calculations
 /// on a finite element can be expressed in element-local coordinates and will only
@@ -44,12 +52,62 @@ impl NBSideInclusions {
   }
 }
 
+/// An iterator over the finite element numbers of a mesh, in increasing order.
+pub struct FENumIter {
+  priv next: uint,
+  priv end: uint
+}
+impl FENumIter {
+  pub fn new(num_fes: uint) -> FENumIter {
+    FENumIter { next: 0, end: num_fes }
+  }
+}
+impl Iterator<FENum> for FENumIter {
+  fn next(&mut self) -> Option<FENum> {
+    if self.next < self.end { self.next += 1; Some(FENum(self.next - 1)) } else { None }
+  }
+  fn size_hint(&self) -> (uint, Option<uint>) {
+    let n = self.end - self.next;
+    (n, Some(n))
+  }
+}
+
+/// An iterator over the non-boundary side numbers of a mesh, in increasing order.
+pub struct NBSideNumIter {
+  priv next: uint,
+  priv end: uint
+}
+impl NBSideNumIter {
+  pub fn new(num_nb_sides: uint) -> NBSideNumIter {
+    NBSideNumIter { next: 0, end: num_nb_sides }
+  }
+}
+impl Iterator<NBSideNum> for NBSideNumIter {
+  fn next(&mut self) -> Option<NBSideNum> {
+    if self.next < self.end { self.next += 1; Some(NBSideNum(self.next - 1)) } else { None }
+  }
+  fn size_hint(&self) -> (uint, Option<uint>) {
+    let n = self.end - self.next;
+    (n, Some(n))
+  }
+}
+
 pub trait Mesh<Mon> {
 
   fn num_fes(&self) -> uint;
 
   fn num_nb_sides(&self) -> uint;
 
+  /// Iterate over the numbers of all finite elements in the mesh.
+  fn fe_nums(&self) -> FENumIter {
+    FENumIter::new(self.num_fes())
+  }
+
+  /// Iterate over the numbers of all non-boundary sides in the mesh.
+  fn nb_side_nums(&self) -> NBSideNumIter {
+    NBSideNumIter::new(self.num_nb_sides())
+  }
+
   fn num_oriented_element_shapes(&self) -> uint;
 
   fn oriented_shape_for_fe(&self, fe: FENum) -> OShape;
@@ -64,9 +122,20 @@ pub trait Mesh<Mon> {
   // returned depending on the mesh implementation.
   fn dependent_dim_for_oshape_side(&self, os: OShape, sf: SideFace) -> Dim;
 
+  /// The unit normal vector of the given side of the oriented shape which points out of the shape, independently
+  /// of any normal direction convention used by the mesh for normal trace integrals.
+  fn outward_normal_for_oshape_side(&self, os: OShape, sf: SideFace) -> ~[R];
+
+  /// The measure (length, area, ...) of the given side of the oriented shape.
+  fn oshape_side_measure(&self, os: OShape, sf: SideFace) -> R;
+
+  /// The measure (area, volume, ...) of the interior of the oriented shape.
+  fn oshape_interior_measure(&self, os: OShape) -> R;
+
   fn fe_inclusions_of_nb_side(&self, side_num: NBSideNum) -> NBSideInclusions;
 
-  // Return non-boundary side number of the indicated fe relative side.
+  // Return non-boundary side number of the indicated fe relative side, which must not be a boundary side.
+  // The result is undefined for boundary sides, which may be detected via is_boundary_side.
   fn nb_side_num_for_fe_side(&self, fe: FENum, sf: SideFace) -> NBSideNum;
 
   fn is_boundary_side(&self, fe: FENum, sf: SideFace) -> bool;
@@ -106,6 +175,47 @@ pub trait Mesh<Mon> {
   fn intg_intrel_mon_x_siderel_mon_on_oshape_side(&self, int_mon: Mon, side_mon: Mon, os: OShape, sf: SideFace) -> R;
   
   fn intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(&self, mon: Mon, q: &VectorMonomial<Mon>, os: OShape, sf: SideFace) -> R;
+
+  /// Integrate the product of two face-relative monomials over the given face of the oriented shape.
+  fn intg_facerel_mon_x_facerel_mon_on_oshape_face(&self, mon_1: Mon, mon_2: Mon, os: OShape, face: Face) -> R;
+
+  /// Integrate the product of a face-relative monomial and a face-relative polynomial over the given face of the
+  /// oriented shape.
+  fn intg_facerel_mon_x_facerel_poly_on_oshape_face<P:Polynomial<Mon>>(&self, mon: Mon, p: &P, os: OShape, face: Face) -> R;
+
+  /// Integrate a face-relative monomial exactly over the given face of a finite element, dispatching to the
+  /// closed form integration on the element's oriented shape without integrating a closure.
+  #[inline]
+  fn intg_mon_on_fe_face(&self, mon: Mon, fe: FENum, face: Face) -> R {
+    let os = self.oriented_shape_for_fe(fe);
+    match face {
+      Interior => self.intg_facerel_mon_on_oshape_int(mon, os),
+      Side(sf) => self.intg_facerel_mon_on_oshape_side(mon, os, sf)
+    }
+  }
+
+  /// Integrate a global function times a face-relative monomial over the given face of a finite element, as needed
+  /// for load vectors. The function is evaluated at absolute coordinates and the monomial at face-relative
+  /// coordinates, with side faces handled as in intg_global_fn_x_facerel_mon_on_fe_side.
+  #[inline]
+  fn intg_global_fn_x_facerel_mon_on_fe_face(&self, f: |&[R]| -> R, mon: Mon, fe: FENum, face: Face) -> R {
+    match face {
+      Interior => self.intg_global_fn_x_facerel_mon_on_fe_int(f, mon, fe),
+      Side(sf) => self.intg_global_fn_x_facerel_mon_on_fe_side(f, mon, fe, sf)
+    }
+  }
+
+  /// Integrate a face-relative polynomial over the given face of the oriented shape, as the coefficient-weighted
+  /// sum of the integrals of its monomials. The integral of a polynomial without terms is 0.
+  #[inline]
+  fn intg_facerel_poly_on_oshape_face<P:Polynomial<Mon>>(&self, p: &P, os: OShape, face: Face) -> R {
+    p.foldl_terms(0 as R, |sum, (coef, mon)| {
+      sum + coef * match face {
+        Interior => self.intg_facerel_mon_on_oshape_int(mon, os),
+        Side(sf) => self.intg_facerel_mon_on_oshape_side(mon, os, sf)
+      }
+    })
+  }
  
 }
 
@@ -57,11 +57,28 @@ pub struct Mon4d {
   exps: [Deg,..4]
 }
 
+#[deriving(Eq, Clone)]
 pub enum DegLim {
   MaxMonDeg(u8),
   MaxMonFactorDeg(u8)
 }
 
+impl DegLim {
+
+  /// Get a degree limit satisfied by all products of a monomial satisfying this degree limit with a monomial
+  /// satisfying the other, such as the integrands of inner products. Limits of the same kind combine by adding
+  /// their degrees. A combination of a total degree limit with a factor degree limit gives a factor degree limit
+  /// of the summed degrees, which bounds each factor's degree in the product.
+  pub fn product_with(&self, other: &DegLim) -> DegLim {
+    match (*self, *other) {
+      (MaxMonDeg(l1), MaxMonDeg(l2)) => MaxMonDeg(l1 + l2),
+      (MaxMonFactorDeg(l1), MaxMonFactorDeg(l2)) |
+      (MaxMonDeg(l1), MaxMonFactorDeg(l2)) |
+      (MaxMonFactorDeg(l1), MaxMonDeg(l2)) => MaxMonFactorDeg(l1 + l2),
+    }
+  }
+}
+
 impl Monomial for Mon1d {
 
   #[inline(always)]
@@ -140,6 +140,26 @@ pub trait Polynomial<Mon: Monomial>: ToStr {
     }))
   }
 
+  /// Partial derivative of the polynomial with respect to the given coordinate.
+  fn partial_deriv(&self, r: Dim) -> PolyOwning<Mon> {
+    let mut coefs: ~[R] = vec::with_capacity(self.num_terms());
+    let mut mons: ~[Mon] = vec::with_capacity(self.num_terms());
+    self.each_term(|(c, mon)| {
+      let e = *mon.exp(r);
+      if e > 0 && c != 0 as R {
+        coefs.push(c * (e as R));
+        mons.push(mon.map_exp(r, |e| Deg(*e-1)));
+      }
+    });
+    if mons.len() == 0 { PolyOwning::zero() }
+    else { PolyOwning::new(coefs, mons) }
+  }
+
+  /// The classical gradient of the polynomial, as a vector of partial derivatives by coordinate.
+  fn gradient(&self) -> ~[PolyOwning<Mon>] {
+    vec::from_fn(domain_space_dims::<Mon>(), |r| self.partial_deriv(Dim(r)))
+  }
+
 }
 
 
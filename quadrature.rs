@@ -3,35 +3,47 @@ use std::libc::size_t;
 use std::ptr;
 use std::libc::{c_uint, c_int, c_void};
 use std::cast;
+use std::num::abs;
+use std::f64::consts::PI;
 
 use common::*;
 
-#[inline(never)]
+// Perform space-adaptive quadrature of f over the rectangle with the given corners, to within the requested relative
+// or absolute error, without a limit on the number of evaluations of f.
+#[inline]
 pub fn space_adaptive_quadrature(f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R], rel_err: R, abs_err: R) -> R {
-  let (val, status) = unsafe {
+  let (val, _, _) = space_adaptive_quadrature_with_error(f, min_corner, max_corner, rel_err, abs_err, 0);
+  val
+}
+
+// Perform space-adaptive quadrature of f over the rectangle with the given corners, limited to at most max_evals
+// evaluations of f (0 for no limit), returning the integral estimate, the estimated absolute error of the estimate,
+// and whether the estimated error meets the requested absolute or relative tolerance. When the evaluation limit is reached before the
+// tolerances are met, the under-resolved estimate is returned with the flag false rather than failing.
+#[inline(never)]
+pub fn space_adaptive_quadrature_with_error(f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R],
+                                            rel_err: R, abs_err: R, max_evals: uint) -> (R, R, bool) {
+  let (val, err, status) = unsafe {
     let f_dom_space_dims = min_corner.len() as c_uint;
     let f_range_space_dims = 1 as c_uint;
-    let f_pv: *c_void = cast::transmute(f); 
+    let f_pv: *c_void = cast::transmute(f);
     let integrand_caller_pv: *c_void = cast::transmute(h_integrand_caller);
-    let min_bounds = min_corner.as_ptr();
-    let max_bounds = max_corner.as_ptr();
-    let max_evals = 0 as size_t;
     let norm_unused = 0u32;
     let mut val = 0 as R;
     let mut err = 0 as R;
-    let status = 
+    let status =
       hquadrature(f_range_space_dims,
                   integrand_caller_pv,
                   f_pv,
-                  f_dom_space_dims, min_bounds, max_bounds,
-                  max_evals, rel_err, abs_err,
+                  f_dom_space_dims, min_corner.as_ptr(), max_corner.as_ptr(),
+                  max_evals as size_t, abs_err, rel_err,
                   norm_unused, &mut val, &mut err);
-    (val, status)
+    (val, err, status)
   };
 
   if (status != 0) { fail!("hquadrature call returned non-zero status"); }
-  
-  val
+
+  (val, err, err <= abs_err || err <= rel_err * abs(val))
 }
 
 #[inline]
@@ -67,6 +79,101 @@ pub fn gaussian_quadrature(n: uint, f: & |R| -> R, a: R, b: R) -> R {
   }
 }
 
+// Perform Gaussian-Legendre quadrature of f on the rectangle with the given minimum and maximum corners, using n
+// points per axis. A zero dimensional domain is a single point, and domains of more than two dimensions are
+// integrated with the tensor product rule of gauss_legendre_tensor.
+#[inline(never)]
+pub fn gaussian_quadrature_rect(n: uint, f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R]) -> R {
+  match min_corner.len() {
+    0 => (*f)(&[]),
+    1 => {
+      let mut x = [0 as R];
+      gaussian_quadrature(n, &|t: R| { x[0] = t; (*f)(x) }, min_corner[0], max_corner[0])
+    }
+    2 => {
+      let mut x = [0 as R, 0 as R];
+      gaussian_quadrature_2D_rect(n, &|s: R, t: R| { x[0] = s; x[1] = t; (*f)(x) },
+                                  min_corner[0], max_corner[0], min_corner[1], max_corner[1])
+    }
+    _ => gauss_legendre_tensor(f, min_corner, max_corner, n)
+  }
+}
+
+// Compute the nodes and weights of the 1D Gauss-Legendre rule of the given order on [-1,1], with nodes in ascending
+// order. The nodes are the roots of the Legendre polynomial of degree order, found by Newton iteration from
+// Chebyshev-like initial estimates.
+pub fn gauss_legendre_nodes_weights(order: uint) -> (~[R], ~[R]) {
+  if order == 0 { fail!("Gauss-Legendre order must be positive.") }
+  let mut nodes = vec::from_elem(order, 0 as R);
+  let mut weights = vec::from_elem(order, 0 as R);
+  // Evaluate the Legendre polynomial of degree order and its derivative at x via the three term recurrence.
+  let legendre_and_deriv = |x: R| -> (R, R) {
+    let (mut p_prev, mut p) = (1 as R, x);
+    for k in range(1, order) {
+      let p_next = ((2*k+1) as R * x * p - k as R * p_prev) / (k+1) as R;
+      p_prev = p; p = p_next;
+    }
+    (p, order as R * (x * p - p_prev) / (x*x - 1 as R))
+  };
+  for i in range(0, (order+1)/2) {
+    let mut x = (PI * (i as R + 0.75) / (order as R + 0.5)).cos();
+    let mut iters = 0u;
+    loop {
+      let (p, dp) = legendre_and_deriv(x);
+      let dx = p / dp;
+      x -= dx;
+      iters += 1;
+      if abs(dx) <= 1e-15 || iters == 100 { break; }
+    }
+    let (_, dp) = legendre_and_deriv(x);
+    let w = 2 as R / ((1 as R - x*x) * dp * dp);
+    nodes[i] = -x; weights[i] = w;
+    nodes[order-1-i] = x; weights[order-1-i] = w;
+  }
+  (nodes, weights)
+}
+
+// Perform tensor product Gauss-Legendre quadrature of f on the rectangle with the given minimum and maximum corners,
+// of any number of dimensions, using the 1D rule of the given order on each axis, for order^d evaluation points.
+// The result is exact (to rounding) for polynomials of degree at most 2*order-1 in each variable.
+#[inline(never)]
+pub fn gauss_legendre_tensor(f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R], order: uint) -> R {
+  let d = min_corner.len();
+  let (nodes, weights) = gauss_legendre_nodes_weights(order);
+  let half_lens = vec::from_fn(d, |r| (max_corner[r] - min_corner[r])/2.);
+  let mids = vec::from_fn(d, |r| (max_corner[r] + min_corner[r])/2.);
+  let jacobian = half_lens.iter().fold(1 as R, |prod, &h| prod * h);
+
+  // Iterate over the multi-indexes of the nodes, with the last axis varying fastest.
+  let mut ixs = vec::from_elem(d, 0u);
+  let mut x = vec::from_fn(d, |r| mids[r] + half_lens[r] * nodes[0]);
+  let mut sum = 0 as R;
+  loop {
+    let w = ixs.iter().fold(1 as R, |prod, &i| prod * weights[i]);
+    sum += w * (*f)(x.as_slice());
+    // Advance the multi-index, returning when all have been visited.
+    let mut r = d;
+    loop {
+      if r == 0 { return jacobian * sum; }
+      r -= 1;
+      ixs[r] += 1;
+      if ixs[r] < order { x[r] = mids[r] + half_lens[r] * nodes[ixs[r]]; break; }
+      ixs[r] = 0;
+      x[r] = mids[r] + half_lens[r] * nodes[0];
+    }
+  }
+}
+
+// Perform single point midpoint quadrature of f on the rectangle with the given minimum and maximum corners,
+// evaluating f at the rectangle's center and multiplying by the rectangle's measure. The rule is exact only for
+// constant and affine integrands.
+#[inline(never)]
+pub fn midpoint_quadrature_rect(f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R]) -> R {
+  let center = vec::from_fn(min_corner.len(), |r| (min_corner[r] + max_corner[r])/2.);
+  let measure = range(0, min_corner.len()).fold(1 as R, |prod, r| prod * (max_corner[r] - min_corner[r]));
+  measure * (*f)(center.as_slice())
+}
+
 
 
 
@@ -133,3 +240,35 @@ fn test_h_quadrature() {
   assert_eq!(space_adaptive_quadrature(&f1, min_bounds, max_bounds, 1e-5, 1e-5), 2.0)
 }
 
+#[test]
+fn test_space_adaptive_quadrature_with_error_oscillatory() {
+  let f = |x: &[f64]| (100. * x[0]).sin();
+  let exact = (1. - (100f64).cos()) / 100.;
+
+  // A single application of the underlying rule cannot resolve the oscillations.
+  let (_, est_err, converged) = space_adaptive_quadrature_with_error(&f, [0.], [1.], 1e-10, 1e-10, 15);
+  assert!(!converged);
+  assert!(est_err > 1e-10);
+
+  let (val, est_err, converged) = space_adaptive_quadrature_with_error(&f, [0.], [1.], 1e-10, 1e-10, 0);
+  assert!(converged);
+  assert!(est_err <= 1e-10);
+  assert!(abs(val - exact) < 1e-8);
+}
+
+#[test]
+fn test_gauss_legendre_tensor_exact_for_deg5_with_order3() {
+  // x^5 + x^2 y^3 z - 3 y z^4 + 2 over [0,1]x[-1,2]x[1,3], of degree 5, needing order 3.
+  let f = |x: &[f64]| pow(x[0],5) + x[0]*x[0]*pow(x[1],3)*x[2] - 3.*x[1]*pow(x[2],4) + 2.;
+  let exact = 1./6. * 3. * 2. + 1./3. * 15./4. * 4. - 3. * 3./2. * 242./5. + 2. * 6.;
+  let val = gauss_legendre_tensor(&f, [0.,-1.,1.], [1.,2.,3.], 3);
+  assert!(abs(val - exact) < 1e-12 * abs(exact));
+}
+
+#[test]
+fn test_gauss_legendre_nodes_weights() {
+  let (nodes, weights) = gauss_legendre_nodes_weights(3);
+  assert!(abs(nodes[0] + (0.6f64).sqrt()) < 1e-15 && abs(nodes[1]) < 1e-15 && abs(nodes[2] - (0.6f64).sqrt()) < 1e-15);
+  assert!(abs(weights[0] - 5./9.) < 1e-15 && abs(weights[1] - 8./9.) < 1e-15 && abs(weights[2] - 5./9.) < 1e-15);
+}
+
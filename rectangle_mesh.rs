@@ -6,7 +6,7 @@ use mesh::*;
 use quadrature::*;
 
 use std::vec;
-use std::num::sqrt;
+use std::num::{sqrt, abs};
 use std::iter::range_inclusive;
 use std::cast;
 
@@ -18,6 +18,65 @@ use std::cast;
 #[deriving(Eq, TotalEq, Ord, TotalOrd, Clone)]
 pub struct MeshCoord(uint);
 
+// QuadRule selects the numerical integration method used for integrals involving global functions.
+#[deriving(Eq, Clone)]
+pub enum QuadRule {
+  // Space-adaptive quadrature, to within the mesh's integration error tolerances.
+  SpaceAdaptive,
+  // Gauss-Legendre quadrature with the indicated number of points per axis.
+  GaussLegendre(uint),
+  // Single point midpoint rule: the integrand at the face center times the face measure. This is a cheap
+  // option for rapid prototyping, and is exact only for constant (and affine) integrands.
+  Midpoint,
+}
+
+// UnmetIntgTolHandling determines what is done when space-adaptive quadrature reaches the mesh's limit on integrand
+// evaluations without achieving the mesh's integration error tolerances.
+#[deriving(Eq, Clone)]
+pub enum UnmetIntgTolHandling {
+  // Silently use the under-resolved integral estimate (the default).
+  IgnoreUnmetIntgTol,
+  // Use the under-resolved estimate after logging a warning with the estimated error.
+  WarnOnUnmetIntgTol,
+  // Fail with the estimated error.
+  FailOnUnmetIntgTol,
+}
+
+// NormalDir selects the sign convention for unit normals on element sides used in normal trace integrals.
+#[deriving(Eq, Clone)]
+pub enum NormalDir {
+  // Normals point out of the element (the default).
+  Outward,
+  // Normals point into the element, which negates all normal trace integrals.
+  Inward,
+}
+
+/// MeshError describes an inconsistent rectangle mesh specification.
+#[deriving(Eq, Clone)]
+pub enum MeshError {
+  /// The number of bounds or logical dimensions given (first) differs from the number of space dimensions (second).
+  DimensionMismatch(uint, uint),
+  /// The axis has a logical dimension of 0, dividing it into no elements.
+  EmptyAxis(Dim),
+  /// The axis has a maximum bound not exceeding its minimum bound.
+  InvertedBounds(Dim),
+  /// The relative or absolute numerical integration error tolerance is negative.
+  NegativeIntgTol,
+}
+
+impl ToStr for MeshError {
+  fn to_str(&self) -> ~str {
+    match *self {
+      DimensionMismatch(given, space_dims) =>
+        format!("Mesh specification has {} components where {} space dimensions are required.", given, space_dims),
+      EmptyAxis(r) => format!("Mesh axis {} has no elements.", *r),
+      InvertedBounds(r) => format!("Mesh axis {} has a maximum bound not exceeding its minimum bound.", *r),
+      NegativeIntgTol => ~"Integration error tolerances must be non-negative.",
+    }
+  }
+}
+
+#[deriving(Clone)]
 pub struct RectMesh<Mon> {
 
   // The number of spatial dimensions of the Euclidiean space containing the mesh. 
@@ -31,12 +90,29 @@ pub struct RectMesh<Mon> {
   // with directions corresponding to the coordinate axes (cols, rows,...).
   mesh_ldims: ~[MeshCoord],
 
-  // Actual dimensions of any single finite element, the displacement vector from the
+  // Whether each axis is periodic, in which case the sides on the minimum and maximum bounds of the axis are
+  // identified, as non-boundary sides shared by the first and last elements along the axis.
+  periodic_axes: ~[bool],
+
+  // Coordinates of the element boundaries along each axis, from the minimum to the maximum bound, with
+  // mesh_ldims[r]+1 coordinates for axis r.
+  axis_node_coords: ~[~[R]],
+
+  // For each axis, the numbers of the distinct element widths along the axis of the elements at each mesh
+  // coordinate. Each combination of distinct widths over the axes is an oriented shape, numbered with the width
+  // number of the first axis varying fastest.
+  axis_width_nums: ~[~[uint]],
+  // Cumulative products of the numbers of distinct element widths along the axes, the last being the number of
+  // oriented shapes.
+  cumprods_axis_num_widths: ~[uint],
+
+  // Actual dimensions of the finite elements of each oriented shape, the displacement vector from the
   // minimum coordinates corner to the maximum coordinates corner.
-  fe_side_lens: ~[R],
+  oshape_side_lens: ~[~[R]],
 
-  // Actual dimensions of any finite element side, omitting the dimension on which it is constant (for quadrature).
-  fe_side_lens_wo_dim: ~[~[R]],
+  // Actual dimensions of the sides of the finite elements of each oriented shape, by perpendicular axis, omitting
+  // the dimension on which the side is constant (for quadrature).
+  oshape_side_lens_wo_dim: ~[~[~[R]]],
 
   // space_dims - 1 zeros, to be used as integration lower bounds when integrating over sides
   side_space_dims_zeros: ~[R],
@@ -62,89 +138,190 @@ pub struct RectMesh<Mon> {
   // The number of side faces (boundary or non-boundary) for each finite element.
   num_side_faces_per_fe: uint,
 
-  // The diameter (diagonal length) of the element rectangles in the mesh.
+  // The maximum diameter (diagonal length) of the element rectangles in the mesh.
   rect_diameter: R,
-  // 1/rect_diameter  
-  rect_diameter_inv: R,
+  // The reciprocals of the diameters of the element rectangles of each oriented shape.
+  oshape_diameters_inv: ~[R],
 
   // The tolerated relative and absolute erros for numerical integration.
   integration_rel_err: R,
   integration_abs_err: R,
 
+  // The numerical integration rules for integrals over element interiors and sides.
+  int_quad_rule: QuadRule,
+  side_quad_rule: QuadRule,
+  // Quadrature rules overriding the above for integrals over the interiors and sides of particular finite elements,
+  // by finite element number, or empty if no element's rules are overridden.
+  fe_quad_rule_overrides: ~[Option<QuadRule>],
+
+  // The direction of side normals relative to the element, for normal trace integrals.
+  normal_convention: NormalDir,
+
+  // The maximum number of integrand evaluations for a space-adaptive integral (0 for no limit), and the handling
+  // of integrals whose error tolerances are not met within the limit.
+  intg_max_evals: uint,
+  unmet_intg_tol_handling: UnmetIntgTolHandling,
+
   // Work buffers.
   fe_min_corner_buf: ~[R],
   fe_max_corner_buf: ~[R],
   intg_pt_trans_buf: ~[R],
   mesh_coords_buf: ~[MeshCoord],
+  fe_int_origin_buf: ~[R],
 }
 
 
+// Relative tolerance, as a fraction of the axis length, within which element widths along an axis are considered
+// equal, so that their elements share an oriented shape.
+static GRADED_WIDTH_REL_TOL: R = 1e-10;
+
 fn new_impl<Mon:Monomial>(min_bounds: ~[R],
                           max_bounds: ~[R],
                           mesh_ldims: ~[MeshCoord],
                           integration_rel_err: R,
-                          integration_abs_err: R) -> RectMesh<Mon> {
+                          integration_abs_err: R,
+                          int_quad_rule: QuadRule,
+                          side_quad_rule: QuadRule) -> RectMesh<Mon> {
+  match try_new_impl(min_bounds, max_bounds, mesh_ldims,
+                     integration_rel_err, integration_abs_err,
+                     int_quad_rule, side_quad_rule) {
+    Ok(mesh) => mesh,
+    Err(e) => fail!(e.to_str())
+  }
+}
+
+fn try_new_impl<Mon:Monomial>(min_bounds: ~[R],
+                              max_bounds: ~[R],
+                              mesh_ldims: ~[MeshCoord],
+                              integration_rel_err: R,
+                              integration_abs_err: R,
+                              int_quad_rule: QuadRule,
+                              side_quad_rule: QuadRule) -> Result<RectMesh<Mon>, MeshError> {
 
   let space_dims = domain_space_dims::<Mon>();
-  assert!(min_bounds.len() == space_dims);
-  assert!(max_bounds.len() == space_dims);
-  assert!(mesh_ldims.len() == space_dims);
-  
-  let fe_side_lens: ~[R] =
+  match check_bounds_and_ldims(space_dims, min_bounds, max_bounds, mesh_ldims) {
+    Err(e) => return Err(e),
+    Ok(()) => {}
+  }
+  if !(integration_rel_err >= 0 as R) || !(integration_abs_err >= 0 as R) { return Err(NegativeIntgTol); }
+
+  // Uniform element widths, with the element boundaries at multiples of the width from the minimum bound.
+  let axis_widths: ~[~[R]] =
     vec::from_fn(space_dims, |r| {
       let bounds_diff = max_bounds[r] - min_bounds[r];
       let ldim_r = *mesh_ldims[r];
-      assert!(bounds_diff > 0 as R);
-      assert!(ldim_r > 0);
-      bounds_diff/(ldim_r as R)
+      vec::from_elem(ldim_r, bounds_diff/(ldim_r as R))
     });
- 
-  // fe side dimensions in side-internal reduced dimension spaces, where the constant dimension is dropped (for quadrature)
-  let fe_side_lens_wo_dim: ~[~[R]] =
+  let axis_node_coords: ~[~[R]] =
     vec::from_fn(space_dims, |r| {
-      if r != space_dims - 1 { fe_side_lens.slice(0,r) + fe_side_lens.slice_from(r+1) }
-      else { fe_side_lens.slice(0,r).to_owned() }
+      let ldim_r = *mesh_ldims[r];
+      vec::from_fn(ldim_r + 1, |i| if i == ldim_r { max_bounds[r] } else { min_bounds[r] + (i as R) * axis_widths[r][i] })
     });
 
-  let cumprods_mesh_ldims: ~[uint] =
-    mesh_ldims.iter().scan(1, |prod, &ldim| {
-      *prod *= *ldim;
+  Ok(new_impl_from_axis_nodes(axis_node_coords, axis_widths,
+                              integration_rel_err, integration_abs_err,
+                              int_quad_rule, side_quad_rule))
+}
+
+// Check the consistency of the bounds and logical dimensions for a mesh of the given number of space dimensions.
+fn check_bounds_and_ldims(space_dims: uint, min_bounds: &[R], max_bounds: &[R], mesh_ldims: &[MeshCoord]) -> Result<(), MeshError> {
+  for &len in [min_bounds.len(), max_bounds.len(), mesh_ldims.len()].iter() {
+    if len != space_dims { return Err(DimensionMismatch(len, space_dims)); }
+  }
+  for r in range(0, space_dims) {
+    if *mesh_ldims[r] == 0 { return Err(EmptyAxis(Dim(r))); }
+    if !(max_bounds[r] > min_bounds[r]) { return Err(InvertedBounds(Dim(r))); }
+  }
+  Ok(())
+}
+
+// Construct a mesh from the element boundary coordinates along each axis and the corresponding element widths,
+// the widths at each axis being grouped into distinct widths to within GRADED_WIDTH_REL_TOL.
+fn new_impl_from_axis_nodes<Mon:Monomial>(axis_node_coords: ~[~[R]],
+                                          axis_widths: ~[~[R]],
+                                          integration_rel_err: R,
+                                          integration_abs_err: R,
+                                          int_quad_rule: QuadRule,
+                                          side_quad_rule: QuadRule) -> RectMesh<Mon> {
+
+  let space_dims = domain_space_dims::<Mon>();
+  assert!(axis_node_coords.len() == space_dims);
+  assert!(axis_widths.len() == space_dims);
+
+  let min_bounds: ~[R] = axis_node_coords.map(|coords| coords[0]);
+  let max_bounds: ~[R] = axis_node_coords.map(|coords| *coords.last());
+  let mesh_ldims: ~[MeshCoord] = axis_widths.map(|widths| MeshCoord(widths.len()));
+
+  // Number the distinct element widths along each axis, in order of first appearance.
+  let mut axis_distinct_widths: ~[~[R]] = vec::with_capacity(space_dims);
+  let mut axis_width_nums: ~[~[uint]] = vec::with_capacity(space_dims);
+  for r in range(0, space_dims) {
+    let tol = GRADED_WIDTH_REL_TOL * (max_bounds[r] - min_bounds[r]);
+    let mut distinct: ~[R] = ~[];
+    let nums = axis_widths[r].map(|&w| {
+      assert!(w > 0 as R);
+      match distinct.iter().position(|&dw| abs(dw - w) <= tol) {
+        Some(n) => n,
+        None => { distinct.push(w); distinct.len() - 1 }
+      }
+    });
+    axis_distinct_widths.push(distinct);
+    axis_width_nums.push(nums);
+  }
+
+  let cumprods_axis_num_widths: ~[uint] =
+    axis_distinct_widths.iter().scan(1, |prod, widths| {
+      *prod *= widths.len();
       Some(*prod)
     }).to_owned_vec();
 
-  let cumprods_nb_side_mesh_ldims_by_perp_axis: ~[~[uint]] =
-    vec::from_fn(space_dims, |perp_axis| {
-      vec::from_fn(space_dims, |prods_top_dim| {
-        range_inclusive(0, prods_top_dim).fold(1u, |ldims_prod, r| {
-          ldims_prod * (if r != perp_axis { *mesh_ldims[r] } else { *mesh_ldims[r]-1 })
-        })
+  let num_oshapes = *cumprods_axis_num_widths.last();
+
+  let oshape_side_lens: ~[~[R]] =
+    vec::from_fn(num_oshapes, |os| {
+      vec::from_fn(space_dims, |r| {
+        let preceeding_prod = if r == 0 { 1 } else { cumprods_axis_num_widths[r-1] };
+        axis_distinct_widths[r][(os % cumprods_axis_num_widths[r]) / preceeding_prod]
       })
     });
 
-  let num_fes = *cumprods_mesh_ldims.last();
+  // fe side dimensions in side-internal reduced dimension spaces, where the constant dimension is dropped (for quadrature)
+  let oshape_side_lens_wo_dim: ~[~[~[R]]] =
+    oshape_side_lens.map(|side_lens| {
+      vec::from_fn(space_dims, |r| {
+        if r != space_dims - 1 { side_lens.slice(0,r) + side_lens.slice_from(r+1) }
+        else { side_lens.slice(0,r).to_owned() }
+      })
+    });
 
-  let nb_side_counts_by_perp_axis = cumprods_nb_side_mesh_ldims_by_perp_axis.iter()
-                                      .map(|cumprods| *cumprods.last())
-                                      .to_owned_vec();
+  let cumprods_mesh_ldims: ~[uint] =
+    mesh_ldims.iter().scan(1, |prod, &ldim| {
+      *prod *= *ldim;
+      Some(*prod)
+    }).to_owned_vec();
 
-  let num_nb_sides = nb_side_counts_by_perp_axis.iter().fold(0u, |sum, &x| sum + x);
+  let periodic_axes = vec::from_elem(space_dims, false);
 
-  let first_nb_side_nums_by_perp_axis: ~[NBSideNum] = {
-    ~[NBSideNum(0u)] +
-    nb_side_counts_by_perp_axis.init()
-      .iter().scan(0, |sum, &axis_nb_sides| { *sum += axis_nb_sides; Some(NBSideNum(*sum)) })
-      .to_owned_vec()
-  };
+  let (cumprods_nb_side_mesh_ldims_by_perp_axis, first_nb_side_nums_by_perp_axis, num_nb_sides) =
+    nb_side_numbering(mesh_ldims, periodic_axes);
+
+  let num_fes = *cumprods_mesh_ldims.last();
 
-  let rect_diameter = sqrt(fe_side_lens.iter().fold(0 as R, |sum_sq_lens, &len| sum_sq_lens + len*len));
+  let oshape_diameters: ~[R] =
+    oshape_side_lens.map(|side_lens| sqrt(side_lens.iter().fold(0 as R, |sum_sq_lens, &len| sum_sq_lens + len*len)));
+  let rect_diameter = oshape_diameters.iter().fold(0 as R, |max_diam, &diam| if diam > max_diam { diam } else { max_diam });
 
   RectMesh {
     space_dims: space_dims,
     min_bounds: min_bounds,
     max_bounds: max_bounds,
     mesh_ldims: mesh_ldims,
-    fe_side_lens: fe_side_lens,
-    fe_side_lens_wo_dim: fe_side_lens_wo_dim,
+    periodic_axes: periodic_axes,
+    axis_node_coords: axis_node_coords,
+    axis_width_nums: axis_width_nums,
+    cumprods_axis_num_widths: cumprods_axis_num_widths,
+    oshape_side_lens: oshape_side_lens,
+    oshape_side_lens_wo_dim: oshape_side_lens_wo_dim,
     side_space_dims_zeros: vec::from_elem(space_dims-1, 0 as R),
     cumprods_mesh_ldims: cumprods_mesh_ldims,
     cumprods_nb_side_mesh_ldims_by_perp_axis: cumprods_nb_side_mesh_ldims_by_perp_axis,
@@ -153,25 +330,78 @@ fn new_impl<Mon:Monomial>(min_bounds: ~[R],
     num_nb_sides: num_nb_sides,
     num_side_faces_per_fe: 2 * space_dims,
     rect_diameter: rect_diameter,
-    rect_diameter_inv: 1./rect_diameter,
+    oshape_diameters_inv: oshape_diameters.map(|&diam| 1./diam),
     integration_rel_err: integration_rel_err,
     integration_abs_err: integration_abs_err,
+    int_quad_rule: int_quad_rule,
+    side_quad_rule: side_quad_rule,
+    fe_quad_rule_overrides: ~[],
+    normal_convention: Outward,
+    intg_max_evals: 0,
+    unmet_intg_tol_handling: IgnoreUnmetIntgTol,
     fe_min_corner_buf: vec_with_len(space_dims),
     fe_max_corner_buf: vec_with_len(space_dims),
     intg_pt_trans_buf: vec_with_len(space_dims),
     mesh_coords_buf: vec_with_len(space_dims),
+    fe_int_origin_buf: vec_with_len(space_dims),
   }
 }
 
+// Number the non-boundary sides for the given logical mesh dimensions and periodic axes, giving the cumulative products
+// of the logical dimensions of the non-boundary side meshes by perpendicular axis, the first non-boundary side numbers
+// by perpendicular axis, and the number of non-boundary sides. Along a periodic axis with logical dimension k there are
+// k sides perpendicular to the axis in each line of elements along it, the last joining the last and first elements,
+// instead of k-1.
+fn nb_side_numbering(mesh_ldims: &[MeshCoord], periodic_axes: &[bool]) -> (~[~[uint]], ~[NBSideNum], uint) {
+  let space_dims = mesh_ldims.len();
+
+  let cumprods_nb_side_mesh_ldims_by_perp_axis: ~[~[uint]] =
+    vec::from_fn(space_dims, |perp_axis| {
+      vec::from_fn(space_dims, |prods_top_dim| {
+        range_inclusive(0, prods_top_dim).fold(1u, |ldims_prod, r| {
+          ldims_prod * (if r != perp_axis || periodic_axes[r] { *mesh_ldims[r] } else { *mesh_ldims[r]-1 })
+        })
+      })
+    });
+
+  let nb_side_counts_by_perp_axis = cumprods_nb_side_mesh_ldims_by_perp_axis.iter()
+                                      .map(|cumprods| *cumprods.last())
+                                      .to_owned_vec();
+
+  let num_nb_sides = nb_side_counts_by_perp_axis.iter().fold(0u, |sum, &x| sum + x);
+
+  let first_nb_side_nums_by_perp_axis: ~[NBSideNum] = {
+    ~[NBSideNum(0u)] +
+    nb_side_counts_by_perp_axis.init()
+      .iter().scan(0, |sum, &axis_nb_sides| { *sum += axis_nb_sides; Some(NBSideNum(*sum)) })
+      .to_owned_vec()
+  };
+
+  (cumprods_nb_side_mesh_ldims_by_perp_axis, first_nb_side_nums_by_perp_axis, num_nb_sides)
+}
+
 
 impl<Mon:Monomial> RectMesh<Mon> {
- 
+
   /// Construct a new rectangle mesh with default numerical integration error tolerances. 
+  /// A logical dimension of 1 may be given for any axis to form an extruded mesh of a single layer
+  /// along that axis, as for quasi-2D problems embedded in 3D. All sides perpendicular to such an
+  /// axis are then boundary sides, and the mesh has no non-boundary sides perpendicular to it.
   pub fn new(min_bounds: ~[R],
              max_bounds: ~[R],
              mesh_ldims: ~[MeshCoord]) -> RectMesh<Mon> {
     new_impl(min_bounds, max_bounds, mesh_ldims,
-             DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR)
+             DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+             SpaceAdaptive, SpaceAdaptive)
+  }
+
+  /// Construct a new rectangle mesh as in new, returning an error describing the problem if the bounds and logical
+  /// dimensions are inconsistent, instead of failing.
+  pub fn try_new(min_bounds: ~[R],
+                 max_bounds: ~[R],
+                 mesh_ldims: ~[MeshCoord]) -> Result<~RectMesh<Mon>, MeshError> {
+    RectMesh::try_new_with_intg_tols(min_bounds, max_bounds, mesh_ldims,
+                                     DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR)
   }
 
   /// Construct a new rectangle mesh, also specifying numerical integration error tolerances.
@@ -181,7 +411,175 @@ impl<Mon:Monomial> RectMesh<Mon> {
                             integration_rel_err: R,
                             integration_abs_err: R) -> RectMesh<Mon> {
       new_impl(min_bounds, max_bounds, mesh_ldims,
-               integration_rel_err, integration_abs_err)
+               integration_rel_err, integration_abs_err,
+               SpaceAdaptive, SpaceAdaptive)
+  }
+
+  /// Construct a new rectangle mesh as in new_with_intg_tols, returning an error describing the problem if the
+  /// bounds, logical dimensions or tolerances are inconsistent, instead of failing.
+  pub fn try_new_with_intg_tols(min_bounds: ~[R],
+                                max_bounds: ~[R],
+                                mesh_ldims: ~[MeshCoord],
+                                integration_rel_err: R,
+                                integration_abs_err: R) -> Result<~RectMesh<Mon>, MeshError> {
+    match try_new_impl(min_bounds, max_bounds, mesh_ldims,
+                       integration_rel_err, integration_abs_err,
+                       SpaceAdaptive, SpaceAdaptive) {
+      Ok(mesh) => Ok(~mesh),
+      Err(e) => Err(e)
+    }
+  }
+
+  /// Construct a new rectangle mesh, specifying the numerical integration error tolerances as multiples of the
+  /// machine epsilon for the real number type R, so that the tolerances follow the precision of R.
+  pub fn new_with_eps_tols(min_bounds: ~[R],
+                           max_bounds: ~[R],
+                           mesh_ldims: ~[MeshCoord],
+                           integration_rel_err_eps_mult: R,
+                           integration_abs_err_eps_mult: R) -> RectMesh<Mon> {
+      new_impl(min_bounds, max_bounds, mesh_ldims,
+               integration_rel_err_eps_mult * R_EPSILON, integration_abs_err_eps_mult * R_EPSILON,
+               SpaceAdaptive, SpaceAdaptive)
+  }
+
+  /// Construct a new rectangle mesh using the given quadrature rule for both interior and side integrals.
+  pub fn new_with_quad_rule(min_bounds: ~[R],
+                            max_bounds: ~[R],
+                            mesh_ldims: ~[MeshCoord],
+                            quad_rule: QuadRule) -> RectMesh<Mon> {
+      new_impl(min_bounds, max_bounds, mesh_ldims,
+               DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+               quad_rule, quad_rule)
+  }
+
+  /// Construct a new rectangle mesh, specifying separate quadrature rules for integrals over element interiors
+  /// and over element sides.
+  pub fn new_with_quad_rules(min_bounds: ~[R],
+                             max_bounds: ~[R],
+                             mesh_ldims: ~[MeshCoord],
+                             int_quad_rule: QuadRule,
+                             side_quad_rule: QuadRule) -> RectMesh<Mon> {
+      new_impl(min_bounds, max_bounds, mesh_ldims,
+               DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+               int_quad_rule, side_quad_rule)
+  }
+
+  /// Construct a new rectangle mesh using the given quadrature rule for interior and side integrals, except on the
+  /// listed finite elements, whose interior and side integrals use the quadrature rules paired with them instead.
+  /// This allows raising the integration accuracy only where needed, such as on elements near a singularity.
+  pub fn new_with_fe_quad_rule_overrides(min_bounds: ~[R],
+                                         max_bounds: ~[R],
+                                         mesh_ldims: ~[MeshCoord],
+                                         quad_rule: QuadRule,
+                                         fe_quad_rules: ~[(FENum, QuadRule)]) -> RectMesh<Mon> {
+      let mut mesh = new_impl(min_bounds, max_bounds, mesh_ldims,
+                              DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+                              quad_rule, quad_rule);
+      if fe_quad_rules.len() > 0 {
+        let mut overrides = vec::from_elem(mesh.num_fes, None);
+        for &(fe, fe_quad_rule) in fe_quad_rules.iter() {
+          if *fe >= mesh.num_fes { fail!(format!("Quadrature rule override for nonexistent finite element {}.", *fe)); }
+          overrides[*fe] = Some(fe_quad_rule);
+        }
+        mesh.fe_quad_rule_overrides = overrides;
+      }
+      mesh
+  }
+
+  /// Construct a new rectangle mesh with the given numerical integration error tolerances, limiting space-adaptive
+  /// integrals to at most intg_max_evals integrand evaluations (0 for no limit). Integrals whose estimated errors
+  /// do not meet the tolerances within the limit are handled as indicated, instead of silently returning the
+  /// under-resolved value.
+  pub fn new_with_intg_tol_handling(min_bounds: ~[R],
+                                    max_bounds: ~[R],
+                                    mesh_ldims: ~[MeshCoord],
+                                    integration_rel_err: R,
+                                    integration_abs_err: R,
+                                    intg_max_evals: uint,
+                                    unmet_intg_tol_handling: UnmetIntgTolHandling) -> RectMesh<Mon> {
+      let mut mesh = new_impl(min_bounds, max_bounds, mesh_ldims,
+                              integration_rel_err, integration_abs_err,
+                              SpaceAdaptive, SpaceAdaptive);
+      mesh.intg_max_evals = intg_max_evals;
+      mesh.unmet_intg_tol_handling = unmet_intg_tol_handling;
+      mesh
+  }
+
+  /// Construct a new rectangle mesh with default numerical integration settings, using the given direction
+  /// convention for side normals in normal trace integrals. Normals are outward for the other constructors.
+  /// The inward convention negates intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side, and so also the
+  /// weak gradients of side supported functions, to match formulations defining weak gradients that way.
+  pub fn new_with_normal_convention(min_bounds: ~[R],
+                                    max_bounds: ~[R],
+                                    mesh_ldims: ~[MeshCoord],
+                                    normal_convention: NormalDir) -> RectMesh<Mon> {
+      let mut mesh = new_impl(min_bounds, max_bounds, mesh_ldims,
+                              DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+                              SpaceAdaptive, SpaceAdaptive);
+      mesh.normal_convention = normal_convention;
+      mesh
+  }
+
+  /// Construct a new graded rectangle mesh with default numerical integration settings, where axis_coords[r] is
+  /// the ascending sequence of element boundary coordinates along axis r, beginning with min_bounds[r] and ending
+  /// with max_bounds[r], so that element widths may vary along each axis. Each combination of distinct element
+  /// widths along the axes forms a separate oriented shape, so meshes with few distinct widths are cheapest to
+  /// build bases on. Widths along an axis equal to within a small fraction of the axis length are considered equal.
+  pub fn new_graded(min_bounds: ~[R],
+                    max_bounds: ~[R],
+                    axis_coords: ~[~[R]]) -> RectMesh<Mon> {
+      let space_dims = domain_space_dims::<Mon>();
+      assert!(min_bounds.len() == space_dims && max_bounds.len() == space_dims && axis_coords.len() == space_dims);
+      for r in range(0, space_dims) {
+        let coords = &axis_coords[r];
+        if coords.len() < 2 || coords[0] != min_bounds[r] || *coords.last() != max_bounds[r] {
+          fail!(format!("Coordinates along axis {} must run from the minimum to the maximum bound.", r));
+        }
+      }
+      let axis_widths: ~[~[R]] = axis_coords.map(|coords| {
+        let widths: ~[R] = coords.windows(2).map(|c| {
+          if c[1] <= c[0] { fail!("Graded mesh axis coordinates must be strictly increasing."); }
+          c[1] - c[0]
+        }).collect();
+        widths
+      });
+      new_impl_from_axis_nodes(axis_coords, axis_widths,
+                               DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+                               SpaceAdaptive, SpaceAdaptive)
+  }
+
+  /// Construct a new rectangle mesh with default numerical integration settings, periodic along each axis r for
+  /// which periodic_axes[r] is true. The sides on the minimum and maximum bounds of a periodic axis are identified,
+  /// as non-boundary sides shared by the first and last elements along the axis, so that they support side basis
+  /// elements and solutions are periodic along the axis. A periodic axis must have at least two elements.
+  pub fn new_periodic(min_bounds: ~[R],
+                      max_bounds: ~[R],
+                      mesh_ldims: ~[MeshCoord],
+                      periodic_axes: ~[bool]) -> RectMesh<Mon> {
+      let mut mesh = new_impl(min_bounds, max_bounds, mesh_ldims,
+                              DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR,
+                              SpaceAdaptive, SpaceAdaptive);
+      if periodic_axes.len() != mesh.space_dims {
+        fail!(DimensionMismatch(periodic_axes.len(), mesh.space_dims).to_str());
+      }
+      for r in range(0, mesh.space_dims) {
+        if periodic_axes[r] && *mesh.mesh_ldims[r] < 2 {
+          fail!(format!("Periodic mesh axis {} must have at least two elements.", r));
+        }
+      }
+      let (cumprods_nb_side_mesh_ldims_by_perp_axis, first_nb_side_nums_by_perp_axis, num_nb_sides) =
+        nb_side_numbering(mesh.mesh_ldims, periodic_axes);
+      mesh.cumprods_nb_side_mesh_ldims_by_perp_axis = cumprods_nb_side_mesh_ldims_by_perp_axis;
+      mesh.first_nb_side_nums_by_perp_axis = first_nb_side_nums_by_perp_axis;
+      mesh.num_nb_sides = num_nb_sides;
+      mesh.periodic_axes = periodic_axes;
+      mesh
+  }
+
+  /// Determine whether the mesh is periodic along the given axis.
+  #[inline(always)]
+  pub fn is_periodic_axis(&self, r: Dim) -> bool {
+    self.periodic_axes[*r]
   }
 
 
@@ -229,7 +627,12 @@ impl<Mon:Monomial> RectMesh<Mon> {
     let is_lesser_side = side_face_is_lesser_on_perp_axis(side_face);
     for r in range(0, self.space_dims) {
       let fe_coord_r = self.fe_mesh_coord(Dim(r), fe);
-      self.mesh_coords_buf[r] = if is_lesser_side && r == *a { MeshCoord(*fe_coord_r - 1) } else { fe_coord_r };
+      self.mesh_coords_buf[r] =
+        if is_lesser_side && r == *a {
+          // The lesser side of a first element along a periodic axis is the last side along the axis.
+          if *fe_coord_r == 0 { MeshCoord(*self.mesh_ldims[r] - 1) } else { MeshCoord(*fe_coord_r - 1) }
+        }
+        else { fe_coord_r };
     }
     self.mesh_coords_buf.as_slice()
   }
@@ -289,7 +692,7 @@ impl<Mon:Monomial> RectMesh<Mon> {
     for r in range(0, self.space_dims) {
       let min_corner_comp_r = self.fe_min_corner_comp(fe, Dim(r));
       self.fe_min_corner_buf[r] = min_corner_comp_r;
-      self.fe_max_corner_buf[r] = min_corner_comp_r + self.fe_side_lens[r];
+      self.fe_max_corner_buf[r] = min_corner_comp_r + self.fe_side_len(fe, Dim(r));
     }
     (self.fe_min_corner_buf.as_slice(), self.fe_max_corner_buf.as_slice())
   }
@@ -300,10 +703,126 @@ impl<Mon:Monomial> RectMesh<Mon> {
     self.fe_min_corner_comp(fe, r)
   }
 
+  /// Write the interior origin of the given finite element into the passed buffer, which must have a length equal
+  /// to the number of space dimensions. This avoids the allocation of fe_interior_origin in repeated calls.
+  #[inline]
+  pub fn fe_interior_origin_into(&self, fe: FENum, out: &mut [R]) {
+    assert!(out.len() == self.space_dims);
+    for r in range(0, self.space_dims) {
+      out[r] = self.fe_interior_origin_comp(fe, Dim(r));
+    }
+  }
+
+  /// Retrieve the interior origin of the given finite element as a newly allocated vector.
+  pub fn fe_interior_origin(&self, fe: FENum) -> ~[R] {
+    let mut origin = vec_with_len(self.space_dims);
+    self.fe_interior_origin_into(fe, origin.as_mut_slice());
+    origin
+  }
+
   /// Retrieve the given space coordinate of the minimum corner of the given finite element.
   #[inline(always)]
   pub fn fe_min_corner_comp(&self, fe: FENum, r: Dim) -> R {
-    self.min_bounds[*r] + (*self.fe_mesh_coord(r, fe) as R) * self.fe_side_lens[*r]
+    self.axis_node_coords[*r][*self.fe_mesh_coord(r, fe)]
+  }
+
+  /// Retrieve the width of the given finite element along axis r.
+  #[inline(always)]
+  pub fn fe_side_len(&self, fe: FENum, r: Dim) -> R {
+    self.oshape_side_lens[*self.oriented_shape_for_fe(fe)][*r]
+  }
+
+  /// Find the 2^d corner vertices of the given finite element. Vertex i has the element's maximum coordinate along
+  /// axis r if bit r of i is set, and its minimum coordinate along r otherwise, so the vertices are ordered by binary
+  /// counting with the first axis varying fastest, from the minimum corner to the maximum corner.
+  pub fn fe_vertices(&self, fe: FENum) -> ~[~[R]] {
+    vec::from_fn(1u << self.space_dims, |i| {
+      vec::from_fn(self.space_dims, |r| {
+        let min_r = self.fe_interior_origin_comp(fe, Dim(r));
+        if (i >> r) & 1 == 1 { min_r + self.fe_side_len(fe, Dim(r)) } else { min_r }
+      })
+    })
+  }
+
+  // Retrieve the dimensions of the finite elements of the given oriented shape.
+  #[inline(always)]
+  fn side_lens_for_oshape<'a>(&'a self, oshape: OShape) -> &'a [R] {
+    assert!(*oshape < self.oshape_side_lens.len());
+    self.oshape_side_lens[*oshape].as_slice()
+  }
+
+  /// Find the finite element containing the given point, if the point is within the bounds of the mesh. A point on
+  /// a side shared by two finite elements is assigned to the element of lesser mesh coordinate along the side's
+  /// perpendicular axis, and a point on the mesh's minimum bounds to the first element along that axis.
+  pub fn fe_containing_point(&self, x: &[R]) -> Option<FENum> {
+    assert!(x.len() == self.space_dims);
+    let mut fe = 0u;
+    for r in range(0, self.space_dims) {
+      if x[r] < self.min_bounds[r] || x[r] > self.max_bounds[r] { return None; }
+      // Find the least mesh coordinate whose element's maximum corner is not below the point.
+      let nodes = &self.axis_node_coords[r];
+      let (mut lo, mut hi) = (0u, *self.mesh_ldims[r] - 1);
+      while lo < hi {
+        let mid = (lo + hi) / 2;
+        if nodes[mid+1] >= x[r] { hi = mid } else { lo = mid + 1 }
+      }
+      let coord_r = lo;
+      fe += coord_r * if r == 0 { 1 } else { self.cumprods_mesh_ldims[r-1] };
+    }
+    Some(FENum(fe))
+  }
+
+  /// Find the number of boundary sides which are perpendicular to each axis, by perpendicular axis number. There are
+  /// no boundary sides perpendicular to a periodic axis.
+  pub fn num_boundary_sides_by_perp_axis(&self) -> ~[uint] {
+    vec::from_fn(self.space_dims, |perp_axis| {
+      if self.periodic_axes[perp_axis] { 0 }
+      else {
+        range(0, self.space_dims).fold(1u, |prod, r| {
+          prod * if r == perp_axis { 2 } else { *self.mesh_ldims[r] }
+        })
+      }
+    })
+  }
+
+  // The quadrature rules for integrals over the interior and the sides of the given finite element.
+  #[inline]
+  fn fe_int_quad_rule(&self, fe: FENum) -> QuadRule {
+    if self.fe_quad_rule_overrides.len() == 0 { return self.int_quad_rule; }
+    match self.fe_quad_rule_overrides[*fe] { Some(quad_rule) => quad_rule, None => self.int_quad_rule }
+  }
+  #[inline]
+  fn fe_side_quad_rule(&self, fe: FENum) -> QuadRule {
+    if self.fe_quad_rule_overrides.len() == 0 { return self.side_quad_rule; }
+    match self.fe_quad_rule_overrides[*fe] { Some(quad_rule) => quad_rule, None => self.side_quad_rule }
+  }
+
+  // Numerically integrate f over the rectangle with the given corners using the indicated quadrature rule.
+  #[inline]
+  fn quadrature_by_rule(&self, quad_rule: QuadRule, f: & |&[R]| -> R, min_corner: &[R], max_corner: &[R]) -> R {
+    match quad_rule {
+      SpaceAdaptive if self.unmet_intg_tol_handling == IgnoreUnmetIntgTol && self.intg_max_evals == 0 =>
+        space_adaptive_quadrature(f, min_corner, max_corner, self.integration_rel_err, self.integration_abs_err),
+      SpaceAdaptive => {
+        let (val, est_err, converged) =
+          space_adaptive_quadrature_with_error(f, min_corner, max_corner,
+                                               self.integration_rel_err, self.integration_abs_err, self.intg_max_evals);
+        if !converged {
+          match self.unmet_intg_tol_handling {
+            IgnoreUnmetIntgTol => {}
+            WarnOnUnmetIntgTol =>
+              warn!("Integration tolerances were not met within {} evaluations, estimated error is {}.",
+                    self.intg_max_evals, est_err),
+            FailOnUnmetIntgTol =>
+              fail!(format!("Integration tolerances were not met within {} evaluations, estimated error is {}.",
+                            self.intg_max_evals, est_err))
+          }
+        }
+        val
+      }
+      GaussLegendre(n) => gaussian_quadrature_rect(n, f, min_corner, max_corner),
+      Midpoint => midpoint_quadrature_rect(f, min_corner, max_corner)
+    }
   }
 
 } // RectMesh impl
@@ -324,27 +843,50 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
   
   #[inline(always)]
   fn num_oriented_element_shapes(&self) -> uint {
-    1u
+    self.oshape_side_lens.len()
   }
   
   #[inline(always)]
   fn oriented_shape_for_fe(&self, fe: FENum) -> OShape {
     assert!(*fe < self.num_fes);
-    OShape(0)
+    if self.oshape_side_lens.len() == 1 { return OShape(0); }
+    OShape(range(0, self.space_dims).fold(0u, |os, r| {
+      let preceeding_prod = if r == 0 { 1 } else { self.cumprods_axis_num_widths[r-1] };
+      os + self.axis_width_nums[r][*self.fe_mesh_coord(Dim(r), fe)] * preceeding_prod
+    }))
   }
   
   #[inline(always)]
   fn num_side_faces_for_oshape(&self, oshape: OShape) -> uint {
-    assert!(oshape == OShape(0));
+    assert!(*oshape < self.oshape_side_lens.len());
     self.num_side_faces_per_fe 
   }
   
   #[inline(always)]
   fn dependent_dim_for_oshape_side(&self, oshape: OShape, side_face: SideFace) -> Dim {
-    assert!(oshape == OShape(0));
+    assert!(*oshape < self.oshape_side_lens.len());
     assert!(*side_face < self.num_side_faces_per_fe);
     side_face_perp_axis(side_face)
   }
+
+  // The outward normal of a side is the positive or negative unit vector along the side's perpendicular axis.
+  fn outward_normal_for_oshape_side(&self, oshape: OShape, side_face: SideFace) -> ~[R] {
+    assert!(*oshape < self.oshape_side_lens.len());
+    assert!(*side_face < self.num_side_faces_per_fe);
+    let a = side_face_perp_axis(side_face);
+    let outward_sense = if side_face_is_lesser_on_perp_axis(side_face) { -1 as R } else { 1 as R };
+    vec::from_fn(self.space_dims, |r| if r == *a { outward_sense } else { 0 as R })
+  }
+
+  fn oshape_side_measure(&self, oshape: OShape, side_face: SideFace) -> R {
+    assert!(*side_face < self.num_side_faces_per_fe);
+    let a = side_face_perp_axis(side_face);
+    self.oshape_side_lens_wo_dim[*oshape][*a].iter().fold(1 as R, |prod, &len| prod * len)
+  }
+
+  fn oshape_interior_measure(&self, oshape: OShape) -> R {
+    self.side_lens_for_oshape(oshape).iter().fold(1 as R, |prod, &len| prod * len)
+  }
   
   #[inline]
   fn fe_inclusions_of_nb_side(&self, n: NBSideNum) -> NBSideInclusions {
@@ -354,11 +896,23 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
       let side_mesh_coords = cast::transmute_mut(self).side_mesh_coords_for_nb_side_num(n);
       self.fe_with_mesh_coords(side_mesh_coords)
     };
-    let greater_fe = FENum(*lesser_fe + (if *a == 0 {1} else {self.cumprods_mesh_ldims[*a-1]}));
-    NBSideInclusions {
-      nb_side_num: n,
-      fe1: lesser_fe,  side_face_in_fe1: greater_side_face_perp_to_axis(a),
-      fe2: greater_fe, side_face_in_fe2: lesser_side_face_perp_to_axis(a)
+    let fe_step_a = if *a == 0 {1} else {self.cumprods_mesh_ldims[*a-1]};
+    let last_coord_a = *self.mesh_ldims[*a]-1;
+    if *self.fe_mesh_coord(a, lesser_fe) == last_coord_a {
+      // On a periodic axis, the last side along the axis joins the lesser side of the first element along the axis
+      // to the greater side of the last one, which has the greater fe number.
+      let first_fe = FENum(*lesser_fe - last_coord_a * fe_step_a);
+      NBSideInclusions {
+        nb_side_num: n,
+        fe1: first_fe,  side_face_in_fe1: lesser_side_face_perp_to_axis(a),
+        fe2: lesser_fe, side_face_in_fe2: greater_side_face_perp_to_axis(a)
+      }
+    } else {
+      NBSideInclusions {
+        nb_side_num: n,
+        fe1: lesser_fe,  side_face_in_fe1: greater_side_face_perp_to_axis(a),
+        fe2: FENum(*lesser_fe + fe_step_a), side_face_in_fe2: lesser_side_face_perp_to_axis(a)
+      }
     }
   }
  
@@ -379,23 +933,20 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
     let a = side_face_perp_axis(side_face);
     let mcoord_a = self.fe_mesh_coord(a, fe);
     let is_lesser_side = side_face_is_lesser_on_perp_axis(side_face);
-    *mcoord_a == 0 && is_lesser_side || !is_lesser_side && *mcoord_a == *self.mesh_ldims[*a]-1
+    !self.periodic_axes[*a] && (*mcoord_a == 0 && is_lesser_side || !is_lesser_side && *mcoord_a == *self.mesh_ldims[*a]-1)
   }
   
   fn num_boundary_sides(&self) -> uint {
-    range(0, self.space_dims).fold(0u, |perp_axis_contrs, perp_axis| {
-      perp_axis_contrs +
-      range(0, self.space_dims).fold(1u, |prod, r| {
-        prod * if r == perp_axis { 2 } else { *self.mesh_ldims[r] }
-      })
-    })
+    self.num_boundary_sides_by_perp_axis().iter().fold(0u, |sum, &n| sum + n)
   }
 
   fn boundary_fes_by_oshape_side(&self) -> ~[~[~[FENum]]] { // oshape, side face -> fes
     let space_dims = domain_space_dims::<Mon>();
     let est_b_fes_per_side_face = self.num_boundary_sides() / self.num_side_faces_per_fe;
     
-    let mut res = ~[vec::from_elem(self.num_side_faces_per_fe, vec::with_capacity(est_b_fes_per_side_face))];
+    let mut res = vec::from_fn(self.num_oriented_element_shapes(), |_| {
+      vec::from_elem(self.num_side_faces_per_fe, vec::with_capacity(est_b_fes_per_side_face))
+    });
     
     let register_b_sides_on_perp_axis = |fe_lcoords: &[MeshCoord], perp_axis: Dim| {
       let fe = self.fe_with_mesh_coords(fe_lcoords);
@@ -404,14 +955,14 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
       let lesser_side_is_b_side = fe_lcoords[*perp_axis] == MeshCoord(0);
       if lesser_side_is_b_side {
         let sf = lesser_side_face_perp_to_axis(perp_axis);
-        res[0][*sf].push(fe);
+        res[*self.oriented_shape_for_fe(fe)][*sf].push(fe);
       }
       
       // If the fe is at the maximum mesh coord in the perp axis dim, then its greater side along the axis is on the boundary.
       let greater_side_is_b_side = fe_lcoords[*perp_axis] == MeshCoord(*self.mesh_ldims[*perp_axis] - 1);
       if greater_side_is_b_side {
         let sf = greater_side_face_perp_to_axis(perp_axis);
-        res[0][*sf].push(fe);
+        res[*self.oriented_shape_for_fe(fe)][*sf].push(fe);
       }
     };
 
@@ -419,6 +970,8 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
     let mut fe_lcoords = vec::from_elem(space_dims, MeshCoord(0));
 
     for perp_axis in range(0, space_dims) {
+      // Sides perpendicular to a periodic axis are all non-boundary sides.
+      if self.periodic_axes[perp_axis] { continue; }
      
       // Incrementor for fe_lcoords which will traverse all logical mesh values which have the min or max
       // logical coordinate value in the perpendicular axis dimension.  Bumps the first non-max coord and
@@ -455,8 +1008,7 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
   
   #[inline(always)]
   fn shape_diameter_inv(&self, oshape: OShape) -> R {
-    assert!(oshape == OShape(0));
-    self.rect_diameter_inv    
+    self.oshape_diameters_inv[*oshape]
   }
 
   #[inline(always)]
@@ -483,7 +1035,7 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
     let (fe_min_corner, fe_max_corner) = unsafe { // Mutate a work buffer to hold the fe min and max corners.
       cast::transmute_mut(self).fe_coord_min_max_corners(fe)
     };
-    space_adaptive_quadrature(&f, fe_min_corner, fe_max_corner, self.integration_rel_err, self.integration_abs_err)
+    self.quadrature_by_rule(self.fe_int_quad_rule(fe), &f, fe_min_corner, fe_max_corner)
   }
 
   #[inline]
@@ -492,9 +1044,9 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
       cast::transmute_mut(self).fe_coord_min_max_corners(fe)
     };
     let fe_int_origin = &fe_min_corner;
-    space_adaptive_quadrature(&|x: &[R]| { f(x) * mon.value_at_for_origin(x, *fe_int_origin) },
-                            fe_min_corner, fe_max_corner,
-                            self.integration_rel_err, self.integration_abs_err)
+    self.quadrature_by_rule(self.fe_int_quad_rule(fe),
+                            &|x: &[R]| { f(x) * mon.value_at_for_origin(x, *fe_int_origin) },
+                            fe_min_corner, fe_max_corner)
   }
   
   #[inline]
@@ -513,7 +1065,7 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
       f(x, x_rel)
     };
     
-    space_adaptive_quadrature(&integrand, fe_min_corner, fe_max_corner, self.integration_rel_err, self.integration_abs_err)
+    self.quadrature_by_rule(self.fe_int_quad_rule(fe), &integrand, fe_min_corner, fe_max_corner)
   }
 
   
@@ -522,39 +1074,45 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
     let lesser_side = side_face_is_lesser_on_perp_axis(side_face);
     
     let x = unsafe { cast::transmute_mut(self).intg_pt_trans_buf.mut_slice_from(0) };
+
+    // Compute the fe origin once into a work buffer, rather than for each integration point.
+    let fe_int_origin = unsafe { cast::transmute_mut(self).fe_int_origin_buf.mut_slice_from(0) };
+    self.fe_interior_origin_into(fe, fe_int_origin);
+    let side_a_coord = fe_int_origin[*a] + if lesser_side { 0 as R } else { self.fe_side_len(fe, a) };
     
     let integrand = |x_ss: &[R]| { // side space x, omitting dimension a and relative to the side's own origin
 
       // Translate the side space point to the global full space for g's evaluation.
       for r in range(0, *a) {
-        x[r] = self.fe_interior_origin_comp(fe, Dim(r)) + x_ss[r]; 
+        x[r] = fe_int_origin[r] + x_ss[r]; 
       }
-      x[*a] = self.fe_interior_origin_comp(fe, a) + if lesser_side { 0 as R } else { self.fe_side_lens[*a] };
+      x[*a] = side_a_coord;
       for r in range(*a+1, self.space_dims) {
-        x[r] = self.fe_interior_origin_comp(fe, Dim(r)) + x_ss[r-1]; 
+        x[r] = fe_int_origin[r] + x_ss[r-1]; 
       }
       
       g(x) * mon.value_at_reduced_dim_by_fixing(x_ss, a, 0 as R)
     };
     
-    space_adaptive_quadrature(&integrand, self.side_space_dims_zeros, self.fe_side_lens_wo_dim[*a], self.integration_rel_err, self.integration_abs_err)
+    let side_lens_wo_dim = &self.oshape_side_lens_wo_dim[*self.oriented_shape_for_fe(fe)][*a];
+    self.quadrature_by_rule(self.fe_side_quad_rule(fe), &integrand, self.side_space_dims_zeros, side_lens_wo_dim.as_slice())
   }
 
   #[inline]
   fn intg_facerel_poly_on_oshape_int<P:Polynomial<Mon>>(&self, p: &P, oshape: OShape) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     p.foldl_terms(0 as R, |sum, (coef, mon)| {
-      sum + coef * mon.integral_over_rect_at_origin(self.fe_side_lens)  
+      sum + coef * mon.integral_over_rect_at_origin(side_lens)  
     })
   }
 
 /*
   #[inline]
   fn intg_facerel_poly_x_facerel_poly_on_oshape_int<P:Polynomial<Mon>>(&self, p1: &P, p2: &P, oshape: OShape) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     p1.foldl_terms(0 as R, |sum, (coef1, mon1)| {
       p2.foldl_terms(sum, |sum, (coef2, mon2)| {
-        sum + coef1 * coef2 * (mon1*mon2).integral_over_rect_at_origin(self.fe_side_lens)
+        sum + coef1 * coef2 * (mon1*mon2).integral_over_rect_at_origin(side_lens)
       })
     })
   }
@@ -562,57 +1120,76 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
 
   #[inline]
   fn intg_facerel_poly_x_facerel_poly_on_oshape_side<P:Polynomial<Mon>>(&self, p1: &P, p2: &P, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
     p1.foldl_terms(0 as R, |sum, (coef1, mon1)| {
       p2.foldl_terms(sum, |sum, (coef2, mon2)| {
-        sum + coef1 * coef2 * (mon1*mon2).surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+        sum + coef1 * coef2 * (mon1*mon2).surface_integral_siderel_over_rect_side(side_lens, a)
       })
     })
   }
 
   #[inline]
   fn intg_facerel_mon_on_oshape_int(&self, mon: Mon, oshape: OShape) -> R {
-    assert!(oshape == OShape(0));
-    mon.integral_over_rect_at_origin(self.fe_side_lens)
+    let side_lens = self.side_lens_for_oshape(oshape);
+    mon.integral_over_rect_at_origin(side_lens)
   }
 
   #[inline]
   fn intg_facerel_mon_on_oshape_side(&self, mon: Mon, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
-    mon.surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+    mon.surface_integral_siderel_over_rect_side(side_lens, a)
   }
 
-/*
+  /// The product monomial is integrated exactly by the power rule along each axis of the face, using the fe side
+  /// lengths, with the axis perpendicular to a side dropped for side faces.
   #[inline]
-  fn intg_facerel_mon_x_facerel_poly_on_oshape_int<P:Polynomial<Mon>>(&self, mon: Mon, p: &P, oshape: OShape) -> R {
-    assert!(oshape == OShape(0));
-    p.foldl_terms(0 as R, |sum, (coef, p_mon)| {
-      sum + coef * (mon*p_mon).integral_over_rect_at_origin(self.fe_side_lens)
-    })
+  fn intg_facerel_mon_x_facerel_mon_on_oshape_face(&self, mon_1: Mon, mon_2: Mon, oshape: OShape, face: Face) -> R {
+    let side_lens = self.side_lens_for_oshape(oshape);
+    let mon = mon_1 * mon_2;
+    match face {
+      Interior => mon.integral_over_rect_at_origin(side_lens),
+      Side(side_face) => {
+        assert!(*side_face < self.num_side_faces_per_fe);
+        mon.surface_integral_siderel_over_rect_side(side_lens, side_face_perp_axis(side_face))
+      }
+    }
+  }
+
+  /// Each monomial product of the polynomial's terms is integrated exactly without forming a product polynomial.
+  #[inline]
+  fn intg_facerel_mon_x_facerel_poly_on_oshape_face<P:Polynomial<Mon>>(&self, mon: Mon, p: &P, oshape: OShape, face: Face) -> R {
+    let side_lens = self.side_lens_for_oshape(oshape);
+    match face {
+      Interior => {
+        p.foldl_terms(0 as R, |sum, (coef, p_mon)| {
+          sum + coef * (mon*p_mon).integral_over_rect_at_origin(side_lens)
+        })
+      }
+      Side(side_face) => self.intg_facerel_mon_x_facerel_poly_on_oshape_side(mon, p, oshape, side_face)
+    }
   }
-*/
 
   #[inline]
   fn intg_facerel_mon_x_facerel_poly_on_oshape_side<P:Polynomial<Mon>>(&self, mon: Mon, p: &P, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
     p.foldl_terms(0 as R, |sum, (coef, p_mon)| {
-      sum + coef * (mon*p_mon).surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+      sum + coef * (mon*p_mon).surface_integral_siderel_over_rect_side(side_lens, a)
     })
   }
 
   #[inline]
   fn intg_intrel_mon_x_siderel_mon_on_oshape_side(&self, int_mon: Mon, side_mon: Mon, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
     let is_lesser_side = side_face_is_lesser_on_perp_axis(side_face);
-    let side_intrel_a_coord = if is_lesser_side { 0 as R } else { self.fe_side_lens[*a] };
+    let side_intrel_a_coord = if is_lesser_side { 0 as R } else { side_lens[*a] };
 
     /* Here we break the interior-relative monomial on the side into the constant a-dim factor and the monomial
        of other dimension factors. Since the interior and side-relative coordinate systems differ only in dimension 
@@ -620,19 +1197,19 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
     let int_mon_dim_a_fac = pow(side_intrel_a_coord, *int_mon.exp(a) as uint);
     let int_mon_wo_dim_a_fac = int_mon.map_exp(a, |_| Deg(0));
 
-    int_mon_dim_a_fac * (int_mon_wo_dim_a_fac * side_mon).surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+    int_mon_dim_a_fac * (int_mon_wo_dim_a_fac * side_mon).surface_integral_siderel_over_rect_side(side_lens, a)
   }
 
   #[inline]
   fn intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(&self, side_mon: Mon, int_vmon: &VectorMonomial<Mon>, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
     match int_vmon.mon_dim() {
       Dim(r) if r == *a => {
         let int_vmon_mon = int_vmon.mon();
         let is_lesser_side = side_face_is_lesser_on_perp_axis(side_face);
-        let side_intrel_a_coord = if is_lesser_side { 0 as R } else { self.fe_side_lens[*a] };
+        let side_intrel_a_coord = if is_lesser_side { 0 as R } else { side_lens[*a] };
 
         /* Here we break the interior-relative monomial on the side into the constant a-dim factor and the monomial
            of other dimension factors. Since the interior and side-relative coordinate systems differ only in dimension 
@@ -641,10 +1218,11 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
         let int_vmon_mon_wo_dim_a_fac = int_vmon_mon.map_exp(a, |_| Deg(0));
 
         let outward_sense = if is_lesser_side { -1 as R } else { 1 as R };
+        let normal_sense = match self.normal_convention { Outward => outward_sense, Inward => -outward_sense };
 
-        outward_sense * 
+        normal_sense * 
         int_vmon_mon_dim_a_fac *
-        (int_vmon_mon_wo_dim_a_fac * side_mon).surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+        (int_vmon_mon_wo_dim_a_fac * side_mon).surface_integral_siderel_over_rect_side(side_lens, a)
       }
       _ => 0 as R
     }
@@ -652,14 +1230,14 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
 
 /*
   fn intg_siderel_poly_x_intrel_vmon_dot_normal_on_oshape_side<P:Polynomial<Mon>>(&self, p: &P, int_vmon: &VectorMonomial<Mon>, oshape: OShape, side_face: SideFace) -> R {
-    assert!(oshape == OShape(0));
+    let side_lens = self.side_lens_for_oshape(oshape);
     assert!(*side_face < self.num_side_faces_per_fe);
     let a = side_face_perp_axis(side_face);
     match int_vmon.mon_dim() {
       Dim(r) if r == *a => {
         let int_vmon_mon = int_vmon.mon();
         let is_lesser_side = side_face_is_lesser_on_perp_axis(side_face);
-        let side_intrel_a_coord = if is_lesser_side { 0 as R } else { self.fe_side_lens[*a] };
+        let side_intrel_a_coord = if is_lesser_side { 0 as R } else { side_lens[*a] };
 
         // Here we break the interior-relative monomial on the side into the constant a-dim factor and the monomial
         // of other dimension factors. Since the interior and side-relative coordinate systems differ only in dimension 
@@ -672,7 +1250,7 @@ impl<Mon:Monomial+RectIntegrable> Mesh<Mon>
         outward_sense * 
         int_vmon_mon_dim_a_fac *
         p.foldl_terms(0 as R, |sum, (coef, mon)| {
-          sum + coef * (int_vmon_mon_wo_dim_a_fac * mon).surface_integral_siderel_over_rect_side(self.fe_side_lens, a)
+          sum + coef * (int_vmon_mon_wo_dim_a_fac * mon).surface_integral_siderel_over_rect_side(side_lens, a)
         })
       }
       _ => 0 as R
@@ -0,0 +1,61 @@
+use rectangle_mesh::{RectMesh, MeshCoord, MeshError};
+use common::*;
+use monomial::Monomial;
+
+
+/// Rectangle mesh builder, specifying the mesh one axis at a time in order of increasing dimension, so that the
+/// bounds and number of divisions for an axis are given together instead of as components of parallel vectors.
+pub struct RectMeshBuilder {
+
+  axis_specs: ~[(R, R, uint)], // (min bound, max bound, number of divisions) by axis
+
+  integration_rel_err: R,
+  integration_abs_err: R,
+}
+
+
+impl RectMeshBuilder {
+
+  /// Start a mesh specification with no axes and the default numerical integration error tolerances.
+  pub fn new() -> RectMeshBuilder {
+    RectMeshBuilder {
+      axis_specs: ~[],
+      integration_rel_err: DEFAULT_INTEGRATION_REL_ERR,
+      integration_abs_err: DEFAULT_INTEGRATION_ABS_ERR,
+    }
+  }
+
+  /// Add the next axis, extending from min to max and divided into num_divisions element widths. The first axis
+  /// added is dimension 0, corresponding to the first variable of the monomials.
+  pub fn axis(self, min: R, max: R, num_divisions: uint) -> RectMeshBuilder {
+    let mut b = self;
+    b.axis_specs.push((min, max, num_divisions));
+    b
+  }
+
+  /// Set the relative error tolerance for numerical integration.
+  pub fn rel_err(self, rel_err: R) -> RectMeshBuilder {
+    let mut b = self;
+    b.integration_rel_err = rel_err;
+    b
+  }
+
+  /// Set the absolute error tolerance for numerical integration.
+  pub fn abs_err(self, abs_err: R) -> RectMeshBuilder {
+    let mut b = self;
+    b.integration_abs_err = abs_err;
+    b
+  }
+
+  /// Build the specified mesh, or describe the first inconsistency found in the specification, as for
+  /// RectMesh::try_new_with_intg_tols. The number of axes must equal the number of space dimensions of the monomial
+  /// type, each axis must have a minimum bound less than its maximum bound and at least one division, and the
+  /// integration error tolerances must be non-negative.
+  pub fn build<Mon:Monomial>(&self) -> Result<~RectMesh<Mon>, MeshError> {
+    let min_bounds = self.axis_specs.map(|&(min, _, _)| min);
+    let max_bounds = self.axis_specs.map(|&(_, max, _)| max);
+    let mesh_ldims = self.axis_specs.map(|&(_, _, n)| MeshCoord(n));
+    RectMesh::try_new_with_intg_tols(min_bounds, max_bounds, mesh_ldims,
+                                     self.integration_rel_err, self.integration_abs_err)
+  }
+}
@@ -0,0 +1,360 @@
+use common::*;
+use monomial::Monomial;
+use polynomial::Polynomial;
+use rectangle_mesh::{RectMesh, RectIntegrable, MeshCoord};
+use mesh::{Mesh, FENum, NBSideNum, OShape, SideFace};
+use wg_basis::{WGBasis, BasisElNum, FaceMonNum};
+use sparse_matrix::{SparseMatrix, Symmetric, General};
+use projection::Projector;
+
+use std::vec;
+use std::num::{sqrt, abs};
+
+
+/// Operations on weak Galerkin bases over rectangle meshes, which depend on RectMesh specifics such as locating the
+/// element containing a point, element grids, and nested uniform refinements.
+pub trait RectMeshWGBasis<Mon> {
+
+  /// Sample the classical gradient of the solution's interior polynomials over a regular grid of points
+  /// within each finite element. Each element is divided into samples_per_axis cells along each axis, and
+  /// the gradient of the element's interior polynomial is evaluated at each cell center. The gradients are
+  /// returned ordered by finite element, then by sample point within the element with the first axis
+  /// varying fastest.
+  fn sample_gradient_on_grid(&self, sol_coefs: &[R], samples_per_axis: uint) -> ~[~[R]];
+
+  /// Compute the maximum of |u_h - exact| over a tensor grid of pts_per_dim points per axis within each finite
+  /// element, where u_h is the solution's interior polynomial on the element. The points are the cell centers of
+  /// the division of each element into pts_per_dim cells per axis, as in sample_gradient_on_grid. This is only a
+  /// sampled approximation of the true supremum norm of the error, from below.
+  fn linf_error_at_quad_points(&self, sol_coefs: &[R], exact: |&[R]| -> R, pts_per_dim: uint) -> R;
+
+  /// Evaluate the solution's interior polynomial at the given point, on the finite element containing the point
+  /// as determined by RectMesh::fe_containing_point. Returns None if the point is outside the mesh.
+  fn eval_solution_at(&self, sol_coefs: &[R], x: &[R]) -> Option<R>;
+
+  /// Evaluate the classical gradient of the solution's interior polynomial at the given point, on the finite
+  /// element containing the point. Returns None if the point is outside the mesh.
+  fn eval_gradient_at(&self, sol_coefs: &[R], x: &[R]) -> Option<~[R]>;
+
+  /// Evaluate both the solution's interior polynomial and its classical gradient at the given point, locating the
+  /// finite element containing the point only once. Returns None if the point is outside the mesh.
+  fn eval_value_and_gradient_at(&self, sol_coefs: &[R], x: &[R]) -> Option<(R, ~[R])>;
+
+  /** Sample the solution's interior polynomials at n equally spaced points along the line segment from start to
+   *  end, inclusive of both endpoints, returning pairs of the arc length from start and the solution value at each
+   *  point. A point on a side shared by two finite elements is evaluated on the element determined by
+   *  RectMesh::fe_containing_point, so samples crossing element boundaries are assigned deterministically. The
+   *  segment must lie within the mesh.
+   */
+  fn sample_solution_on_line(&self, sol_coefs: &[R], start: &[R], end: &[R], n: uint) -> ~[(R, R)];
+
+  /** Build the prolongation matrix from the coarse basis to this basis, for two-level solvers, with entry (i,j) the
+   *  coefficient of fine basis element b_i in the representation of coarse basis element c_j. This basis' mesh must
+   *  be a nested uniform refinement of the coarse basis' mesh, with the same bounds and a multiple of its logical
+   *  dimensions along each axis, and the bases must have the same degree limits. A fine interior polynomial is the
+   *  projection of the coarse interior polynomial of the containing coarse element. A fine side polynomial is the
+   *  projection of the coarse side polynomial if the fine side lies on a coarse side, and of the trace of the coarse
+   *  interior polynomial otherwise. Since the coarse spaces are contained in the fine spaces these projections are
+   *  exact, so in particular coarse constants are mapped to fine constants. The matrix is General, with an explicit
+   *  zero in column 0 for each row, so that all rows are present even where no coarse element contributes.
+   */
+  fn build_prolongation(&self, coarse_basis: &WGBasis<Mon,RectMesh<Mon>>) -> SparseMatrix;
+
+  /** Interpolate the passed solution for this basis to the fine basis, for transferring solutions to a refined mesh
+   *  for visualization or as initial guesses. The fine basis' mesh must be a nested uniform refinement of this
+   *  basis' mesh, with the same degree limits, as for build_prolongation, which is applied to the coefficients.
+   *  The fine interior and side polynomials are thus the projections of the coarse interior polynomials of the
+   *  containing coarse elements or of the coarse side polynomials, so solutions have the same values on both meshes.
+   */
+  fn interpolate_to(&self, fine_basis: &WGBasis<Mon,RectMesh<Mon>>, coarse_sol_coefs: &[R]) -> ~[R];
+
+  /** Integrate the solution's interior polynomials over the slice of the mesh by the hyperplane perpendicular to
+   *  the given axis at the given coordinate, as for totals through a plane. The interior polynomial traces on the
+   *  plane are integrated exactly for each element which the plane passes through. A plane along the shared sides
+   *  of elements takes the traces of the elements of lesser mesh coordinate along the axis, as determined by
+   *  RectMesh::fe_containing_point. The coordinate must be within the mesh bounds along the axis.
+   */
+  fn integrate_solution_on_slice(&self, sol_coefs: &[R], axis: Dim, coord: R) -> R;
+
+  /** Assemble the weighted boundary mass matrix, with entry (i,j) the integral over the outside boundary of the
+   *  weight times the traces of the interior parts of basis elements b_i and b_j, as for Robin conditions and
+   *  boundary L2 projections. Since boundary sides support no basis elements, only interior supported elements of
+   *  finite elements having boundary sides have non-zero rows, the other rows holding only a zero diagonal entry so
+   *  that the result can be added to system matrices over the whole basis. The result is Symmetric, with only the
+   *  upper triangle stored.
+   */
+  fn assemble_boundary_mass(&self, weight: |&[R]| -> R) -> SparseMatrix;
+
+  /** Restrict a solution to the block of finite elements having mesh coordinates within the given inclusive bounds,
+   *  as for domain decomposition. The basis elements supported on the interiors of the block's elements and on their
+   *  non-boundary sides are returned with their solution coefficients, in order of basis element number. Sides
+   *  shared by two elements of the block appear once, and sides shared with elements outside the block are included.
+   */
+  fn restrict_to_element_block(&self, sol_coefs: &[R], min_coords: &[MeshCoord], max_coords: &[MeshCoord]) -> ~[(BasisElNum, R)];
+
+}
+
+impl<Mon:Monomial+RectIntegrable> RectMeshWGBasis<Mon> for WGBasis<Mon,RectMesh<Mon>> {
+
+  fn sample_gradient_on_grid(&self, sol_coefs: &[R], samples_per_axis: uint) -> ~[~[R]] {
+    let samples_per_fe = range(0, self.mesh.space_dims).fold(1u, |prod, _| prod * samples_per_axis);
+    let mut grads = vec::with_capacity(self.mesh.num_fes() * samples_per_fe);
+    let mut fe_grad_polys = ~[];
+    let mut polys_fe = None;
+    each_fe_grid_point(&*self.mesh, samples_per_axis, |fe, x_int_rel| {
+      if polys_fe != Some(fe) {
+        fe_grad_polys = self.fe_int_poly(fe, sol_coefs).gradient();
+        polys_fe = Some(fe);
+      }
+      grads.push(fe_grad_polys.map(|p| p.value_at(x_int_rel)));
+    });
+    grads
+  }
+
+  fn linf_error_at_quad_points(&self, sol_coefs: &[R], exact: |&[R]| -> R, pts_per_dim: uint) -> R {
+    let mut max_err = 0 as R;
+    let mut x = vec::from_elem(self.mesh.space_dims, 0 as R);
+    each_fe_grid_point(&*self.mesh, pts_per_dim, |fe, x_int_rel| {
+      for r in range(0, x.len()) {
+        x[r] = self.mesh.fe_interior_origin_comp(fe, Dim(r)) + x_int_rel[r];
+      }
+      let err = abs(self.fe_int_poly(fe, sol_coefs).value_at(x_int_rel) - exact(x.as_slice()));
+      if err > max_err { max_err = err; }
+    });
+    max_err
+  }
+
+  fn eval_solution_at(&self, sol_coefs: &[R], x: &[R]) -> Option<R> {
+    self.mesh.fe_containing_point(x).map(|fe| {
+      self.fe_int_poly(fe, sol_coefs).value_at(fe_int_rel_point(&*self.mesh, fe, x).as_slice())
+    })
+  }
+
+  fn eval_gradient_at(&self, sol_coefs: &[R], x: &[R]) -> Option<~[R]> {
+    self.mesh.fe_containing_point(x).map(|fe| {
+      let x_int_rel = fe_int_rel_point(&*self.mesh, fe, x);
+      self.fe_int_poly(fe, sol_coefs).gradient().map(|p| p.value_at(x_int_rel.as_slice()))
+    })
+  }
+
+  fn eval_value_and_gradient_at(&self, sol_coefs: &[R], x: &[R]) -> Option<(R, ~[R])> {
+    self.mesh.fe_containing_point(x).map(|fe| {
+      let x_int_rel = fe_int_rel_point(&*self.mesh, fe, x);
+      let int_poly = self.fe_int_poly(fe, sol_coefs);
+      (int_poly.value_at(x_int_rel.as_slice()), int_poly.gradient().map(|p| p.value_at(x_int_rel.as_slice())))
+    })
+  }
+
+  fn sample_solution_on_line(&self, sol_coefs: &[R], start: &[R], end: &[R], n: uint) -> ~[(R, R)] {
+    let d = self.mesh.space_dims;
+    assert!(start.len() == d && end.len() == d);
+    if n < 2 { fail!("At least two samples are required along a line segment."); }
+    let len = sqrt(range(0, d).fold(0 as R, |sum, r| sum + sq(end[r] - start[r])));
+    let mut x = vec::from_elem(d, 0 as R);
+    vec::from_fn(n, |i| {
+      let t = (i as R) / ((n-1) as R);
+      for r in range(0, d) {
+        x[r] = if i == n-1 { end[r] } else { start[r] + t * (end[r] - start[r]) };
+      }
+      match self.eval_solution_at(sol_coefs, x.as_slice()) {
+        Some(v) => (t * len, v),
+        None => fail!(format!("Line sample point {:?} is outside the mesh.", x))
+      }
+    })
+  }
+
+  fn build_prolongation(&self, coarse_basis: &WGBasis<Mon,RectMesh<Mon>>) -> SparseMatrix {
+    let (fine, coarse) = (&*self.mesh, &*coarse_basis.mesh);
+    let d = fine.space_dims;
+    if coarse.space_dims != d || coarse.min_bounds != fine.min_bounds || coarse.max_bounds != fine.max_bounds ||
+       coarse.periodic_axes != fine.periodic_axes ||
+       range(0, d).any(|r| *fine.mesh_ldims[r] % *coarse.mesh_ldims[r] != 0) ||
+       fine.num_oriented_element_shapes() != 1 || coarse.num_oriented_element_shapes() != 1 {
+      fail!("Prolongation requires the mesh to be a nested uniform refinement of the coarse mesh.");
+    }
+    if self.int_polys_deg_lim != coarse_basis.int_polys_deg_lim || self.side_polys_deg_lim != coarse_basis.side_polys_deg_lim {
+      fail!("Prolongation requires the coarse basis to have the same degree limits.");
+    }
+    let oshape = OShape(0);
+
+    // The coarse element containing a fine element, found from its center, and a work buffer for coarse element
+    // interior origins.
+    let coarse_fe_for = |fe: FENum| -> FENum {
+      let center = vec::from_fn(d, |r| fine.fe_interior_origin_comp(fe, Dim(r)) + fine.fe_side_len(fe, Dim(r))/2.);
+      coarse.fe_containing_point(center.as_slice()).unwrap()
+    };
+    let mut c_origin = vec::from_elem(d, 0 as R);
+
+    let mut projector = Projector::new(self);
+    let mut triplets = vec::from_fn(self.total_els, |i| (i, 0u, 0 as R));
+
+    // Interior supported fine elements, from the interior polynomials of the containing coarse elements.
+    for fe in range(0, fine.num_fes()) { let fe = FENum(fe);
+      let cfe = coarse_fe_for(fe);
+      coarse.fe_interior_origin_into(cfe, c_origin.as_mut_slice());
+      for (c_monn, c_mon) in coarse_basis.int_mons.iter().enumerate() {
+        let c_el = coarse_basis.int_mon_el_num(cfe, FaceMonNum(c_monn));
+        let projs = projector.projs_to_int_supp_approx_spaces(|x| c_mon.value_at_for_origin(x, c_origin.as_slice()),
+                                                              [fe], oshape);
+        for monn in range(0, self.mons_per_fe_int) {
+          let (coef, _) = projs[0].term(monn);
+          if coef != 0 as R { triplets.push((*self.int_mon_el_num(fe, FaceMonNum(monn)), *c_el, coef)); }
+        }
+      }
+    }
+
+    // Side supported fine elements, from the coarse side polynomials for fine sides on coarse sides, else from the
+    // interior polynomial of the coarse element containing the fine side. Side-relative and interior-relative
+    // coordinates differ only along the side's perpendicular axis, on which side monomials do not depend, so coarse
+    // side monomials are evaluated relative to the interior origin of the first including coarse element.
+    for nbs in range(0, fine.num_nb_sides()) { let nbs = NBSideNum(nbs);
+      let incls = fine.fe_inclusions_of_nb_side(nbs);
+      let (cfe_1, cfe_2) = (coarse_fe_for(incls.fe1), coarse_fe_for(incls.fe2));
+      coarse.fe_interior_origin_into(cfe_1, c_origin.as_mut_slice());
+      let c_fns: ~[(BasisElNum, Mon)] =
+        if cfe_1 == cfe_2 {
+          coarse_basis.int_mons.iter().enumerate().map(|(c_monn, c_mon)| {
+            (coarse_basis.int_mon_el_num(cfe_1, FaceMonNum(c_monn)), c_mon.clone())
+          }).collect()
+        } else {
+          let c_nbs = coarse.nb_side_num_for_fe_side(cfe_1, incls.side_face_in_fe1);
+          coarse_basis.side_mons_for_oshape_side(oshape, incls.side_face_in_fe1).iter().enumerate().map(|(c_monn, c_mon)| {
+            (coarse_basis.nb_side_mon_el_num(c_nbs, FaceMonNum(c_monn)), c_mon.clone())
+          }).collect()
+        };
+      for &(c_el, ref c_mon) in c_fns.iter() {
+        let projs = projector.projs_to_side_supp_approx_spaces(|x| c_mon.value_at_for_origin(x, c_origin.as_slice()),
+                                                               [incls.fe1], oshape, incls.side_face_in_fe1);
+        for monn in range(0, self.mons_per_fe_side) {
+          let (coef, _) = projs[0].term(monn);
+          if coef != 0 as R { triplets.push((*self.nb_side_mon_el_num(nbs, FaceMonNum(monn)), *c_el, coef)); }
+        }
+      }
+    }
+
+    SparseMatrix::from_triplets(self.total_els, triplets, General)
+  }
+
+  fn interpolate_to(&self, fine_basis: &WGBasis<Mon,RectMesh<Mon>>, coarse_sol_coefs: &[R]) -> ~[R] {
+    if coarse_sol_coefs.len() != self.total_els {
+      fail!(format!("Expected {} coarse solution coefficients, got {}.", self.total_els, coarse_sol_coefs.len()));
+    }
+    let p = fine_basis.build_prolongation(self);
+    let mut fine_sol_coefs = vec::from_elem(fine_basis.num_els(), 0 as R);
+    for (r, c, v) in p.entries() {
+      fine_sol_coefs[r] += v * coarse_sol_coefs[c];
+    }
+    fine_sol_coefs
+  }
+
+  fn integrate_solution_on_slice(&self, sol_coefs: &[R], axis: Dim, coord: R) -> R {
+    let mesh = &*self.mesh;
+    let d = mesh.space_dims;
+    assert!(*axis < d);
+    // Find the layer of elements along the axis crossed by the plane, from the element containing a plane point.
+    let layer = {
+      let mut pt = mesh.min_bounds.clone();
+      pt[*axis] = coord;
+      match mesh.fe_containing_point(pt.as_slice()) {
+        Some(fe) => mesh.fe_mesh_coord(axis, fe),
+        None => fail!(format!("Slice coordinate {} is outside the mesh along axis {}.", coord, *axis))
+      }
+    };
+    let mut side_lens = vec::from_elem(d, 0 as R);
+    let mut total = 0 as R;
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      if mesh.fe_mesh_coord(axis, fe) != layer { continue; }
+      let x_a = coord - mesh.fe_interior_origin_comp(fe, axis);
+      for r in range(0, d) { side_lens[r] = mesh.fe_side_len(fe, Dim(r)); }
+      total += self.fe_int_poly(fe, sol_coefs).foldl_terms(0 as R, |sum, (coef, mon)| {
+        let trace_factor = pow(x_a, *mon.exp(axis) as uint);
+        sum + coef * trace_factor * mon.map_exp(axis, |_| Deg(0)).surface_integral_siderel_over_rect_side(side_lens.as_slice(), axis)
+      });
+    }
+    total
+  }
+
+  fn assemble_boundary_mass(&self, weight: |&[R]| -> R) -> SparseMatrix {
+    let mesh = &*self.mesh;
+    let one: Mon = Monomial::one();
+    let n = self.mons_per_fe_int;
+    let mut triplets = vec::from_fn(self.total_els, |i| (i, i, 0 as R));
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let num_sides = mesh.num_side_faces_for_oshape(mesh.oriented_shape_for_fe(fe));
+      let b_sides: ~[SideFace] = range(0, num_sides).map(|sf| SideFace(sf)).filter(|&sf| mesh.is_boundary_side(fe, sf)).collect();
+      if b_sides.len() == 0 { continue; }
+      for i in range(0, n) {
+        let (r, mon_i) = (*self.int_mon_el_num(fe, FaceMonNum(i)), self.int_mons[i].clone());
+        for j in range(i, n) {
+          let (c, mon_j) = (*self.int_mon_el_num(fe, FaceMonNum(j)), self.int_mons[j].clone());
+          let weighted_trace_prod = |x: &[R]| {
+            let x_int_rel = fe_int_rel_point(&*self.mesh, fe, x);
+            weight(x) * mon_i.value_at(x_int_rel) * mon_j.value_at(x_int_rel)
+          };
+          let val = b_sides.iter().fold(0 as R, |sum, &sf| {
+            sum + mesh.intg_global_fn_x_facerel_mon_on_fe_side(|x| weighted_trace_prod(x), one.clone(), fe, sf)
+          });
+          triplets.push((r, c, val));
+        }
+      }
+    }
+    SparseMatrix::from_triplets(self.total_els, triplets, Symmetric)
+  }
+
+  fn restrict_to_element_block(&self, sol_coefs: &[R], min_coords: &[MeshCoord], max_coords: &[MeshCoord]) -> ~[(BasisElNum, R)] {
+    let mesh = &*self.mesh;
+    assert!(min_coords.len() == mesh.space_dims && max_coords.len() == mesh.space_dims);
+    for r in range(0, mesh.space_dims) {
+      if min_coords[r] > max_coords[r] || *max_coords[r] >= *mesh.mesh_ldims[r] {
+        fail!(format!("Invalid element block bounds {:?} to {:?}.", min_coords, max_coords));
+      }
+    }
+    let mut els = ~[];
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let in_block = range(0, mesh.space_dims).all(|r| {
+        let c = mesh.fe_mesh_coord(Dim(r), fe);
+        min_coords[r] <= c && c <= max_coords[r]
+      });
+      if !in_block { continue; }
+      for monn in range(0, self.mons_per_fe_int) {
+        els.push(self.int_mon_el_num(fe, FaceMonNum(monn)));
+      }
+      for sf in range(0, mesh.num_side_faces_for_oshape(mesh.oriented_shape_for_fe(fe))) { let sf = SideFace(sf);
+        if mesh.is_boundary_side(fe, sf) { continue; }
+        for monn in range(0, self.mons_per_fe_side) {
+          els.push(self.fe_side_mon_el_num(fe, sf, FaceMonNum(monn)));
+        }
+      }
+    }
+    els.sort();
+    els.dedup();
+    els.move_iter().map(|el| (el, sol_coefs[*el])).collect()
+  }
+
+}  // RectMeshWGBasis impl for WGBasis
+
+// Convert a global point to coordinates relative to the interior origin of the given finite element.
+fn fe_int_rel_point<Mon:Monomial+RectIntegrable>(mesh: &RectMesh<Mon>, fe: FENum, x: &[R]) -> ~[R] {
+  vec::from_fn(x.len(), |r| x[r] - mesh.fe_interior_origin_comp(fe, Dim(r)))
+}
+
+// Call f with each finite element and, in turn, each point of its regular grid of cell centers having
+// pts_per_axis cells per axis. Points are passed relative to the interior origin (minimum corner) of
+// the element, with the first axis varying fastest.
+fn each_fe_grid_point<Mon:Monomial+RectIntegrable>(mesh: &RectMesh<Mon>, pts_per_axis: uint, f: |FENum, &[R]|) {
+  if pts_per_axis == 0 { fail!("At least one grid point per axis is required."); }
+  let space_dims = mesh.space_dims;
+  let pts_per_fe = range(0, space_dims).fold(1u, |prod, _| prod * pts_per_axis);
+  let mut x = vec::from_elem(space_dims, 0 as R);
+  for fe in range(0, mesh.num_fes()) {
+    for pt in range(0, pts_per_fe) {
+      let mut rem = pt;
+      for r in range(0, space_dims) {
+        let cell = rem % pts_per_axis;
+        rem /= pts_per_axis;
+        x[r] = ((cell as R) + 0.5) * mesh.fe_side_len(FENum(fe), Dim(r)) / (pts_per_axis as R);
+      }
+      f(FENum(fe), x.as_slice());
+    }
+  }
+}
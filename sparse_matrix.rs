@@ -1,16 +1,24 @@
-use common::{R};
+use common::{R, sq};
 use la;
 use la::lapack_int;
 
 use extra::c_vec::CVec;
 use std::cast::transmute;
+use std::cmp::max;
 use std::ptr;
+use std::vec;
+use std::num::{abs, sqrt};
 use std::libc::{c_ulong};
+use std::f64;
+use std::io::buffered::BufferedReader;
+use std::from_str::FromStr;
 
 /// Sparse matrix type, with compressed sparse row storage, 3-array variation (CSR3).
 /// Values must be pushed into the matrix in increasing order of their (row, column)
 /// pairs, with the row being most significant, and with each row being represented by
-/// at least one pushed value (which may be 0).
+/// at least one pushed value (which may be 0). For StructurallySymmetric matrices, the zero
+/// mirror entries (j,i) of pushed entries (i,j) whose mirrors were not also pushed are added
+/// by finish_struct_sym, which should be called once all values have been pushed.
 pub struct SparseMatrix {
 
   priv values: CVec<R>,
@@ -23,6 +31,9 @@ pub struct SparseMatrix {
   priv matrix_type: MatrixType,
 }
 
+/// Version of the binary CSR format written by SparseMatrix::write_csr_binary.
+pub static CSR_BINARY_FORMAT_VERSION: u64 = 1;
+
 pub enum MatrixType {
   Symmetric,             // symmetric with values in upper triangle
   StructurallySymmetric, // structurally symmetric, m_{i,j} present iff m_{j,i} present.
@@ -47,6 +58,31 @@ impl SparseMatrix {
     }
   }
 
+  /// Construct a matrix with the given number of rows from (row, column, value) triplets in any order, with the values
+  /// of triplets having the same row and column being summed. Every row must have at least one triplet, and for a
+  /// Symmetric matrix only upper triangle triplets are allowed. A StructurallySymmetric matrix is completed with any
+  /// missing zero mirror entries, see finish_struct_sym.
+  pub fn from_triplets(num_rows: uint, triplets: &[(uint, uint, R)], mtype: MatrixType) -> SparseMatrix {
+    let mut sorted = triplets.to_owned();
+    sorted.sort_by(|&(r1, c1, _), &(r2, c2, _)| (r1, c1).cmp(&(r2, c2)));
+    let mut m = SparseMatrix::new_with_capacities(sorted.len(), num_rows, mtype);
+    let mut i = 0;
+    while i < sorted.len() {
+      let (r, c, mut val) = sorted[i];
+      if r >= num_rows { fail!("Triplet row index out of range.") }
+      match mtype { Symmetric if c < r => fail!("Only upper triangle triplets are allowed for a symmetric matrix."), _ => {} }
+      i += 1;
+      while i < sorted.len() && sorted[i].n0() == r && sorted[i].n1() == c {
+        val += sorted[i].n2();
+        i += 1;
+      }
+      m.push(r, c, val);
+    }
+    if m.num_rows != num_rows { fail!("Every row of the matrix must have at least one triplet.") }
+    m.finish_struct_sym();
+    m
+  }
+
   #[inline]
   pub fn push(&mut self, r: uint, c: uint, val: R) {
     match r {
@@ -70,6 +106,30 @@ impl SparseMatrix {
     self.num_values += 1;
   }
 
+  /// For a StructurallySymmetric matrix, add a zero mirror entry (j,i) for each stored entry (i,j) whose mirror is
+  /// not stored, completing the symmetric structure required by the structurally symmetric solver. Mirrors in rows
+  /// beyond the last pushed row add those rows. This should be called once all values have been pushed, and before
+  /// the matrix is solved or written. Matrices of other types are not modified.
+  pub fn finish_struct_sym(&mut self) {
+    match self.matrix_type { StructurallySymmetric => {}, _ => return }
+    let mut mirrors = ~[];
+    for (r, c, _) in self.entries() {
+      if r != c && !self.has_entry(c, r) { mirrors.push((c, r, 0 as R)); }
+    }
+    if mirrors.len() == 0 { return; }
+    let num_rows = mirrors.iter().fold(self.num_rows, |n, &(r, _, _)| max(n, r+1));
+    let mut triplets: ~[(uint, uint, R)] = self.entries().collect();
+    triplets.push_all_move(mirrors);
+    // The rebuilt matrix has values capacity for the mirrors, and its structure is already complete.
+    *self = SparseMatrix::from_triplets(num_rows, triplets, StructurallySymmetric);
+  }
+
+  #[inline]
+  fn has_entry(&self, r: uint, c: uint) -> bool {
+    r < self.num_rows &&
+      range(*self.row_first_value_ixs.get(r) as uint, self.row_end_value_ix(r)).any(|i| *self.value_cols.get(i) as uint == c)
+  }
+
   pub fn num_rows(&self) -> uint {
     self.num_rows
   }
@@ -94,6 +154,330 @@ impl SparseMatrix {
     0 as R
   }
 
+  /// Get the diagonal entries of the (square) matrix, with 0 for diagonal entries which are not stored.
+  pub fn diagonal(&self) -> ~[R] {
+    vec::from_fn(self.num_rows(), |i| self.get(i,i))
+  }
+
+  /// Multiply the (square) matrix by the passed vector, returning the product vector. For a Symmetric matrix, the
+  /// stored upper triangle values also contribute at their mirrored positions in the lower triangle.
+  pub fn mat_vec(&self, x: &[R]) -> ~[R] {
+    if x.len() != self.num_rows { fail!("Vector length does not match sparse matrix dimensions.") }
+    let sym = match self.matrix_type { Symmetric => true, _ => false };
+    let mut res = vec::from_elem(self.num_rows, 0 as R);
+    for r in range(0, self.num_rows) {
+      let first_val_ix = *self.row_first_value_ixs.get(r) as uint;
+      let next_row_begin = if r == self.num_rows-1 { self.num_values } else { *self.row_first_value_ixs.get(r+1) as uint };
+      for i in range(first_val_ix, next_row_begin) {
+        let (c, val) = (*self.value_cols.get(i) as uint, *self.values.get(i));
+        res[r] += val * x[c];
+        if sym && c != r {
+          res[c] += val * x[r];
+        }
+      }
+    }
+    res
+  }
+
+  /// Iterate over the stored entries of the matrix as (row, column, value) triplets, in order of increasing row and
+  /// then column. For a Symmetric matrix only the stored upper triangle entries are produced.
+  pub fn entries<'a>(&'a self) -> EntryIter<'a> {
+    EntryIter { m: self, r: 0u, i: 0u }
+  }
+
+  /// Iterate over the stored entries of the matrix as in entries, beginning with the entry at the given position in
+  /// that order, such as the first entry stored after some earlier stage of the matrix's construction.
+  pub fn entries_from<'a>(&'a self, first_entry: uint) -> EntryIter<'a> {
+    // Find the row of the entry as the last row beginning at or before it, by bisection.
+    let (mut lo, mut hi) = (0u, self.num_rows);
+    while hi > lo + 1 {
+      let mid = (lo + hi) / 2;
+      if *self.row_first_value_ixs.get(mid) as uint <= first_entry { lo = mid; } else { hi = mid; }
+    }
+    EntryIter { m: self, r: lo, i: first_entry }
+  }
+
+  /// Form the Galerkin product P^T A P of this square matrix A with the matrix P having this matrix's number of rows
+  /// and the given number of columns, such as the coarse grid operator for a prolongation P in two-level methods.
+  /// The product is Symmetric, holding its upper triangle, if this matrix is Symmetric, and General otherwise. The
+  /// rows of AP and then of P^T(AP) are accumulated sparsely, as combinations of the rows of P and of AP, so the cost
+  /// is proportional to the number of multiplications of nonzero entries rather than to the dense product.
+  pub fn galerkin_product(&self, p: &SparseMatrix, p_num_cols: uint) -> SparseMatrix {
+    let n = self.num_rows();
+    if p.num_rows() != n { fail!("Prolongation rows do not match the sparse matrix dimensions.") }
+    let sym = match self.matrix_type { Symmetric => true, _ => false };
+
+    // The nonzero entries of P by row and by column, and the rows of A including any unstored lower triangle.
+    let mut p_rows: ~[~[(uint, R)]] = vec::from_elem(n, ~[]);
+    let mut p_cols: ~[~[(uint, R)]] = vec::from_elem(p_num_cols, ~[]);
+    for (r, c, v) in p.entries() {
+      if c >= p_num_cols { fail!("Prolongation column index out of range.") }
+      if v != 0 as R { p_rows[r].push((c, v)); p_cols[c].push((r, v)); }
+    }
+    let mut a_rows: ~[~[(uint, R)]] = vec::from_elem(n, ~[]);
+    for (r, c, v) in self.entries() {
+      a_rows[r].push((c, v));
+      if sym && c != r { a_rows[c].push((r, v)); }
+    }
+
+    let mut acc = RowAccumulator::new(p_num_cols);
+
+    // Row r of AP is the combination of the rows of P weighted by the entries of row r of A.
+    let mut ap_rows: ~[~[(uint, R)]] = vec::with_capacity(n);
+    for r in range(0, n) {
+      for &(k, a_rk) in a_rows[r].iter() {
+        for &(j, p_kj) in p_rows[k].iter() { acc.add(j, a_rk * p_kj); }
+      }
+      ap_rows.push(acc.take_row(0));
+    }
+
+    // Row i of P^T(AP) is the combination of the rows of AP weighted by the entries of column i of P. The diagonal
+    // entry is always included so that every row is represented.
+    let mut pt_ap_rows: ~[~[(uint, R)]] = vec::with_capacity(p_num_cols);
+    let mut num_values = 0u;
+    for i in range(0, p_num_cols) {
+      acc.add(i, 0 as R);
+      for &(r, p_ri) in p_cols[i].iter() {
+        for &(j, ap_rj) in ap_rows[r].iter() { acc.add(j, p_ri * ap_rj); }
+      }
+      let row = acc.take_row(if sym { i } else { 0 });
+      num_values += row.len();
+      pt_ap_rows.push(row);
+    }
+
+    let mut m = SparseMatrix::new_with_capacities(num_values, p_num_cols, if sym { Symmetric } else { General });
+    for i in range(0, p_num_cols) {
+      for &(j, v) in pt_ap_rows[i].iter() {
+        if v != 0 as R || i == j { m.push(i, j, v); }
+      }
+    }
+    m
+  }
+
+  /// Compute the Frobenius norm of the matrix. For a Symmetric matrix, the stored off-diagonal entries are counted
+  /// twice to include their unstored mirror entries in the lower triangle.
+  pub fn frobenius_norm(&self) -> R {
+    let sym = match self.matrix_type { Symmetric => true, _ => false };
+    sqrt(self.entries().fold(0 as R, |sum, (r, c, v)| {
+      sum + if sym && r != c { 2. * sq(v) } else { sq(v) }
+    }))
+  }
+
+  /// Get the maximum absolute value of the entries of the matrix.
+  pub fn max_abs(&self) -> R {
+    self.entries().fold(0 as R, |max, (_, _, v)| { let a = abs(v); if a > max { a } else { max } })
+  }
+
+  /// Compute the bandwidth of the matrix, the maximum of |r - c| over the stored entries (r,c). For a Symmetric
+  /// matrix this is the same for the stored upper triangle as for the full matrix.
+  pub fn bandwidth(&self) -> uint {
+    self.entries().fold(0u, |max, (r, c, _)| {
+      let dist = if r > c { r - c } else { c - r };
+      if dist > max { dist } else { max }
+    })
+  }
+
+  /// Determine whether the (square) matrix is strictly diagonally dominant by rows, with each diagonal entry exceeding
+  /// in magnitude the sum of the magnitudes of the other entries of its row, which guarantees convergence of the
+  /// Jacobi and Gauss-Seidel iterations. For a Symmetric matrix, the stored off-diagonal entries also count for the
+  /// rows of their mirrored positions in the lower triangle.
+  pub fn is_diagonally_dominant(&self) -> bool {
+    let n = self.num_rows();
+    let sym = match self.matrix_type { Symmetric => true, _ => false };
+    let mut diag = vec::from_elem(n, 0 as R);
+    let mut off_diag_sums = vec::from_elem(n, 0 as R);
+    for (r, c, v) in self.entries() {
+      if r == c { diag[r] = abs(v); }
+      else {
+        off_diag_sums[r] += abs(v);
+        if sym { off_diag_sums[c] += abs(v); }
+      }
+    }
+    range(0, n).all(|i| diag[i] > off_diag_sums[i])
+  }
+
+  /// Compute the symmetrically scaled matrix D^(-1/2) A D^(-1/2) of this (square) matrix A, where D is the diagonal
+  /// of A, returning the scaled matrix, which has unit diagonal and the same type and structure as A, and the
+  /// scaling vector of the diagonal entries of D^(-1/2). A solution x of A x = b is recovered from the solution y of
+  /// the scaled system D^(-1/2) A D^(-1/2) y = D^(-1/2) b as x = D^(-1/2) y. The diagonal entries must be positive.
+  pub fn symmetric_diagonal_scaling(&self) -> (SparseMatrix, ~[R]) {
+    let n = self.num_rows();
+    let scaling = vec::from_fn(n, |i| {
+      let d = self.get(i,i);
+      if d <= 0 as R { fail!(format!("Diagonal entry {} of value {} is not positive, cannot scale matrix.", i, d)); }
+      1. / sqrt(d)
+    });
+    let mut scaled = SparseMatrix::new_with_capacities(self.num_values(), n, self.matrix_type);
+    for (r, c, v) in self.entries() {
+      scaled.push(r, c, scaling[r] * v * scaling[c]);
+    }
+    (scaled, scaling)
+  }
+
+  /// Write the matrix in a binary little-endian CSR format which can be read back by read_csr_binary. The format
+  /// consists of the format version, the number of rows n, the number of values nnz and the matrix type, followed
+  /// by the n+1 row beginning indexes (ia, capped with nnz), the nnz column numbers (ja) and the nnz values (a).
+  pub fn write_csr_binary<W:Writer>(&self, w: &mut W) {
+    w.write_le_u64(CSR_BINARY_FORMAT_VERSION);
+    w.write_le_u64(self.num_rows as u64);
+    w.write_le_u64(self.num_values as u64);
+    w.write_le_u64(match self.matrix_type { Symmetric => 0, StructurallySymmetric => 1, General => 2 });
+    for r in range(0, self.num_rows) {
+      w.write_le_i64(*self.row_first_value_ixs.get(r) as i64);
+    }
+    w.write_le_i64(self.num_values as i64);
+    for i in range(0, self.num_values) {
+      w.write_le_i64(*self.value_cols.get(i) as i64);
+    }
+    for i in range(0, self.num_values) {
+      w.write_le_f64(*self.values.get(i));
+    }
+  }
+
+  /// Read a matrix previously written by write_csr_binary, failing if the format version is not supported or the
+  /// structure read is not valid.
+  pub fn read_csr_binary<Rd:Reader>(rd: &mut Rd) -> SparseMatrix {
+    let version = rd.read_le_u64();
+    if version != CSR_BINARY_FORMAT_VERSION {
+      fail!(format!("Unsupported binary CSR format version {}, expected {}.", version, CSR_BINARY_FORMAT_VERSION));
+    }
+    let num_rows = rd.read_le_u64() as uint;
+    let num_values = rd.read_le_u64() as uint;
+    let mtype = match rd.read_le_u64() {
+      0 => Symmetric, 1 => StructurallySymmetric, 2 => General,
+      t => fail!(format!("Unknown matrix type code {} in binary CSR data.", t))
+    };
+    let mut m = SparseMatrix::new_with_capacities(num_values, num_rows, mtype);
+    for r in range(0, num_rows) {
+      *m.row_first_value_ixs.get_mut(r) = rd.read_le_i64() as lapack_int;
+    }
+    if rd.read_le_i64() != num_values as i64 { fail!("Row beginning indexes cap does not match the number of values."); }
+    for i in range(0, num_values) {
+      *m.value_cols.get_mut(i) = rd.read_le_i64() as lapack_int;
+    }
+    for i in range(0, num_values) {
+      *m.values.get_mut(i) = rd.read_le_f64();
+    }
+    m.num_rows = num_rows;
+    m.num_values = num_values;
+    match m.validate() {
+      Ok(_) => m,
+      Err(msg) => fail!(format!("Invalid binary CSR data: {}", msg))
+    }
+  }
+
+  /// Write the matrix in the MatrixMarket coordinate real format, with 1-based row and column numbers and values in
+  /// full precision. A Symmetric matrix is written with the symmetric qualifier as its lower triangle, as the format
+  /// requires, and other matrices are written as general. The matrix type is also recorded in a comment line, so
+  /// that read_matrix_market can restore a StructurallySymmetric matrix as such.
+  pub fn write_matrix_market<W:Writer>(&self, w: &mut W) {
+    let (sym, type_name) = match self.matrix_type {
+      Symmetric => (true, "Symmetric"),
+      StructurallySymmetric => (false, "StructurallySymmetric"),
+      General => (false, "General"),
+    };
+    w.write_line(format!("%%MatrixMarket matrix coordinate real {}", if sym { "symmetric" } else { "general" }));
+    w.write_line(format!("% wgfem matrix type: {}", type_name));
+    w.write_line(format!("{} {} {}", self.num_rows, self.num_rows, self.num_values));
+    for (r, c, v) in self.entries() {
+      let (mm_r, mm_c) = if sym { (c+1, r+1) } else { (r+1, c+1) };
+      w.write_line(format!("{} {} {}", mm_r, mm_c, f64::to_str_exp_digits(v, 17, false)));
+    }
+  }
+
+  /// Read a square matrix in the MatrixMarket coordinate real format, such as written by write_matrix_market.
+  /// Symmetric input gives a Symmetric matrix holding the upper triangle, and general input gives a General matrix
+  /// unless a different type is recorded in a wgfem matrix type comment. Entries with the same row and column are
+  /// summed, and every row must have at least one entry.
+  pub fn read_matrix_market<Rd:Reader>(rd: &mut BufferedReader<Rd>) -> SparseMatrix {
+    let mut lines = rd.lines();
+    let header = match lines.next() { Some(l) => l, None => fail!("MatrixMarket data is empty.") };
+    let header_words: ~[&str] = header.words().collect();
+    if header_words.len() != 5 || header_words[0] != "%%MatrixMarket" || header_words[1] != "matrix" ||
+       header_words[2] != "coordinate" || header_words[3] != "real" {
+      fail!(format!("Unsupported MatrixMarket header: {}", header.trim_right()));
+    }
+    let mut mtype = match header_words[4] {
+      "symmetric" => Symmetric,
+      "general" => General,
+      q => fail!(format!("Unsupported MatrixMarket symmetry qualifier {}.", q))
+    };
+    let mut size_line = ~"";
+    for line in lines.by_ref() {
+      if line.starts_with("% wgfem matrix type:") {
+        mtype = match line.slice_from("% wgfem matrix type:".len()).trim() {
+          "Symmetric" => Symmetric,
+          "StructurallySymmetric" => StructurallySymmetric,
+          "General" => General,
+          t => fail!(format!("Unknown matrix type {} in MatrixMarket comment.", t))
+        };
+      }
+      else if !line.starts_with("%") && !line.trim().is_empty() { size_line = line; break; }
+    }
+    let size: ~[uint] = size_line.words().map(|s| parse_mm_field::<uint>(s)).collect();
+    if size.len() != 3 { fail!(format!("Invalid MatrixMarket size line: {}", size_line.trim_right())); }
+    let (num_rows, num_cols, num_entries) = (size[0], size[1], size[2]);
+    if num_rows != num_cols { fail!("Only square MatrixMarket matrices are supported.") }
+    let sym_input = match header_words[4] { "symmetric" => true, _ => false };
+    let mut triplets = vec::with_capacity(num_entries);
+    for line in lines {
+      if line.starts_with("%") || line.trim().is_empty() { continue; }
+      let fields: ~[&str] = line.words().collect();
+      if fields.len() != 3 { fail!(format!("Invalid MatrixMarket entry line: {}", line.trim_right())); }
+      let (i, j, v) = (parse_mm_field::<uint>(fields[0]), parse_mm_field::<uint>(fields[1]), parse_mm_field::<R>(fields[2]));
+      if i == 0 || j == 0 || i > num_rows || j > num_rows {
+        fail!(format!("MatrixMarket entry ({}, {}) is out of range for {} rows.", i, j, num_rows));
+      }
+      // Symmetric input holds the lower triangle, which is stored as the mirrored upper triangle.
+      if sym_input && i < j { fail!("Upper triangle entry found in symmetric MatrixMarket data.") }
+      triplets.push(if sym_input { (j-1, i-1, v) } else { (i-1, j-1, v) });
+    }
+    if triplets.len() != num_entries {
+      fail!(format!("MatrixMarket data has {} entries, expected {}.", triplets.len(), num_entries));
+    }
+    SparseMatrix::from_triplets(num_rows, triplets, mtype)
+  }
+
+  /// Check the invariants of the compressed sparse row structure which solvers rely on: row beginning indexes which
+  /// start at 0 and are non-decreasing and within the values array, column numbers which are strictly increasing
+  /// within each row and within the range of the (square) matrix, and for Symmetric matrices only upper triangle
+  /// columns. Solvers may behave unpredictably for matrices failing these checks.
+  pub fn validate(&self) -> Result<(), ~str> {
+    if self.num_rows == 0 { return Ok(()); }
+    if *self.row_first_value_ixs.get(0) != 0 {
+      return Err(format!("Row 0 begins at value index {}, expected 0.", *self.row_first_value_ixs.get(0)));
+    }
+    let sym = match self.matrix_type { Symmetric => true, _ => false };
+    for r in range(0, self.num_rows) {
+      let row_begin = *self.row_first_value_ixs.get(r) as int;
+      let row_end = if r == self.num_rows-1 { self.num_values as int } else { *self.row_first_value_ixs.get(r+1) as int };
+      if row_end < row_begin || row_end > self.num_values as int {
+        return Err(format!("Row {} has invalid value index range [{}, {}) for {} values.",
+                           r, row_begin, row_end, self.num_values));
+      }
+      for i in range(row_begin as uint, row_end as uint) {
+        let c = *self.value_cols.get(i) as int;
+        if c < 0 || c >= self.num_rows as int {
+          return Err(format!("Column {} at value index {} in row {} is out of range for {} columns.",
+                             c, i, r, self.num_rows));
+        }
+        if i > row_begin as uint && c <= *self.value_cols.get(i-1) as int {
+          return Err(format!("Column {} at value index {} in row {} does not follow previous column {}.",
+                             c, i, r, *self.value_cols.get(i-1)));
+        }
+        if sym && c < r as int {
+          return Err(format!("Column {} in row {} is below the diagonal of a symmetric matrix.", c, r));
+        }
+      }
+    }
+    Ok(())
+  }
+
+  #[inline]
+  fn row_end_value_ix(&self, r: uint) -> uint {
+    if r == self.num_rows-1 { self.num_values } else { *self.row_first_value_ixs.get(r+1) as uint }
+  }
+
   pub fn debug_print(&self) {
     unsafe {
       for r in range(0, self.num_rows) {
@@ -125,6 +509,75 @@ impl SparseMatrix {
 
 }
 
+// Parse a whitespace delimited field of MatrixMarket data, failing if it is not valid for the type.
+fn parse_mm_field<T:FromStr>(s: &str) -> T {
+  match from_str(s.trim()) {
+    Some(v) => v,
+    None => fail!(format!("Invalid MatrixMarket field {}.", s))
+  }
+}
+
+// Sparse accumulator for a row of a sparse matrix product, holding the row's sums by column, with the columns which
+// have been added to since the row was last taken.
+struct RowAccumulator {
+  vals: ~[R],
+  touched: ~[bool],
+  cols: ~[uint],
+}
+
+impl RowAccumulator {
+
+  fn new(num_cols: uint) -> RowAccumulator {
+    RowAccumulator {
+      vals: vec::from_elem(num_cols, 0 as R),
+      touched: vec::from_elem(num_cols, false),
+      cols: ~[],
+    }
+  }
+
+  #[inline]
+  fn add(&mut self, c: uint, v: R) {
+    if !self.touched[c] {
+      self.touched[c] = true;
+      self.cols.push(c);
+    }
+    self.vals[c] += v;
+  }
+
+  // Take the accumulated row as (column, value) pairs in increasing column order, omitting columns below min_col,
+  // and reset the accumulator for the next row.
+  fn take_row(&mut self, min_col: uint) -> ~[(uint, R)] {
+    self.cols.sort();
+    let mut row = vec::with_capacity(self.cols.len());
+    for &c in self.cols.iter() {
+      if c >= min_col { row.push((c, self.vals[c])); }
+      self.vals[c] = 0 as R;
+      self.touched[c] = false;
+    }
+    self.cols.clear();
+    row
+  }
+}
+
+/// Iterator over the stored entries of a sparse matrix, see SparseMatrix::entries.
+pub struct EntryIter<'a> {
+  priv m: &'a SparseMatrix,
+  priv r: uint,
+  priv i: uint,
+}
+
+impl<'a> Iterator<(uint, uint, R)> for EntryIter<'a> {
+  fn next(&mut self) -> Option<(uint, uint, R)> {
+    if self.i >= self.m.num_values { return None; }
+    while self.i >= self.m.row_end_value_ix(self.r) {
+      self.r += 1;
+    }
+    let entry = (self.r, *self.m.value_cols.get(self.i) as uint, *self.m.values.get(self.i));
+    self.i += 1;
+    Some(entry)
+  }
+}
+
 #[unsafe_destructor]
 impl Drop for SparseMatrix {
   #[inline(never)]
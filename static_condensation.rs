@@ -0,0 +1,190 @@
+use common::R;
+use monomial::Monomial;
+use mesh::{Mesh, FENum, SideFace};
+use wg_basis::FaceMonNum;
+use variational_bilinear_form::VariationalBilinearForm;
+use dense_matrix::DenseMatrix;
+use sparse_matrix::{SparseMatrix, Symmetric};
+use la;
+use la::lapack_int;
+
+use extra::treemap::TreeMap;
+use std::vec;
+
+/* Static condensation of a WG system onto the side supported basis elements.
+ * The system for a symmetric variational form, partitioned into interior and side supported element blocks, is
+ *   [ A   B ] [u_i]   [f_i]
+ *   [ B'  C ] [u_s] = [f_s]
+ * where A is block diagonal with one block for each finite element, since interior supported elements only
+ * interact on their common supporting finite element. The interior elements are thus eliminated element-by-element,
+ * using dense inverses of the diagonal blocks of A, leaving the smaller Schur complement system
+ *   (C - B' A^-1 B) u_s = f_s - B' A^-1 f_i
+ * in only the side elements. The interior part of the solution is recovered from the side part as
+ *   u_i = A^-1 (f_i - B u_s).
+ */
+
+pub struct CondensedSystem {
+
+  // The Schur complement system matrix and right hand side over the side supported elements.
+  sys: SparseMatrix,
+  rhs: ~[R],
+
+  // Per finite element data for reconstruction of the interior solution: the inverse of the element's interior
+  // block of A, the element's rows of the coupling block B restricted to the element's side elements, and the
+  // numbers of these side elements relative to the first side supported element.
+  fe_int_block_invs: ~[DenseMatrix],
+  fe_couplings: ~[DenseMatrix],
+  fe_side_els: ~[~[uint]],
+
+  // The interior part f_i of the original right hand side.
+  int_rhs: ~[R],
+
+  mons_per_fe_int: uint,
+}
+
+impl CondensedSystem {
+
+  /// Condense the system for the given symmetric variational form and single column right hand side onto the
+  /// side supported basis elements.
+  pub fn new<Mon:Monomial, MeshT:Mesh<Mon>, VBF:VariationalBilinearForm<Mon,MeshT>>
+         (vbf: &VBF, rhs: &DenseMatrix) -> CondensedSystem {
+    let basis = vbf.basis();
+    let mesh = basis.mesh();
+    if rhs.num_rows() != basis.num_els() || rhs.num_cols() != 1 {
+      fail!("Right hand side for static condensation should be a single column with a row for each basis element.");
+    }
+
+    let (int_int, int_side, side_side) = vbf.basis_els_vs_basis_els_transpose_blocks();
+
+    let (num_int_els, n) = (basis.num_int_els(), basis.mons_per_fe_int());
+    let num_side_els = basis.num_els() - num_int_els;
+
+    // Schur complement values by (row, column) in the upper triangle, starting from those of C.
+    let mut schur_vals: TreeMap<(uint,uint),R> = TreeMap::new();
+    for (r, c, v) in side_side.entries() {
+      schur_vals.insert((r, c), v);
+    }
+    let mut cond_rhs = vec::from_fn(num_side_els, |i| rhs.get(num_int_els + i, 0));
+    let int_rhs = vec::from_fn(num_int_els, |i| rhs.get(i, 0));
+
+    let mut fe_int_block_invs = vec::with_capacity(mesh.num_fes());
+    let mut fe_couplings = vec::with_capacity(mesh.num_fes());
+    let mut fe_side_els = vec::with_capacity(mesh.num_fes());
+
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let fe_first_int_el = *basis.int_mon_el_num(fe, FaceMonNum(0));
+
+      let side_els = {
+        let mut els = ~[];
+        for sf in range(0, mesh.num_side_faces_for_oshape(mesh.oriented_shape_for_fe(fe))) { let sf = SideFace(sf);
+          if !mesh.is_boundary_side(fe, sf) {
+            for monn in range(0, basis.mons_per_fe_side()) {
+              els.push(*basis.fe_side_mon_el_num(fe, sf, FaceMonNum(monn)) - num_int_els);
+            }
+          }
+        }
+        els
+      };
+
+      let a_inv = symmetric_inverse(&DenseMatrix::upper_triangle_from_fn(n, |i,j| {
+        int_int.get(fe_first_int_el + i, fe_first_int_el + j)
+      }));
+      let b = DenseMatrix::from_fn(n, side_els.len(), |i,j| int_side.get(fe_first_int_el + i, side_els[j]));
+
+      // A^-1 B and A^-1 f_i for this finite element.
+      let a_inv_b = DenseMatrix::from_fn(n, side_els.len(), |i,j| {
+        range(0, n).fold(0 as R, |sum, k| sum + a_inv.get(i,k) * b.get(k,j))
+      });
+      let a_inv_f = vec::from_fn(n, |i| {
+        range(0, n).fold(0 as R, |sum, k| sum + a_inv.get(i,k) * int_rhs[fe_first_int_el + k])
+      });
+
+      for j in range(0, side_els.len()) {
+        for l in range(0, side_els.len()) {
+          let (r, c) = (side_els[j], side_els[l]);
+          if r <= c {
+            let contr = range(0, n).fold(0 as R, |sum, i| sum + b.get(i,j) * a_inv_b.get(i,l));
+            let did_update = match schur_vals.find_mut(&(r,c)) { Some(v) => { *v -= contr; true }, None => false };
+            if !did_update {
+              schur_vals.insert((r,c), -contr);
+            }
+          }
+        }
+        cond_rhs[side_els[j]] -= range(0, n).fold(0 as R, |sum, i| sum + b.get(i,j) * a_inv_f[i]);
+      }
+
+      fe_int_block_invs.push(a_inv);
+      fe_couplings.push(b);
+      fe_side_els.push(side_els);
+    }
+
+    let mut sys = SparseMatrix::new_with_capacities(schur_vals.len(), num_side_els, Symmetric);
+    for (&(r,c), &v) in schur_vals.iter() {
+      sys.push(r, c, v);
+    }
+
+    CondensedSystem {
+      sys: sys,
+      rhs: cond_rhs,
+      fe_int_block_invs: fe_int_block_invs,
+      fe_couplings: fe_couplings,
+      fe_side_els: fe_side_els,
+      int_rhs: int_rhs,
+      mons_per_fe_int: n,
+    }
+  }
+
+  /// The condensed system matrix over the side supported basis elements, as a Symmetric matrix.
+  #[inline]
+  pub fn sys<'a>(&'a self) -> &'a SparseMatrix {
+    &self.sys
+  }
+
+  /// The condensed right hand side over the side supported basis elements.
+  #[inline]
+  pub fn rhs<'a>(&'a self) -> &'a [R] {
+    self.rhs.as_slice()
+  }
+
+  /// Reconstruct the full solution basis coefficients, for interior and side supported elements, from a solution of
+  /// the condensed system over the side supported elements.
+  pub fn reconstruct(&self, side_sol: &[R]) -> ~[R] {
+    if side_sol.len() != self.rhs.len() { fail!("Side solution length does not match the condensed system."); }
+    let n = self.mons_per_fe_int;
+    let mut full_sol = vec::with_capacity(self.int_rhs.len() + side_sol.len());
+    for fe in range(0, self.fe_int_block_invs.len()) {
+      let (a_inv, b, side_els) = (&self.fe_int_block_invs[fe], &self.fe_couplings[fe], &self.fe_side_els[fe]);
+      let resid = vec::from_fn(n, |i| {
+        self.int_rhs[fe*n + i] - range(0, side_els.len()).fold(0 as R, |sum, j| sum + b.get(i,j) * side_sol[side_els[j]])
+      });
+      for i in range(0, n) {
+        full_sol.push(range(0, n).fold(0 as R, |sum, k| sum + a_inv.get(i,k) * resid[k]));
+      }
+    }
+    full_sol.push_all(side_sol);
+    full_sol
+  }
+
+  /// Solve the condensed system and reconstruct the full solution basis coefficients.
+  pub fn solve(&self) -> ~[R] {
+    let side_sol = la::solve_sparse(&self.sys, &DenseMatrix::from_fn(self.rhs.len(), 1, |i,_| self.rhs[i]));
+    self.reconstruct(side_sol)
+  }
+
+}
+
+// Invert the symmetric matrix whose upper triangle is given, returning the full inverse.
+fn symmetric_inverse(m: &DenseMatrix) -> DenseMatrix {
+  let n = m.num_rows();
+  let mut a = DenseMatrix::of_size(n, n);
+  m.copy_upper_triangle_into(&mut a);
+  let inv = DenseMatrix::from_fn(n, n, |i,j| if i == j { 1 as R } else { 0 as R });
+  let mut pivots: ~[lapack_int] = vec::from_elem(n, 0 as lapack_int);
+  let info = unsafe {
+    la::solve_symmetric_as_col_maj_with_ut_sys(a.mut_col_maj_data_ptr(), n as lapack_int,
+                                               inv.mut_col_maj_data_ptr(), n as lapack_int,
+                                               pivots.as_mut_ptr())
+  };
+  if info != 0 { fail!("Interior block of finite element is singular, static condensation failed."); }
+  inv
+}
@@ -19,6 +19,18 @@ fn test_constr_from_fn() {
   assert_eq!(data_as_vec, ~[0.,1000.,2000.,1.,1001.,2001.,2.,1002.,2002.]);
 }
 
+#[test]
+fn test_constr_hilbert_from_fn() {
+  let m = DenseMatrix::from_fn(3,4, |i,j| 1./((i + j + 1) as R));
+  assert_eq!(m.num_rows(), 3);
+  assert_eq!(m.num_cols(), 4);
+  assert_eq!(m.get(0,0), 1.);
+  assert_eq!(m.get(1,0), 1./2.);
+  assert_eq!(m.get(0,1), 1./2.);
+  assert_eq!(m.get(2,1), 1./4.);
+  assert_eq!(m.get(2,3), 1./6.);
+}
+
 #[test]
 fn test_constr_from_elem_with_cols_capacity() {
   let m = DenseMatrix::from_elem_with_cols_capacity(3,3, 10., 5);
@@ -198,3 +210,59 @@ fn test_bad_copy_upper_triangle_into() {
   m_src.copy_upper_triangle_into(m);
 }
 
+
+#[test]
+fn test_scaled_and_neg() {
+  let m = DenseMatrix::from_fn(3,2, |r,c| 1000. * r as R + c as R + 1.);
+
+  let neg_m = -m;
+  let m_times_neg_one = m.scaled(-1.);
+  let m_times_zero = m.scaled(0.);
+  for r in range(0u,3) {
+    for c in range(0u,2) {
+      assert_eq!(neg_m.get(r,c), -m.get(r,c));
+      assert_eq!(m_times_neg_one.get(r,c), neg_m.get(r,c));
+      assert_eq!(m_times_zero.get(r,c), 0.);
+    }
+  }
+  assert_eq!(m.scaled(2.).get(2,1), 4004.);
+}
+
+#[test]
+fn test_add_sub() {
+  let a = DenseMatrix::from_fn(3,3, |r,c| 1000. * r as R + c as R);
+  let a_plus_a_minus_a = a + a - a;
+  for r in range(0u,3) {
+    for c in range(0u,3) {
+      assert_eq!(a_plus_a_minus_a.get(r,c), a.get(r,c));
+    }
+  }
+  assert_eq!((a + a).get(2,1), 4002.);
+}
+
+#[test]
+#[should_fail]
+fn test_bad_add_dims() {
+  let a = DenseMatrix::from_elem(3,3, 1.);
+  let b = DenseMatrix::from_elem(3,2, 1.);
+  a + b;
+}
+
+#[test]
+fn test_cols_and_rows() {
+  let m = DenseMatrix::from_rows(2,3,
+    [~[1., 2., 3.],
+     ~[4., 5., 6.]]);
+  assert_eq!(m.col(0), ~[1., 4.]);
+  assert_eq!(m.col(1), ~[2., 5.]);
+  assert_eq!(m.col(2), ~[3., 6.]);
+  assert_eq!(m.row(0), ~[1., 2., 3.]);
+  assert_eq!(m.row(1), ~[4., 5., 6.]);
+}
+
+#[test]
+#[should_fail]
+fn test_bad_col() {
+  let m = DenseMatrix::from_elem(2,3, 1.);
+  m.col(3);
+}
@@ -1,8 +1,9 @@
 use la;
 use common::R;
-use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric};
+use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric, General};
 use dense_matrix::DenseMatrix;
-use std::num::abs;
+use extra::tempfile::TempDir;
+use std::num::{abs, sqrt};
 
 #[test]
 fn test_do_la_init() {
@@ -103,3 +104,317 @@ fn test_sparse_asymmetric_solve() {
   approx_eq(sol, [1./3., 4./3., 0.], 1e-15);
 }
 
+
+#[test]
+fn test_estimate_convergence_rate_second_order() {
+  // errors e = c h^2 for h = 1/2, 1/4, 1/8, 1/16
+  let errs = [3.*0.25, 3.*0.0625, 3.*0.015625, 3.*0.00390625];
+  let rates = la::estimate_convergence_rate(errs, 2.);
+  approx_eq(rates, [2., 2., 2.], 1e-12);
+}
+
+#[test]
+fn test_estimate_convergence_rate_non_power_of_two_factor() {
+  // errors e = h^3 with the mesh size dividing by 3 at each refinement
+  let errs = [1., 1./27., 1./729.];
+  let rates = la::estimate_convergence_rate(errs, 3.);
+  approx_eq(rates, [3., 3.], 1e-12);
+}
+
+#[test]
+fn test_residual_norm_of_direct_solve() {
+  //      1 2 3
+  // A =  2 2 0
+  //      3 0 3 
+  // b = [3 2 1]^t
+  let mut A = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  A.push(0,0, 1.);
+  A.push(0,1, 2.);
+  A.push(0,2, 3.);
+  A.push(1,1, 2.);
+  A.push(2,2, 3.);
+  
+  let b = DenseMatrix::from_rows(3,1, [~[3.],~[2.],~[1.]]);
+
+  let sol = la::solve_sparse(&A, &b);
+
+  assert!(la::residual_norm(&A, sol, &b) < 1e-14);
+  assert!(abs(la::residual_norm(&A, [0.,0.,0.], &b) - sqrt(14.)) < 1e-14);
+}
+
+#[test]
+#[should_fail]
+fn test_sparse_solve_with_short_rhs() {
+  let mut A = SparseMatrix::new_with_capacities(3, 3, Symmetric);
+  A.push(0,0, 1.);
+  A.push(1,1, 2.);
+  A.push(2,2, 3.);
+  
+  let b = DenseMatrix::from_rows(2,1, [~[3.],~[2.]]);
+  
+  la::solve_sparse(&A, &b);
+}
+
+#[test]
+fn test_sparse_solve_with_stats() {
+  // 100x100 tridiagonal system with 2 on the diagonal and -1 on the off-diagonals.
+  let n = 100u;
+  let mut A = SparseMatrix::new_with_capacities(2*n-1, n, Symmetric);
+  for i in range(0, n) {
+    A.push(i,i, 2.);
+    if i < n-1 { A.push(i,i+1, -1.); }
+  }
+  let b = DenseMatrix::from_elem(n,1, 1.);
+
+  let (sol, stats) = la::solve_sparse_with_stats(&A, &b);
+
+  assert!(la::residual_norm(&A, sol, &b) < 1e-10);
+  assert!(stats.factor_nnz >= n);
+  assert!(stats.peak_memory_kb > 0);
+  assert!(stats.elapsed_secs >= 0.);
+}
+
+#[test]
+fn test_estimate_spectral_radius_of_tridiagonal() {
+  // The 5x5 matrix tridiag(-1, 2, -1) has eigenvalues 2 - 2 cos(k pi/6), k = 1..5, the largest being 2 + sqrt(3),
+  // with the next largest 3.
+  let n = 5u;
+  let mut A = SparseMatrix::new_with_capacities(2*n, n, Symmetric);
+  for i in range(0, n) {
+    A.push(i,i, 2.);
+    if i+1 < n { A.push(i,i+1, -1.); }
+  }
+
+  let est = la::estimate_spectral_radius(&A as &la::LinearOperator, 200);
+
+  assert!(abs(est - (2. + sqrt(3.))) < 1e-10);
+}
+
+#[test]
+fn test_factor_nnz_at_least_matrix_nnz() {
+  // 5-point Laplacian on a 10x10 grid, with upper triangle stored, whose factorization has fill-in.
+  let k = 10u;
+  let n = k*k;
+  let mut A = SparseMatrix::new_with_capacities(3*n, n, Symmetric);
+  for i in range(0, n) {
+    A.push(i,i, 4.);
+    if (i+1) % k != 0 { A.push(i,i+1, -1.); }
+    if i+k < n { A.push(i,i+k, -1.); }
+  }
+  let b = DenseMatrix::from_elem(n,1, 1.);
+
+  let (sol, stats) = la::solve_sparse_with_stats(&A, &b);
+
+  assert!(la::residual_norm(&A, sol, &b) < 1e-10);
+  assert!(stats.factor_nnz() >= A.num_values());
+}
+
+#[test]
+fn test_symmetric_diagonal_scaling_solve() {
+  //      4 2 0
+  // A =  2 9 3
+  //      0 3 16
+  let mut A = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  A.push(0,0, 4.); A.push(0,1, 2.);
+  A.push(1,1, 9.); A.push(1,2, 3.);
+  A.push(2,2, 16.);
+
+  let (S, scaling) = A.symmetric_diagonal_scaling();
+  approx_eq(scaling, [1./2., 1./3., 1./4.], 1e-15);
+  for i in range(0u, 3) {
+    assert!(abs(S.get(i,i) - 1.) < 1e-15);
+  }
+  assert!(abs(S.get(0,1) - 2./6.) < 1e-15);
+  assert!(abs(S.get(1,2) - 3./12.) < 1e-15);
+  assert_eq!(S.get(0,2), 0.);
+
+  let b = [1., 2., 3.];
+  let sol = la::solve_sparse(&A, &DenseMatrix::from_fn(3,1, |i,_| b[i]));
+  let scaled_sol = la::solve_sparse(&S, &DenseMatrix::from_fn(3,1, |i,_| scaling[i] * b[i]));
+  let unscaled_sol = ~[scaling[0] * scaled_sol[0], scaling[1] * scaled_sol[1], scaling[2] * scaled_sol[2]];
+  approx_eq(unscaled_sol, sol, 1e-13);
+}
+
+#[test]
+fn test_dense_general_solve_3x3() {
+  let a = DenseMatrix::from_rows(3, 3, [~[2., 1., 0.],
+                                        ~[0., 1., 3.],
+                                        ~[1., 0., 1.]]);
+  let b = DenseMatrix::from_rows(3, 2, [~[1.,  0.],
+                                        ~[2.,  1.],
+                                        ~[3., -1.]]);
+  // Inverse of a by adjugate, det(a) = 5.
+  let a_inv = DenseMatrix::from_rows(3, 3, [~[ 1./5., -1./5.,  3./5.],
+                                            ~[ 3./5.,  2./5., -6./5.],
+                                            ~[-1./5.,  1./5.,  2./5.]]);
+  let expected = DenseMatrix::from_fn(3, 2, |r,c| range(0u,3).fold(0 as R, |sum, k| sum + a_inv.get(r,k) * b.get(k,c)));
+
+  let x = la::solve_dense_general(&a, &b);
+
+  assert_eq!(x.num_rows(), 3);
+  assert_eq!(x.num_cols(), 2);
+  approx_eq(x.col(0).as_slice(), expected.col(0).as_slice(), 1e-12);
+  approx_eq(x.col(1).as_slice(), expected.col(1).as_slice(), 1e-12);
+  approx_eq(x.col(0).as_slice(), [8./5., -11./5., 7./5.], 1e-12);
+  // Inputs are left unmodified.
+  assert_eq!(a.get(0,1), 1.);
+  assert_eq!(b.get(2,1), -1.);
+}
+
+// A symmetric, diagonally dominant 4x4 system, stored by its upper triangle, for the iterative solver tests.
+fn diag_dominant_4x4_system() -> (SparseMatrix, DenseMatrix) {
+  //      4 -1  1  0
+  // A = -1  5 -2  1
+  //      1 -2  6 -1
+  //      0  1 -1  3
+  let mut a = SparseMatrix::new_with_capacities(9, 4, Symmetric);
+  a.push(0,0, 4.); a.push(0,1, -1.); a.push(0,2, 1.);
+  a.push(1,1, 5.); a.push(1,2, -2.); a.push(1,3, 1.);
+  a.push(2,2, 6.); a.push(2,3, -1.);
+  a.push(3,3, 3.);
+  let b = DenseMatrix::from_rows(4,1, [~[1.],~[-2.],~[3.],~[0.5]]);
+  (a, b)
+}
+
+#[test]
+fn test_sor_converges_on_diagonally_dominant_system_matching_direct_solve() {
+  let (a, b) = diag_dominant_4x4_system();
+  assert!(a.is_diagonally_dominant());
+
+  let direct_sol = la::solve_sparse(&a, &b);
+  let gs_sol = la::solve_sparse_sor(&a, &b, 1., 1e-12, 200).unwrap();
+  let sor_sol = la::solve_sparse_sor(&a, &b, 1.2, 1e-12, 200).unwrap();
+  approx_eq(gs_sol, direct_sol, 1e-10);
+  approx_eq(sor_sol, direct_sol, 1e-10);
+
+  match la::solve_sparse_sor(&a, &b, 1., 1e-12, 1) {
+    Err(la::NotConverged(iters, rel_res)) => { assert_eq!(iters, 1); assert!(rel_res > 1e-12); }
+    _ => fail!("Expected SOR to not converge in a single iteration.")
+  }
+}
+
+#[test]
+fn test_sor_reports_zero_diagonal() {
+  let mut a = SparseMatrix::new_with_capacities(4, 2, General);
+  a.push(0,0, 2.); a.push(0,1, 1.);
+  a.push(1,0, 1.); a.push(1,1, 0.);
+  let b = DenseMatrix::from_rows(2,1, [~[1.],~[1.]]);
+  assert_eq!(la::solve_sparse_sor(&a, &b, 1., 1e-10, 10), Err(la::ZeroDiagonal(1)));
+}
+
+#[test]
+fn test_jacobi_pcg_reduces_iterations_on_ill_scaled_diagonal_system() {
+  let scales = [1., 10., 100., 1000., 10000.];
+  let mut a = SparseMatrix::new_with_capacities(5, 5, Symmetric);
+  for i in range(0u, 5) { a.push(i, i, scales[i]); }
+  let b = DenseMatrix::from_rows(5,1, [~[1.],~[2.],~[3.],~[4.],~[5.]]);
+  let exact = [1., 0.2, 0.03, 0.004, 0.0005];
+
+  // Jacobi preconditioning turns the system into the identity, solved in a single iteration.
+  let pcg_sol = la::solve_sparse_pcg_jacobi(&a, &b, 1e-12, 1).unwrap();
+  approx_eq(pcg_sol, exact, 1e-14);
+
+  // Plain CG needs an iteration for each of the 5 distinct eigenvalues.
+  match la::solve_sparse_cg(&a, &b, 1e-12, 3) {
+    Err(la::NotConverged(iters, _)) => assert_eq!(iters, 3),
+    _ => fail!("Expected plain CG not to converge in 3 iterations.")
+  }
+  let cg_sol = la::solve_sparse_cg(&a, &b, 1e-12, 50).unwrap();
+  approx_eq(cg_sol, exact, 1e-10);
+}
+
+#[test]
+fn test_cg_and_pcg_match_direct_solve() {
+  let (a, b) = diag_dominant_4x4_system();
+
+  let direct_sol = la::solve_sparse(&a, &b);
+  approx_eq(la::solve_sparse_cg(&a, &b, 1e-12, 20).unwrap(), direct_sol, 1e-10);
+  approx_eq(la::solve_sparse_pcg_jacobi(&a, &b, 1e-12, 20).unwrap(), direct_sol, 1e-10);
+
+  let mut indef = SparseMatrix::new_with_capacities(2, 2, Symmetric);
+  indef.push(0,0, -1.);
+  indef.push(1,1, 1.);
+  let b2 = DenseMatrix::from_rows(2,1, [~[1.],~[0.]]);
+  assert_eq!(la::solve_sparse_cg(&indef, &b2, 1e-12, 10), Err(la::IndefiniteSystem(0)));
+}
+
+#[test]
+fn test_dump_system_reload_and_resolve() {
+  //      4 1 0
+  // A =  1 3 1
+  //      0 1 2
+  let mut A = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  A.push(0,0, 4.); A.push(0,1, 1.);
+  A.push(1,1, 3.); A.push(1,2, 1.);
+  A.push(2,2, 2.);
+  let rhs = ~[1., 1./3., -2.5e-7];
+
+  // A directory unique to this run, which is removed with its contents when tmp_dir is dropped.
+  let tmp_dir = match TempDir::new("wgfem_test_dump_system") {
+    Some(tmp_dir) => tmp_dir,
+    None => fail!("Could not create a temporary directory.")
+  };
+  let dir = tmp_dir.path().join("dump");
+  la::dump_system(&A, rhs, &dir);
+  let (reloaded_A, reloaded_rhs) = la::load_system(&dir);
+
+  assert_eq!(reloaded_rhs, rhs);
+  let sol = la::solve_sparse(&A, &DenseMatrix::from_fn(3,1, |r,_| rhs[r]));
+  let reloaded_sol = la::solve_sparse(&reloaded_A, &DenseMatrix::from_fn(3,1, |r,_| reloaded_rhs[r]));
+  approx_eq(reloaded_sol, sol, 1e-15);
+}
+
+#[test]
+fn test_sparse_general_solve() {
+  //      2 1 0
+  // A =  0 3 1
+  //      1 0 4
+  // b = [4 9 13]^t
+  // sol = [1 2 3]^t
+  let mut A = SparseMatrix::new_with_capacities(6, 3, General);
+  A.push(0,0, 2.); A.push(0,1, 1.);
+  A.push(1,1, 3.); A.push(1,2, 1.);
+  A.push(2,0, 1.); A.push(2,2, 4.);
+
+  let b = DenseMatrix::from_rows(3,1, [~[4.],~[9.],~[13.]]);
+
+  let sol = la::solve_sparse(&A, &b);
+
+  approx_eq(sol, [1., 2., 3.], 1e-14);
+}
+
+#[test]
+fn test_try_solve_sparse_bad_entry_gives_err() {
+  // As in test_sparse_symmetric_solve_bad_entry, but the backend's failure is returned instead of failing.
+  let mut A = SparseMatrix::new_with_capacities(7, 3, Symmetric);
+  A.push(0,0, 1.);
+  A.push(0,1, 2.);
+  A.push(0,2, 3.);
+  A.push(1,0, 2.); // Lower triangular entries are not allowed in a symmetric system.
+  A.push(1,1, 2.);
+  A.push(2,0, 3.); // "
+  A.push(2,2, 3.);
+
+  let b = DenseMatrix::from_rows(3,1, [~[3.],~[2.],~[1.]]);
+
+  match la::try_solve_sparse(&A, &b) {
+    Err(la::BackendFailure(la::MklPardiso, stat)) => assert!(stat != 0),
+    Err(e) => fail!(format!("Unexpected solve error: {}", e.to_str())),
+    Ok(_) => fail!("Solve of malformed symmetric system should have given an error."),
+  }
+}
+
+#[test]
+fn test_try_solve_sparse_ok() {
+  let mut A = SparseMatrix::new_with_capacities(3, 3, Symmetric);
+  A.push(0,0, 1.);
+  A.push(1,1, 2.);
+  A.push(2,2, 3.);
+
+  let b = DenseMatrix::from_rows(3,1, [~[3.],~[2.],~[1.]]);
+
+  match la::try_solve_sparse(&A, &b) {
+    Ok(sol) => approx_eq(sol, [3., 1., 1./3.], 1e-15),
+    Err(e) => fail!(format!("Unexpected solve error: {}", e.to_str())),
+  }
+}
@@ -1,6 +1,6 @@
 use common::*;
 use monomial;
-use monomial::{Monomial, Mon1d, Mon2d, Mon3d, Mon4d, MaxMonDeg};
+use monomial::{Monomial, Mon1d, Mon2d, Mon3d, Mon4d, MaxMonDeg, MaxMonFactorDeg};
 
 #[test]
 fn test_domain_dims() {
@@ -754,3 +754,11 @@ fn test_max_var_deg_4d() {
   assert_eq!(m.max_var_deg(), Deg(4));
 }
 
+
+#[test]
+fn test_deg_lim_products() {
+  assert_eq!(MaxMonDeg(2).product_with(&MaxMonDeg(3)), MaxMonDeg(5));
+  assert_eq!(MaxMonFactorDeg(2).product_with(&MaxMonFactorDeg(3)), MaxMonFactorDeg(5));
+  assert_eq!(MaxMonDeg(2).product_with(&MaxMonFactorDeg(1)), MaxMonFactorDeg(3));
+  assert_eq!(MaxMonFactorDeg(1).product_with(&MaxMonDeg(2)), MaxMonFactorDeg(3));
+}
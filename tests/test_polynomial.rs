@@ -639,5 +639,25 @@ fn test_4d_owned_max_var_deg() {
   assert_eq!(one_plus_xy2z3t4.max_var_deg(), Deg(4));
 }
 
+#[test]
+fn test_2d_owned_partial_derivs() {
+  let one_mon: Mon2d = Monomial::one();
+  let x_mon = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y_mon = Mon2d { exps: [Deg(0), Deg(1)] };
+  // 1 + 3 x^2 y + 2 y
+  let p = PolyOwning::new(~[1.,3.,2.], ~[one_mon, x_mon*x_mon*y_mon, y_mon]);
 
+  assert!(p.partial_deriv(Dim(0)).equiv(&PolyOwning::new(~[6.], ~[x_mon*y_mon])));
+  assert!(p.partial_deriv(Dim(1)).equiv(&PolyOwning::new(~[3.,2.], ~[x_mon*x_mon, one_mon])));
+}
 
+#[test]
+fn test_2d_owned_gradient_of_const_is_zero() {
+  let one_mon: Mon2d = Monomial::one();
+  let p = PolyOwning::new(~[5.], ~[one_mon]);
+  let grad = p.gradient();
+
+  assert_eq!(grad.len(), 2);
+  assert_eq!(grad[0].value_at([0.3,0.7]), 0.);
+  assert_eq!(grad[1].value_at([0.3,0.7]), 0.);
+}
@@ -1,11 +1,14 @@
-use common::{R, pow, Dim, Deg, DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR};
+use common::{R, R_EPSILON, pow, Dim, Deg, DEFAULT_INTEGRATION_REL_ERR, DEFAULT_INTEGRATION_ABS_ERR};
 use monomial::{Monomial, Mon1d, Mon2d, Mon3d, Mon4d};
-use polynomial::{poly};
+use polynomial::{poly, PolyOwning};
 use vector_monomial::VectorMonomial;
-use mesh::{Mesh, FENum, OShape, NBSideNum, NBSideInclusions};
+use mesh::{Mesh, FENum, OShape, SideFace, NBSideNum, NBSideInclusions, Interior, Side};
 use rectangle_mesh::*;
+use rectangle_mesh_builder::RectMeshBuilder;
 
 use std::num::{sqrt, abs};
+use std::f64;
+use extra::test::BenchHarness;
 
 
 struct NBSideGeom {
@@ -31,7 +34,7 @@ fn test_3x4_constr() -> () {
   assert_eq!(&rmesh3x4.min_bounds, &mesh_min_coords);
   assert_eq!(&rmesh3x4.max_bounds, &mesh_max_coords);
   assert_eq!(&rmesh3x4.mesh_ldims, &mesh_ldims);
-  assert_eq!(&rmesh3x4.fe_side_lens, &~[1./3., 1./4.]);
+  assert_eq!(&rmesh3x4.oshape_side_lens[0], &~[1./3., 1./4.]);
   assert_approx(rmesh3x4.rect_diameter, sqrt(pow(1./3.,2) + pow(1./4.,2)));
   assert_approx(rmesh3x4.shape_diameter_inv(OShape(0)), 1./sqrt(pow(1./3.,2) + pow(1./4.,2)));
   assert_eq!(&rmesh3x4.cumprods_mesh_ldims, &~[3, 3*4]);
@@ -51,6 +54,16 @@ fn test_3x4_constr() -> () {
   assert_eq!(rmesh3x4.integration_abs_err, 1e-5);
 }
 
+#[test]
+fn test_3x4_eps_tols_constr() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_eps_tols(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)], 1000., 100.);
+
+  assert_eq!(rmesh3x4.integration_rel_err, 1000. * R_EPSILON);
+  assert_eq!(rmesh3x4.integration_abs_err, 100. * R_EPSILON);
+  // R is currently always f64.
+  assert_eq!(rmesh3x4.integration_rel_err, 1000. * f64::EPSILON);
+}
+
 #[test]
 fn test_3x4x5_constr() -> () {
   let mesh_min_coords = ~[1f64, 2., 3.];
@@ -62,7 +75,7 @@ fn test_3x4x5_constr() -> () {
   assert_eq!(&rmesh3x4x5.min_bounds, &mesh_min_coords);
   assert_eq!(&rmesh3x4x5.max_bounds, &mesh_max_coords);
   assert_eq!(&rmesh3x4x5.mesh_ldims, &mesh_ldims);
-  assert_eq!(&rmesh3x4x5.fe_side_lens, &~[1./3., 1./4., 1./5.]);
+  assert_eq!(&rmesh3x4x5.oshape_side_lens[0], &~[1./3., 1./4., 1./5.]);
   assert_approx(rmesh3x4x5.rect_diameter, sqrt(pow(1./3.,2) + pow(1./4.,2) + pow(1./5.,2)));
   assert_approx(rmesh3x4x5.shape_diameter_inv(OShape(0)), 1./sqrt(pow(1./3.,2) + pow(1./4.,2) + pow(1./5.,2)));
   assert_eq!(&rmesh3x4x5.cumprods_mesh_ldims, &~[3, 3*4, 3*4*5]);
@@ -94,7 +107,7 @@ fn test_3x4x5x6_constr() -> () {
   assert_eq!(&rmesh3x4x5x6.min_bounds, &mesh_min_coords);
   assert_eq!(&rmesh3x4x5x6.max_bounds, &mesh_max_coords);
   assert_eq!(&rmesh3x4x5x6.mesh_ldims, &mesh_ldims);
-  assert_eq!(&rmesh3x4x5x6.fe_side_lens, &~[1./3., 1./4., 1./5., 1./6.]);
+  assert_eq!(&rmesh3x4x5x6.oshape_side_lens[0], &~[1./3., 1./4., 1./5., 1./6.]);
   assert_approx(rmesh3x4x5x6.rect_diameter, sqrt(pow(1./3.,2) + pow(1./4.,2) + pow(1./5.,2) + pow(1./6.,2)));
   assert_approx(rmesh3x4x5x6.shape_diameter_inv(OShape(0)), 1./sqrt(pow(1./3.,2) + pow(1./4.,2) + pow(1./5.,2) + pow(1./6.,2)));
   assert_eq!(&rmesh3x4x5x6.cumprods_mesh_ldims, &~[3, 3*4, 3*4*5, 3*4*5*6]);
@@ -422,6 +435,17 @@ fn test_2x3x2_boundary_side_fes() -> () {
   assert_eq!(fes_by_b_sf[*front_side], ~[FENum(6), FENum(7), FENum(8), FENum(9), FENum(10), FENum(11)]);
 }
 
+#[test]
+fn test_2x3x4_num_boundary_sides_by_perp_axis() -> () {
+  let rmesh2x3x4: ~RectMesh<Mon3d> = ~RectMesh::new(~[1f64, 2., 3.],
+                                                    ~[2f64, 3., 4.],
+                                                    ~[MeshCoord(2), MeshCoord(3), MeshCoord(4)]);
+  let b_sides_by_axis = rmesh2x3x4.num_boundary_sides_by_perp_axis();
+  assert_eq!(&b_sides_by_axis, &~[2*3*4, 2*2*4, 2*2*3]);
+  assert_eq!(b_sides_by_axis.iter().fold(0u, |sum, &n| sum + n), rmesh2x3x4.num_boundary_sides());
+  assert_eq!(rmesh2x3x4.num_boundary_sides(), 24+16+12);
+}
+
 #[test]
 fn test_3x4_boundary_side_determ() -> () {
   let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.],
@@ -710,6 +734,61 @@ fn test_3x4_nonboundary_side_coords_axis0() -> () {
   assert_eq!(rmesh3x4.nb_side_num_for_fe_side(FENum(11), left_side),  NBSideNum(7));
 }
 
+// Test the wrapped sides perpendicular to the periodic axis of a 2d mesh periodic along axis 0.
+#[test]
+fn test_3x4_periodic_axis0_nonboundary_sides() -> () {
+  let mut rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_periodic(~[1f64, 2.],
+                                                               ~[2f64, 3.],
+                                                               ~[MeshCoord(3), MeshCoord(4)],
+                                                               ~[true, false]);
+  let left_side = lesser_side_face_perp_to_axis(Dim(0));
+  let right_side = greater_side_face_perp_to_axis(Dim(0));
+  let bottom_side = lesser_side_face_perp_to_axis(Dim(1));
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+
+  // The mesh for non-boundary sides perpendicular to axis 0 has dimensions 3 x 4, and that for axis 1 is 3 x 3.
+  assert!(rmesh3x4.is_periodic_axis(Dim(0)) && !rmesh3x4.is_periodic_axis(Dim(1)));
+  assert_eq!(rmesh3x4.num_nb_sides(), 12 + 9);
+  assert_eq!(rmesh3x4.num_boundary_sides_by_perp_axis(), ~[0, 2*3]);
+  assert_eq!(rmesh3x4.num_boundary_sides(), 6);
+
+  // last side in first row, wrapping from the last to the first element of the row
+  let sgeom_2 = nb_side_geom(rmesh3x4, NBSideNum(2));
+  assert_eq!(sgeom_2.perp_axis, Dim(0));
+  assert_eq!(&sgeom_2.mesh_coords, &~[MeshCoord(2), MeshCoord(0)]);
+  assert_eq!(rmesh3x4.fe_inclusions_of_nb_side(NBSideNum(2)),
+             NBSideInclusions { nb_side_num: NBSideNum(2),
+                                fe1: FENum(0), side_face_in_fe1: left_side,
+                                fe2: FENum(2), side_face_in_fe2: right_side });
+  assert_eq!(rmesh3x4.nb_side_num_for_fe_side(FENum(0), left_side), NBSideNum(2));
+  assert_eq!(rmesh3x4.nb_side_num_for_fe_side(FENum(2), right_side), NBSideNum(2));
+
+  // first side in second row, not wrapping
+  assert_eq!(rmesh3x4.fe_inclusions_of_nb_side(NBSideNum(3)),
+             NBSideInclusions { nb_side_num: NBSideNum(3),
+                                fe1: FENum(3), side_face_in_fe1: right_side,
+                                fe2: FENum(4), side_face_in_fe2: left_side });
+
+  // last side perpendicular to axis 0
+  assert_eq!(rmesh3x4.fe_inclusions_of_nb_side(NBSideNum(11)),
+             NBSideInclusions { nb_side_num: NBSideNum(11),
+                                fe1: FENum(9), side_face_in_fe1: left_side,
+                                fe2: FENum(11), side_face_in_fe2: right_side });
+  assert_eq!(rmesh3x4.nb_side_num_for_fe_side(FENum(9), left_side), NBSideNum(11));
+
+  // Only sides perpendicular to the non-periodic axis are on the boundary.
+  for fe in range(0, rmesh3x4.num_fes()) { let fe = FENum(fe);
+    assert!(!rmesh3x4.is_boundary_side(fe, left_side) && !rmesh3x4.is_boundary_side(fe, right_side));
+  }
+  assert!(rmesh3x4.is_boundary_side(FENum(0), bottom_side) && rmesh3x4.is_boundary_side(FENum(9), top_side));
+  let b_side_fes = rmesh3x4.boundary_fes_by_oshape_side();
+  let fes_by_b_sf = b_side_fes[0];
+  assert_eq!(fes_by_b_sf[*left_side], ~[]);
+  assert_eq!(fes_by_b_sf[*right_side], ~[]);
+  assert_eq!(fes_by_b_sf[*bottom_side], ~[FENum(0), FENum(1), FENum(2)]);
+  assert_eq!(fes_by_b_sf[*top_side], ~[FENum(9), FENum(10), FENum(11)]);
+}
+
 
 // Test the non-boundary sides perpendicular to axis 0 for 3d mesh.
 #[test]
@@ -1910,6 +1989,36 @@ fn test_intg_global_x_facerel_mon_on_fe4_sides_2d() -> () {
                 pow(1./4.,3) * pow(1./3.,4)/4.);
 }
 
+#[test]
+fn test_intg_global_x_facerel_mon_on_fe4_faces_2d() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.],
+                                                 ~[2f64, 3.],
+                                                 ~[MeshCoord(3), MeshCoord(4)]);
+  let right_side = greater_side_face_perp_to_axis(Dim(0));
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+
+  let fe4 = FENum(4);
+  let int_origin_0 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(0));
+  let int_origin_1 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(1));
+
+  let x2_y3_int = |x:&[R]| -> R {
+    pow(x[0]-int_origin_0, 2) * pow(x[1]-int_origin_1, 3)
+  };
+
+  assert_approx(rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_face(|v|x2_y3_int(v), y, fe4, Interior),
+                pow(1./3.,3)/3. * pow(1./4.,5)/5.);
+  assert_approx(rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_face(|v|x2_y3_int(v), y, fe4, Side(right_side)),
+                pow(1./3.,2) * pow(1./4.,5)/5.);
+
+  // The function sees absolute coordinates, here x in [4/3, 5/3] on the top side, while the monomial x is relative
+  // to the side's origin.
+  assert_approx(rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_face(|v| v[0], x, fe4, Side(top_side)),
+                7./81.);
+}
+
 #[test]
 fn test_intg_global_x_facerel_mon_on_fe4_sides_3d() -> () {
   let rmesh3x4x5: ~RectMesh<Mon3d> = ~RectMesh::new(~[1f64, 2., 3.],
@@ -2241,6 +2350,52 @@ fn test_intg_intrel_mon_x_siderel_mon_on_oshape_side() -> () {
                 1./5. * pow(1./3.,2)/2. * pow(1./4.,4)/4.);
 }
 
+#[test]
+fn test_intg_const_intrel_mon_x_linear_siderel_mon_on_oshape_side_2d() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  let left_side = lesser_side_face_perp_to_axis(Dim(0));
+  let right_side = greater_side_face_perp_to_axis(Dim(0));
+  let bottom_side = lesser_side_face_perp_to_axis(Dim(1));
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+
+  // A constant interior monomial restricts to the same constant on both the lesser and greater sides.
+  for &sf in [bottom_side, top_side].iter() {
+    assert_approx(rmesh3x4.intg_intrel_mon_x_siderel_mon_on_oshape_side(one, x, OShape(0), sf), pow(1./3.,2)/2.);
+  }
+  for &sf in [left_side, right_side].iter() {
+    assert_approx(rmesh3x4.intg_intrel_mon_x_siderel_mon_on_oshape_side(one, y, OShape(0), sf), pow(1./4.,2)/2.);
+  }
+
+  // The interior monomial x is fixed at 0 on the left side and at the fe width 1/3 on the right side.
+  assert_approx(rmesh3x4.intg_intrel_mon_x_siderel_mon_on_oshape_side(x, y, OShape(0), left_side), 0.);
+  assert_approx(rmesh3x4.intg_intrel_mon_x_siderel_mon_on_oshape_side(x, y, OShape(0), right_side),
+                1./3. * pow(1./4.,2)/2.);
+}
+
+#[test]
+fn test_outward_normals_2d() -> () {
+  let rmesh3x3: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)]);
+  let fe4 = FENum(4);
+  let center = [1.5, 1.5];
+  for sf in range(0, rmesh3x3.num_side_faces_for_oshape(OShape(0))) { let sf = SideFace(sf);
+    let n = rmesh3x3.outward_normal_for_oshape_side(OShape(0), sf);
+    assert_eq!(n.len(), 2);
+    assert_approx(sqrt(n[0]*n[0] + n[1]*n[1]), 1.);
+
+    // Stepping one element width from the center along the normal leads to the element across the side.
+    let across = rmesh3x3.fe_containing_point([center[0] + n[0], center[1] + n[1]]).unwrap();
+    let incls = rmesh3x3.fe_inclusions_of_nb_side(rmesh3x3.nb_side_num_for_fe_side(fe4, sf));
+    assert!(across != fe4);
+    assert!(across == if incls.fe1 == fe4 { incls.fe2 } else { incls.fe1 });
+  }
+  assert_eq!(rmesh3x3.outward_normal_for_oshape_side(OShape(0), lesser_side_face_perp_to_axis(Dim(0))), ~[-1., 0.]);
+  assert_eq!(rmesh3x3.outward_normal_for_oshape_side(OShape(0), greater_side_face_perp_to_axis(Dim(1))), ~[0., 1.]);
+}
+
 #[test]
 fn test_intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side_dim0() -> () {
   let rmesh3x4x5: ~RectMesh<Mon3d> = ~RectMesh::new(~[1f64, 2., 3.],
@@ -2547,7 +2702,468 @@ fn test_intg_siderel_poly_x_intrel_vmon_dot_normal_on_oshape_side_dim2() -> () {
 }
 */
 
+#[test]
+fn test_intg_with_separate_int_and_side_quad_rules() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_quad_rules(~[1f64, 2.],
+                                                                 ~[2f64, 3.],
+                                                                 ~[MeshCoord(3), MeshCoord(4)],
+                                                                 GaussLegendre(5),
+                                                                 GaussLegendre(2));
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+
+  let fe4 = FENum(4);
+  let int_origin_0 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(0));
+  let int_origin_1 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(1));
+
+  // The 5 point interior rule is exact for this degree 7 integrand.
+  let x3_y4_int = |x:&[R]| -> R {
+    pow(x[0]-int_origin_0, 3) * pow(x[1]-int_origin_1, 4)
+  };
+  assert_approx(rmesh3x4.intg_global_fn_on_fe_int(x3_y4_int, fe4),
+                pow(1./3.,4)/4. * pow(1./4.,5)/5.);
+
+  // The 2 point side rule is exact for the cubic integrand, but not for the quartic.
+  let x3_int = |x:&[R]| -> R { pow(x[0]-int_origin_0, 3) };
+  assert_approx(rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(x3_int, one, fe4, top_side),
+                pow(1./3.,4)/4.);
+
+  let x4_int = |x:&[R]| -> R { pow(x[0]-int_origin_0, 4) };
+  let side_len = 1./3.;
+  let (mid, half_gap) = (side_len/2., side_len/(2.*sqrt(3.)));
+  let gauss_2pt_approx = side_len/2. * (pow(mid - half_gap, 4) + pow(mid + half_gap, 4));
+  let side_intg = rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(x4_int, one, fe4, top_side);
+  assert_approx(side_intg, gauss_2pt_approx);
+  assert!(abs(side_intg - pow(side_len,5)/5.) > 1e-6);
+}
+
+#[test]
+fn test_graded_mesh_2d() -> () {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new_graded(~[0f64, 0.], ~[1f64, 2.],
+                                                     ~[~[0., 0.5, 0.75, 1.], ~[0., 1., 2.]]);
+  // Widths 1/2, 1/4, 1/4 along x and 1, 1 along y, for two oriented shapes.
+  assert_eq!(rmesh.num_fes(), 6);
+  assert_eq!(rmesh.num_oriented_element_shapes(), 2);
+  assert!(rmesh.oriented_shape_for_fe(FENum(0)) == OShape(0));
+  assert!(rmesh.oriented_shape_for_fe(FENum(1)) == OShape(1));
+  assert!(rmesh.oriented_shape_for_fe(FENum(2)) == OShape(1));
+  assert!(rmesh.oriented_shape_for_fe(FENum(3)) == OShape(0));
+
+  assert_eq!(rmesh.fe_min_corner_comp(FENum(2), Dim(0)), 0.75);
+  assert_eq!(rmesh.fe_min_corner_comp(FENum(4), Dim(1)), 1.);
+  assert_eq!(rmesh.fe_side_len(FENum(1), Dim(0)), 0.25);
+  assert_approx(rmesh.max_fe_diameter(), sqrt(0.25 + 1.));
+  assert_approx(rmesh.shape_diameter_inv(OShape(1)), 1./sqrt(0.0625 + 1.));
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  assert_approx(rmesh.intg_facerel_mon_on_oshape_int(x, OShape(0)), 0.125);
+  assert_approx(rmesh.intg_facerel_mon_on_oshape_int(x, OShape(1)), 0.03125);
+  assert_approx(rmesh.intg_facerel_mon_on_oshape_side(one, OShape(1), lesser_side_face_perp_to_axis(Dim(1))), 0.25);
+  assert_approx(rmesh.intg_global_fn_on_fe_int(|v: &[R]| v[0], FENum(2)), (1. - 0.5625)/2.);
+
+  assert!(rmesh.fe_containing_point([0.8, 1.5]) == Some(FENum(5)));
+  assert!(rmesh.fe_containing_point([0.5, 0.]) == Some(FENum(0)));
+  assert!(rmesh.fe_containing_point([0.5001, 0.]) == Some(FENum(1)));
+}
+
+#[test]
+#[should_fail]
+fn test_graded_mesh_coords_must_span_bounds() -> () {
+  let _: RectMesh<Mon2d> = RectMesh::new_graded(~[0f64, 0.], ~[1f64, 2.], ~[~[0., 0.5], ~[0., 1., 2.]]);
+}
+
+#[test]
+fn test_intg_with_gauss_legendre_quad_rule_3d() -> () {
+  let rmesh3x4x5: ~RectMesh<Mon3d> = ~RectMesh::new_with_quad_rule(~[1f64, 2., 3.],
+                                                                  ~[2f64, 3., 4.],
+                                                                  ~[MeshCoord(3), MeshCoord(4), MeshCoord(5)],
+                                                                  GaussLegendre(3));
+  let fe4 = FENum(4);
+  let int_origin_0 = rmesh3x4x5.fe_interior_origin_comp(fe4, Dim(0));
+  let int_origin_1 = rmesh3x4x5.fe_interior_origin_comp(fe4, Dim(1));
+  let int_origin_2 = rmesh3x4x5.fe_interior_origin_comp(fe4, Dim(2));
+
+  // The 3 point tensor product rule is exact for this integrand of degree 5 in x.
+  let x5_y_z2_int = |x:&[R]| -> R {
+    pow(x[0]-int_origin_0, 5) * (x[1]-int_origin_1) * pow(x[2]-int_origin_2, 2)
+  };
+  assert_approx(rmesh3x4x5.intg_global_fn_on_fe_int(x5_y_z2_int, fe4),
+                pow(1./3.,6)/6. * pow(1./4.,2)/2. * pow(1./5.,3)/3.);
+}
+
+#[test]
+fn test_intg_within_max_evals_meeting_tols() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_intg_tol_handling(~[1f64, 2.],
+                                                                        ~[2f64, 3.],
+                                                                        ~[MeshCoord(3), MeshCoord(4)],
+                                                                        1e-10, 1e-10, 1000, FailOnUnmetIntgTol);
+  let fe4 = FENum(4);
+  let int_origin_0 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(0));
+  let x2_int = |x:&[R]| -> R { pow(x[0]-int_origin_0, 2) };
+  assert_approx(rmesh3x4.intg_global_fn_on_fe_int(x2_int, fe4), pow(1./3.,3)/3. * 1./4.);
+}
+
+#[test]
+#[should_fail]
+fn test_intg_fails_on_unmet_tols_for_oscillatory_integrand() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_intg_tol_handling(~[1f64, 2.],
+                                                                        ~[2f64, 3.],
+                                                                        ~[MeshCoord(3), MeshCoord(4)],
+                                                                        1e-12, 1e-12, 50, FailOnUnmetIntgTol);
+  rmesh3x4.intg_global_fn_on_fe_int(|x:&[R]| (300. * x[0]).sin() * (300. * x[1]).cos(), FENum(4));
+}
+
+#[test]
+fn test_intg_with_midpoint_quad_rule() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_quad_rule(~[1f64, 2.],
+                                                                ~[2f64, 3.],
+                                                                ~[MeshCoord(3), MeshCoord(4)],
+                                                                Midpoint);
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+
+  let fe4 = FENum(4);
+  let int_origin_0 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(0));
+  let int_origin_1 = rmesh3x4.fe_interior_origin_comp(fe4, Dim(1));
+
+  // Constants are integrated exactly.
+  assert_approx(rmesh3x4.intg_global_fn_on_fe_int(|_:&[R]| 3., fe4), 3. * 1./3. * 1./4.);
+  assert_approx(rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(|_:&[R]| 3., one, fe4, top_side), 3. * 1./3.);
+
+  // A linear integrand is integrated to within the rule's error bound.
+  let x_plus_y_int = |x:&[R]| -> R { (x[0]-int_origin_0) + (x[1]-int_origin_1) };
+  assert!(abs(rmesh3x4.intg_global_fn_on_fe_int(x_plus_y_int, fe4) - (1./4. * pow(1./3.,2)/2. + 1./3. * pow(1./4.,2)/2.)) < 1e-6);
+
+  // Higher degree integrands are not integrated exactly.
+  let x2_int = |x:&[R]| -> R { pow(x[0]-int_origin_0, 2) };
+  let side_intg = rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(x2_int, one, fe4, top_side);
+  assert_approx(side_intg, 1./3. * pow(1./6., 2));
+  assert!(abs(side_intg - pow(1./3.,3)/3.) > 1e-6);
+}
+
+#[test]
+fn test_intg_mon_on_fe_face_matches_closure_intg() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+  let fe4 = FENum(4);
+
+  for &mon in [one, x, y, x*x*y, x*y*y*y].iter() {
+    assert_approx(rmesh3x4.intg_mon_on_fe_face(mon, fe4, Interior),
+                  rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_int(|_:&[R]| 1., mon, fe4));
+  }
+
+  // Side-relative monomials on sides perpendicular to the y axis, which do not involve y.
+  for &sf in [lesser_side_face_perp_to_axis(Dim(1)), greater_side_face_perp_to_axis(Dim(1))].iter() {
+    for &mon in [one, x, x*x*x].iter() {
+      assert_approx(rmesh3x4.intg_mon_on_fe_face(mon, fe4, Side(sf)),
+                    rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(|_:&[R]| 1., mon, fe4, sf));
+    }
+  }
+}
+
+#[test]
+fn test_single_quad_rule_constr() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new_with_quad_rule(~[1f64, 2.],
+                                                                ~[2f64, 3.],
+                                                                ~[MeshCoord(3), MeshCoord(4)],
+                                                                GaussLegendre(3));
+  assert!(rmesh3x4.int_quad_rule == GaussLegendre(3));
+  assert!(rmesh3x4.side_quad_rule == GaussLegendre(3));
+
+  let default_rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  assert!(default_rmesh3x4.int_quad_rule == SpaceAdaptive);
+  assert!(default_rmesh3x4.side_quad_rule == SpaceAdaptive);
+}
+
 fn assert_approx(a:R, b:R) -> () {
   assert!(abs(a - b) < 10e-9)
 }
 
+
+#[test]
+fn test_intg_mon_x_mon_on_oshape_face_matches_quadrature() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+  let fe4 = FENum(4);
+  let os = rmesh3x4.oriented_shape_for_fe(fe4);
+  let fe4_origin = [rmesh3x4.fe_interior_origin_comp(fe4, Dim(0)), rmesh3x4.fe_interior_origin_comp(fe4, Dim(1))];
+
+  let int_pairs = [(one, one), (x, y), (x*x, x*y), (y*y*y, x), (x*y, x*x*y*y)];
+  for &(mon_1, mon_2) in int_pairs.iter() {
+    assert_approx(rmesh3x4.intg_facerel_mon_x_facerel_mon_on_oshape_face(mon_1, mon_2, os, Interior),
+                  rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_int(|x:&[R]| mon_1.value_at_for_origin(x, fe4_origin), mon_2, fe4));
+  }
+
+  // Side-relative monomials on sides perpendicular to the y axis, which do not involve y.
+  let side_pairs = [(one, one), (x, one), (x, x*x), (x*x*x, x)];
+  for &sf in [lesser_side_face_perp_to_axis(Dim(1)), greater_side_face_perp_to_axis(Dim(1))].iter() {
+    for &(mon_1, mon_2) in side_pairs.iter() {
+      assert_approx(rmesh3x4.intg_facerel_mon_x_facerel_mon_on_oshape_face(mon_1, mon_2, os, Side(sf)),
+                    rmesh3x4.intg_global_fn_x_facerel_mon_on_fe_side(|x:&[R]| mon_1.value_at_for_origin(x, fe4_origin), mon_2, fe4, sf));
+    }
+  }
+}
+
+#[test]
+fn test_intg_facerel_poly_on_oshape_face_unit_square() -> () {
+  let rmesh1x1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[1f64, 1.], ~[MeshCoord(1), MeshCoord(1)]);
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+  let p = poly([(3.,x*x), (2.,x*y)]);
+
+  // ∫ 3x^2 + 2xy over the unit square is 1 + 1/2.
+  assert_approx(rmesh1x1.intg_facerel_poly_on_oshape_face(&p, OShape(0), Interior), 1.5);
+
+  // On the top and bottom sides, the side-relative y is 0, leaving ∫_0^1 3x^2 dx.
+  let (bottom, top) = (lesser_side_face_perp_to_axis(Dim(1)), greater_side_face_perp_to_axis(Dim(1)));
+  assert_approx(rmesh1x1.intg_facerel_poly_on_oshape_face(&p, OShape(0), Side(bottom)), 1.);
+  assert_approx(rmesh1x1.intg_facerel_poly_on_oshape_face(&p, OShape(0), Side(top)), 1.);
+
+  // Terms of degree higher than any basis degree are integrated as well, and a polynomial without terms gives 0.
+  let high_deg = poly([(11.,x*x*x*x*x*x*x*x*x*x*y)]);
+  assert_approx(rmesh1x1.intg_facerel_poly_on_oshape_face(&high_deg, OShape(0), Interior), 0.5);
+  let no_terms: PolyOwning<Mon2d> = poly([]);
+  assert_eq!(rmesh1x1.intg_facerel_poly_on_oshape_face(&no_terms, OShape(0), Interior), 0.);
+}
+
+#[test]
+fn test_intg_facerel_mon_x_facerel_poly_on_oshape_face_3d() -> () {
+  let rmesh3x4x5: ~RectMesh<Mon3d> = ~RectMesh::new(~[1f64, 2., 3.],
+                                                   ~[2f64, 3., 4.],
+                                                   ~[MeshCoord(3), MeshCoord(4), MeshCoord(5)]);
+  let top_side = greater_side_face_perp_to_axis(Dim(1));
+  let x = Mon3d { exps: [Deg(1), Deg(0), Deg(0)] };
+  let y = Mon3d { exps: [Deg(0), Deg(1), Deg(0)] };
+  let z = Mon3d { exps: [Deg(0), Deg(0), Deg(1)] };
+  let (a, b, c) = (1./3., 1./4., 1./5.);
+
+  // xz (2x + 3z + 5xz) = 2x^2z + 3xz^2 + 5x^2z^2 on the side perpendicular to y.
+  let p = poly([(2.,x), (3.,z), (5.,x*z)]);
+  let side_intg = rmesh3x4x5.intg_facerel_mon_x_facerel_poly_on_oshape_face(x*z, &p, OShape(0), Side(top_side));
+  assert_approx(side_intg, 2. * pow(a,3)/3. * pow(c,2)/2. + 3. * pow(a,2)/2. * pow(c,3)/3. + 5. * pow(a,3)/3. * pow(c,3)/3.);
+  assert_approx(side_intg, rmesh3x4x5.intg_facerel_mon_x_facerel_poly_on_oshape_side(x*z, &p, OShape(0), top_side));
+
+  // x (y + 2z) = xy + 2xz over the interior.
+  assert_approx(rmesh3x4x5.intg_facerel_mon_x_facerel_poly_on_oshape_face(x, &poly([(1.,y), (2.,z)]), OShape(0), Interior),
+                pow(a,2)/2. * pow(b,2)/2. * c + 2. * pow(a,2)/2. * b * pow(c,2)/2.);
+}
+
+#[test]
+fn test_intg_siderel_mon_x_intrel_vmon_dot_normal_sign_convention() -> () {
+  let rmesh3x4: ~RectMesh<Mon2d> = ~RectMesh::new(~[1f64, 2.], ~[2f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  let one_2d = Mon2d { exps: [Deg(0), Deg(0)] };
+  let e = |r: uint| VectorMonomial { mon: one_2d, mon_dim: Dim(r) };
+
+  // The outward normal is -e_a on the lesser side and e_a on the greater side perpendicular to axis a.
+  for a in range(0u, 2) {
+    let side_len = if a == 0 { 1./4. } else { 1./3. };
+    let (lesser, greater) = (lesser_side_face_perp_to_axis(Dim(a)), greater_side_face_perp_to_axis(Dim(a)));
+    assert_approx(rmesh3x4.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_2d, &e(a), OShape(0), lesser), -side_len);
+    assert_approx(rmesh3x4.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_2d, &e(a), OShape(0), greater), side_len);
+    // A vector monomial along the other axis is tangent to the sides.
+    assert_eq!(rmesh3x4.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_2d, &e(1-a), OShape(0), lesser), 0.);
+    assert_eq!(rmesh3x4.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_2d, &e(1-a), OShape(0), greater), 0.);
+  }
+
+  let rmesh3x4x5: ~RectMesh<Mon3d> = ~RectMesh::new(~[1f64, 2., 3.],
+                                                   ~[2f64, 3., 4.],
+                                                   ~[MeshCoord(3), MeshCoord(4), MeshCoord(5)]);
+  let one_3d = Mon3d { exps: [Deg(0), Deg(0), Deg(0)] };
+  let z = Mon3d { exps: [Deg(0), Deg(0), Deg(1)] };
+  let (back_side, front_side) = (lesser_side_face_perp_to_axis(Dim(2)), greater_side_face_perp_to_axis(Dim(2)));
+  let e_z = VectorMonomial { mon: one_3d, mon_dim: Dim(2) };
+  assert_approx(rmesh3x4x5.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_3d, &e_z, OShape(0), back_side),
+                -1./3. * 1./4.);
+  assert_approx(rmesh3x4x5.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_3d, &e_z, OShape(0), front_side),
+                1./3. * 1./4.);
+  // The interior-relative z is 0 on the back side, and 1/5 on the front side.
+  let z_e_z = VectorMonomial { mon: z, mon_dim: Dim(2) };
+  assert_eq!(rmesh3x4x5.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_3d, &z_e_z, OShape(0), back_side), 0.);
+  assert_approx(rmesh3x4x5.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(one_3d, &z_e_z, OShape(0), front_side),
+                1./5. * 1./3. * 1./4.);
+}
+
+#[test]
+fn test_fe_containing_point_3x3() -> () {
+  let rmesh: RectMesh<Mon2d> = RectMesh::new(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)]);
+
+  // Interior points.
+  assert!(rmesh.fe_containing_point([0.5, 0.5]) == Some(FENum(0)));
+  assert!(rmesh.fe_containing_point([1.5, 2.25]) == Some(FENum(7)));
+  assert!(rmesh.fe_containing_point([2.9, 1.1]) == Some(FENum(5)));
+
+  // Points on internal sides go to the lower element along the side's perpendicular axis.
+  assert!(rmesh.fe_containing_point([1., 0.5]) == Some(FENum(0)));
+  assert!(rmesh.fe_containing_point([1.5, 2.]) == Some(FENum(4)));
+  assert!(rmesh.fe_containing_point([2., 2.]) == Some(FENum(4)));
+
+  // Points on the outside boundary stay within the boundary elements.
+  assert!(rmesh.fe_containing_point([0., 0.]) == Some(FENum(0)));
+  assert!(rmesh.fe_containing_point([3., 1.5]) == Some(FENum(5)));
+  assert!(rmesh.fe_containing_point([3., 3.]) == Some(FENum(8)));
+
+  // Points outside the mesh.
+  assert!(rmesh.fe_containing_point([-0.1, 1.]).is_none());
+  assert!(rmesh.fe_containing_point([1., 3.1]).is_none());
+  assert!(rmesh.fe_containing_point([4., 4.]).is_none());
+}
+
+#[test]
+fn test_fe_and_nb_side_num_iters_3x4() -> () {
+  let rmesh: RectMesh<Mon2d> = RectMesh::new(~[0f64, 0.], ~[3f64, 4.], ~[MeshCoord(3), MeshCoord(4)]);
+
+  let fes: ~[FENum] = rmesh.fe_nums().collect();
+  assert_eq!(fes.len(), rmesh.num_fes());
+  for (i, fe) in fes.iter().enumerate() { assert!(*fe == FENum(i)); }
+
+  let mut nb_sides: ~[NBSideNum] = rmesh.nb_side_nums().collect();
+  assert_eq!(nb_sides.len(), rmesh.num_nb_sides());
+  assert_eq!(rmesh.nb_side_nums().size_hint(), (17, Some(17)));
+  nb_sides.sort();
+  nb_sides.dedup();
+  assert_eq!(nb_sides.len(), 17);
+}
+
+#[test]
+fn test_fe_quad_rule_override_affects_only_its_fe() -> () {
+  fn sq(x: &[R]) -> R { x[0]*x[0] }
+  let rmesh: RectMesh<Mon2d> =
+    RectMesh::new_with_fe_quad_rule_overrides(~[0f64, 0.], ~[2f64, 1.], ~[MeshCoord(2), MeshCoord(1)],
+                                              Midpoint, ~[(FENum(1), GaussLegendre(3))]);
+  let bottom = lesser_side_face_perp_to_axis(Dim(1));
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+
+  // The midpoint rule on fe 0 integrates x^2 over [0,1] as 0.25 instead of 1/3.
+  assert_approx(rmesh.intg_global_fn_on_fe_int(|x| sq(x), FENum(0)), 0.25);
+  // The overriding rule on fe 1 integrates x^2 over [1,2] exactly, on both the interior and the sides.
+  assert_approx(rmesh.intg_global_fn_on_fe_int(|x| sq(x), FENum(1)), 7./3.);
+  assert_approx(rmesh.intg_global_fn_x_facerel_mon_on_fe_side(|x| sq(x), one, FENum(1), bottom), 7./3.);
+  assert_approx(rmesh.intg_global_fn_x_facerel_mon_on_fe_side(|x| sq(x), one, FENum(0), bottom), 0.25);
+}
+
+#[test]
+fn test_oshape_side_and_interior_measures_anisotropic_3d() -> () {
+  // Elements have dimensions 0.5 x 1 x 2.5.
+  let rmesh: RectMesh<Mon3d> = RectMesh::new(~[0f64, 0., 0.], ~[2f64, 3., 5.], ~[MeshCoord(4), MeshCoord(3), MeshCoord(2)]);
+  for a in range(0, 3) {
+    let expected = [2.5, 1.25, 0.5][a];
+    assert_approx(rmesh.oshape_side_measure(OShape(0), lesser_side_face_perp_to_axis(Dim(a))), expected);
+    assert_approx(rmesh.oshape_side_measure(OShape(0), greater_side_face_perp_to_axis(Dim(a))), expected);
+  }
+  assert_approx(rmesh.oshape_interior_measure(OShape(0)), 1.25);
+
+  let rmesh2d: RectMesh<Mon2d> = RectMesh::new(~[0f64, 0.], ~[3f64, 1.], ~[MeshCoord(1), MeshCoord(4)]);
+  assert_approx(rmesh2d.oshape_side_measure(OShape(0), lesser_side_face_perp_to_axis(Dim(0))), 0.25);
+  assert_approx(rmesh2d.oshape_side_measure(OShape(0), greater_side_face_perp_to_axis(Dim(1))), 3.);
+  assert_approx(rmesh2d.oshape_interior_measure(OShape(0)), 0.75);
+}
+
+#[test]
+fn test_fe_vertices_2d_and_3d() -> () {
+  let rmesh2d: RectMesh<Mon2d> = RectMesh::new(~[0f64, 1.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  // fe 4 is at mesh coordinates (1,1), with dimensions 1 x 0.5.
+  assert_eq!(rmesh2d.fe_vertices(FENum(4)), ~[~[1., 1.5], ~[2., 1.5], ~[1., 2.], ~[2., 2.]]);
+
+  let rmesh3d: RectMesh<Mon3d> = RectMesh::new(~[0f64, 0., 0.], ~[2f64, 2., 4.], ~[MeshCoord(2), MeshCoord(2), MeshCoord(2)]);
+  // fe 7 is at mesh coordinates (1,1,1), with dimensions 1 x 1 x 2.
+  let vs = rmesh3d.fe_vertices(FENum(7));
+  assert_eq!(vs.len(), 8);
+  assert_eq!(vs[0], ~[1., 1., 2.]);
+  assert_eq!(vs[1], ~[2., 1., 2.]);
+  assert_eq!(vs[2], ~[1., 2., 2.]);
+  assert_eq!(vs[5], ~[2., 1., 4.]);
+  assert_eq!(vs[7], ~[2., 2., 4.]);
+}
+
+#[test]
+fn test_fe_interior_origin_into_matches_comps() -> () {
+  let rmesh: RectMesh<Mon2d> = RectMesh::new(~[0f64, 1.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  let mut buf = ~[-1f64, -1.];
+  rmesh.fe_interior_origin_into(FENum(4), buf.as_mut_slice());
+  assert_eq!(buf, ~[1., 1.5]);
+  assert_eq!(rmesh.fe_interior_origin(FENum(11)), ~[2., 2.5]);
+  for fe in range(0, rmesh.num_fes()) { let fe = FENum(fe);
+    rmesh.fe_interior_origin_into(fe, buf.as_mut_slice());
+    assert_eq!(buf, ~[rmesh.fe_interior_origin_comp(fe, Dim(0)), rmesh.fe_interior_origin_comp(fe, Dim(1))]);
+    assert_eq!(rmesh.fe_interior_origin(fe), rmesh.fe_vertices(fe)[0]);
+  }
+}
+
+// Global function integration over all element sides of a 100x100 mesh, which translates each integration point
+// relative to the element origin.
+#[bench]
+fn bench_intg_global_fn_on_fe_sides_100x100(bh: &mut BenchHarness) {
+  let rmesh: RectMesh<Mon2d> = RectMesh::new(~[0f64, 0.], ~[1f64, 1.], ~[MeshCoord(100), MeshCoord(100)]);
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  bh.iter(|| {
+    let mut sum = 0 as R;
+    for fe in range(0, rmesh.num_fes()) {
+      for sf in range(0, 4) {
+        sum += rmesh.intg_global_fn_x_facerel_mon_on_fe_side(|x: &[R]| x[0] * x[1], one, FENum(fe), SideFace(sf));
+      }
+    }
+    assert!(sum > 0.);
+  });
+}
+
+#[test]
+fn test_builder_matches_positional_constructor() -> () {
+  let built: ~RectMesh<Mon2d> = RectMeshBuilder::new().axis(0., 3., 3).axis(1., 3., 4).rel_err(1e-8).abs_err(1e-10)
+                                                      .build().unwrap();
+  let rmesh: RectMesh<Mon2d> = RectMesh::new(~[0f64, 1.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(4)]);
+  assert_eq!(built.min_bounds, rmesh.min_bounds);
+  assert_eq!(built.max_bounds, rmesh.max_bounds);
+  assert_eq!(built.mesh_ldims, rmesh.mesh_ldims);
+  assert_eq!(built.num_fes(), 12);
+  assert_eq!(built.fe_vertices(FENum(4)), rmesh.fe_vertices(FENum(4)));
+  assert_eq!(built.integration_rel_err, 1e-8);
+  assert_eq!(built.integration_abs_err, 1e-10);
+}
+
+#[test]
+fn test_builder_rejects_mismatched_axes_and_zero_divisions() -> () {
+  let too_few: Result<~RectMesh<Mon2d>, MeshError> = RectMeshBuilder::new().axis(0., 1., 2).build();
+  assert!(mesh_error(too_few) == Some(DimensionMismatch(1, 2)));
+  let too_many: Result<~RectMesh<Mon2d>, MeshError> = RectMeshBuilder::new().axis(0., 1., 2).axis(0., 1., 2).axis(0., 1., 2).build();
+  assert!(mesh_error(too_many) == Some(DimensionMismatch(3, 2)));
+  let zero_divs: Result<~RectMesh<Mon2d>, MeshError> = RectMeshBuilder::new().axis(0., 1., 2).axis(0., 1., 0).build();
+  assert!(mesh_error(zero_divs) == Some(EmptyAxis(Dim(1))));
+  let reversed: Result<~RectMesh<Mon2d>, MeshError> = RectMeshBuilder::new().axis(1., 0., 2).axis(0., 1., 2).build();
+  assert!(mesh_error(reversed) == Some(InvertedBounds(Dim(0))));
+  let neg_tol: Result<~RectMesh<Mon2d>, MeshError> = RectMeshBuilder::new().axis(0., 1., 2).axis(0., 1., 2).rel_err(-1.).build();
+  assert!(mesh_error(neg_tol) == Some(NegativeIntgTol));
+  let ok: Result<~RectMesh<Mon1d>, MeshError> = RectMeshBuilder::new().axis(0., 1., 2).build();
+  assert!(ok.is_ok());
+}
+
+fn mesh_error<Mon>(res: Result<~RectMesh<Mon>, MeshError>) -> Option<MeshError> {
+  match res { Err(e) => Some(e), Ok(_) => None }
+}
+
+#[test]
+fn test_try_new_error_variants() -> () {
+  let mismatched: Result<~RectMesh<Mon2d>, MeshError> = RectMesh::try_new(~[0f64, 0.], ~[1f64, 1., 1.], ~[MeshCoord(2), MeshCoord(2)]);
+  assert!(mesh_error(mismatched) == Some(DimensionMismatch(3, 2)));
+  let mismatched_ldims: Result<~RectMesh<Mon2d>, MeshError> = RectMesh::try_new(~[0f64, 0.], ~[1f64, 1.], ~[MeshCoord(2)]);
+  assert!(mesh_error(mismatched_ldims) == Some(DimensionMismatch(1, 2)));
+  let empty: Result<~RectMesh<Mon2d>, MeshError> = RectMesh::try_new(~[0f64, 0.], ~[1f64, 1.], ~[MeshCoord(2), MeshCoord(0)]);
+  assert!(mesh_error(empty) == Some(EmptyAxis(Dim(1))));
+  let inverted: Result<~RectMesh<Mon2d>, MeshError> = RectMesh::try_new(~[0f64, 1.], ~[1f64, 1.], ~[MeshCoord(2), MeshCoord(2)]);
+  assert!(mesh_error(inverted) == Some(InvertedBounds(Dim(1))));
+  let ok: Result<~RectMesh<Mon2d>, MeshError> = RectMesh::try_new(~[0f64, 0.], ~[1f64, 2.], ~[MeshCoord(2), MeshCoord(4)]);
+  match ok {
+    Ok(rmesh) => assert_eq!(rmesh.num_fes(), 8),
+    Err(e) => fail!(e.to_str())
+  }
+}
+
+#[test]
+#[should_fail]
+fn test_new_fails_for_inverted_bounds() -> () {
+  let _: RectMesh<Mon2d> = RectMesh::new(~[0f64, 2.], ~[1f64, 1.], ~[MeshCoord(2), MeshCoord(2)]);
+}
@@ -1,8 +1,17 @@
 use sparse_matrix::*;
 use la::lapack_int;
 use la;
+use common::R;
+use dense_matrix::DenseMatrix;
 
 use std::vec;
+use std::ptr;
+use std::cast;
+use std::io::Decorator;
+use std::io::mem::{MemWriter, MemReader};
+use std::io::buffered::BufferedReader;
+use std::str;
+use std::num::{abs, sqrt};
 
 #[test]
 fn test_do_la_init() {
@@ -98,3 +107,346 @@ fn test_3x4_csr3() {
   assert_eq!(m.num_values(), 7);
 }
 
+
+#[test]
+fn test_4x4_general_mat_vec() {
+  let mut m = SparseMatrix::new_with_capacities(8, 4, General);
+  m.push(0,0, 0.); m.push(0,1, 1.);
+  m.push(1,0, 3.); m.push(1,2, 5.);
+  m.push(2,0, 6.);
+  m.push(3,1, 10.); m.push(3,2, 11.); m.push(3,3, 1.);
+
+  assert_eq!(m.mat_vec([1.,2.,3.,4.]), ~[2., 18., 6., 57.]);
+}
+
+#[test]
+fn test_3x3_symmetric_mat_vec() {
+  //      1 2 3
+  // A =  2 2 0
+  //      3 0 3
+  // with only the upper triangle stored.
+  let mut m = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+  m.push(1,1, 2.);
+  m.push(2,2, 3.);
+
+  assert_eq!(m.mat_vec([1.,1.,1.]), ~[6., 4., 6.]);
+  assert_eq!(m.mat_vec([0.,1.,1./3.]), ~[3., 2., 1.]);
+}
+
+#[test]
+fn test_3x3_galerkin_products_by_hand() {
+  //      1   0
+  // P = 1/2 1/2
+  //      0   1
+  let mut p = SparseMatrix::new_with_capacities(4, 3, General);
+  p.push(0,0, 1.);
+  p.push(1,0, 0.5); p.push(1,1, 0.5);
+  p.push(2,1, 1.);
+
+  //      2 -1  0
+  // A = -1  2 -1
+  //      0 -1  2
+  // with only the upper triangle stored. The middle row of AP is zero.
+  let mut a = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  a.push(0,0, 2.); a.push(0,1, -1.);
+  a.push(1,1, 2.); a.push(1,2, -1.);
+  a.push(2,2, 2.);
+  let pt_a_p = a.galerkin_product(&p, 2);
+  match pt_a_p.matrix_type() { Symmetric => {}, _ => fail!("Expected a symmetric product.") }
+  let entries: ~[(uint,uint,R)] = pt_a_p.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.5), (0,1,-0.5), (1,1,1.5)]);
+
+  //      2 -1  0
+  // B =  0  2 -1
+  //     -1  0  2
+  let mut b = SparseMatrix::new_with_capacities(6, 3, General);
+  b.push(0,0, 2.); b.push(0,1, -1.);
+  b.push(1,1, 2.); b.push(1,2, -1.);
+  b.push(2,0, -1.); b.push(2,2, 2.);
+  let pt_b_p = b.galerkin_product(&p, 2);
+  match pt_b_p.matrix_type() { General => {}, _ => fail!("Expected a general product.") }
+  let entries: ~[(uint,uint,R)] = pt_b_p.entries().collect();
+  assert_eq!(entries, ~[(0,0,2.), (0,1,-0.5), (1,0,-0.5), (1,1,2.)]);
+}
+
+#[test]
+fn test_4x3_entries() {
+  let mut m = SparseMatrix::new_with_capacities(7, 4, General);
+  m.push(0,0, 0.); m.push(0,1, 1.);
+  m.push(1,0, 3.); m.push(1,2, 5.);
+  m.push(2,0, 6.);
+  m.push(3,1, 10.); m.push(3,2, 11.);
+
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,0.), (0,1,1.), (1,0,3.), (1,2,5.), (2,0,6.), (3,1,10.), (3,2,11.)]);
+
+  for first in range(0, entries.len() + 1) {
+    let entries_from: ~[(uint,uint,R)] = m.entries_from(first).collect();
+    assert_eq!(entries_from.as_slice(), entries.slice_from(first));
+  }
+}
+
+#[test]
+fn test_3x3_symmetric_entries() {
+  let mut m = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+  m.push(1,1, 2.);
+  m.push(2,2, 3.);
+
+  // Only the upper triangle is stored.
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (0,1,2.), (0,2,3.), (1,1,2.), (2,2,3.)]);
+}
+
+#[test]
+fn test_3x3_symmetric_norms_vs_dense() {
+  //      1  2 3
+  // A =  2 -4 0
+  //      3  0 3
+  let mut m = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+  m.push(1,1, -4.);
+  m.push(2,2, 3.);
+
+  let dense = DenseMatrix::from_rows(3,3,
+    [~[1.,  2., 3.],
+     ~[2., -4., 0.],
+     ~[3.,  0., 3.]]);
+
+  assert!(abs(m.frobenius_norm() - dense.frobenius_norm()) < 1e-14);
+  assert!(abs(m.frobenius_norm() - sqrt(52.)) < 1e-14);
+  assert_eq!(m.max_abs(), 4.);
+  assert_eq!(dense.max_abs(), 4.);
+}
+
+#[test]
+fn test_4x3_general_norms() {
+  let mut m = SparseMatrix::new_with_capacities(7, 4, General);
+  m.push(0,0, 0.); m.push(0,1, 1.);
+  m.push(1,0, 3.); m.push(1,2, 5.);
+  m.push(2,0, 6.);
+  m.push(3,1, -10.); m.push(3,2, 11.);
+
+  assert!(abs(m.frobenius_norm() - sqrt(292.)) < 1e-14);
+  assert_eq!(m.max_abs(), 11.);
+}
+
+#[test]
+fn test_diagonal_dominance_general_and_symmetric() {
+  //      4 -1  2
+  // A = -1  3  1
+  //      0  2  5
+  let mut dominant = SparseMatrix::new_with_capacities(8, 3, General);
+  dominant.push(0,0, 4.); dominant.push(0,1, -1.); dominant.push(0,2, 2.);
+  dominant.push(1,0, -1.); dominant.push(1,1, 3.); dominant.push(1,2, 1.);
+  dominant.push(2,1, 2.); dominant.push(2,2, 5.);
+  assert!(dominant.is_diagonally_dominant());
+
+  // Row 1 has |-2| < |-1| + |2|.
+  let mut not_dominant = SparseMatrix::new_with_capacities(6, 3, General);
+  not_dominant.push(0,0, 4.); not_dominant.push(0,1, -1.);
+  not_dominant.push(1,0, -1.); not_dominant.push(1,1, -2.); not_dominant.push(1,2, 2.);
+  not_dominant.push(2,2, 5.);
+  assert!(!not_dominant.is_diagonally_dominant());
+
+  //      4  2 1
+  // A =  2  2 0
+  //      1  0 2
+  // The stored upper triangle row 1 is dominant alone, but with the mirrored entry 2 row 1 is not strictly dominant.
+  let mut sym = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  sym.push(0,0, 4.); sym.push(0,1, 2.); sym.push(0,2, 1.);
+  sym.push(1,1, 2.);
+  sym.push(2,2, 2.);
+  assert!(!sym.is_diagonally_dominant());
+  let mut sym_dominant = SparseMatrix::new_with_capacities(5, 3, Symmetric);
+  sym_dominant.push(0,0, 4.); sym_dominant.push(0,1, 2.); sym_dominant.push(0,2, 1.);
+  sym_dominant.push(1,1, 3.);
+  sym_dominant.push(2,2, 2.);
+  assert!(sym_dominant.is_diagonally_dominant());
+}
+
+#[test]
+fn test_3x3_struct_sym_mirrors_for_upper_pushes() {
+  //      1 2 3
+  // A =  . 4 0
+  //      . . 5
+  // with only the upper triangle pushed, so zero mirrors should be added at (1,0), (2,0) and (2,1).
+  let mut m = SparseMatrix::new_with_capacities(9, 3, StructurallySymmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+  m.push(1,1, 4.); m.push(1,2, 0.);
+  m.push(2,2, 5.);
+  m.finish_struct_sym();
+
+  unsafe {
+    let (vals, row_begins, cols) = m.csr3_ptrs();
+    assert_eq!(vec::from_buf(vals, 9), ~[1.,2.,3., 0.,4.,0., 0.,0.,5.]);
+    assert_eq!(vec::from_buf(cols, 9), ~[0,1,2, 0,1,2, 0,1,2 as lapack_int]);
+    assert_eq!(vec::from_buf(row_begins, 4), ~[0,3,6,9 as lapack_int]);
+  }
+  assert_eq!(m.num_values(), 9);
+}
+
+#[test]
+fn test_3x3_struct_sym_no_extra_mirrors_when_pairs_pushed() {
+  let mut m = SparseMatrix::new_with_capacities(5, 3, StructurallySymmetric);
+  m.push(0,0, 1.); m.push(0,2, 3.);
+  m.push(1,1, 4.);
+  m.push(2,0, 6.); m.push(2,2, 5.);
+  m.finish_struct_sym();
+
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (0,2,3.), (1,1,4.), (2,0,6.), (2,2,5.)]);
+}
+
+#[test]
+fn test_3x3_struct_sym_mirror_for_lower_push() {
+  // The lower entry at (2,0) is pushed without its mirror at (0,2), which must be inserted into the completed row 0.
+  // The values capacity leaves no room for the mirror, which the finishing step must provide.
+  let mut m = SparseMatrix::new_with_capacities(4, 3, StructurallySymmetric);
+  m.push(0,0, 1.);
+  m.push(1,1, 4.);
+  m.push(2,0, 6.); m.push(2,2, 5.);
+  m.finish_struct_sym();
+
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (0,2,0.), (1,1,4.), (2,0,6.), (2,2,5.)]);
+  assert_eq!(m.get(1,1), 4.);
+}
+
+#[test]
+fn test_3x3_struct_sym_mirrors_in_unpushed_rows() {
+  // Row 2 is represented only by the mirror of the pushed entry (1,2).
+  let mut m = SparseMatrix::new_with_capacities(3, 3, StructurallySymmetric);
+  m.push(0,0, 1.);
+  m.push(1,1, 4.); m.push(1,2, 2.);
+  m.finish_struct_sym();
+
+  assert_eq!(m.num_rows(), 3);
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (1,1,4.), (1,2,2.), (2,1,0.)]);
+}
+
+#[test]
+fn test_3x3_struct_sym_reads_before_finish_do_not_add_mirrors() {
+  let mut m = SparseMatrix::new_with_capacities(4, 3, StructurallySymmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.);
+  assert_eq!(m.get(0,1), 2.);
+  assert_eq!(m.num_values(), 2);
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (0,1,2.)]);
+
+  // Pushing continues in the same row, which an earlier addition of the mirror at (1,0) would have prevented.
+  m.push(0,2, 3.);
+  m.push(1,0, 2.); m.push(1,1, 1.);
+  m.finish_struct_sym();
+  let entries: ~[(uint,uint,R)] = m.entries().collect();
+  assert_eq!(entries, ~[(0,0,1.), (0,1,2.), (0,2,3.), (1,0,2.), (1,1,1.), (2,0,0.)]);
+}
+
+#[test]
+fn test_validate_well_formed_and_corrupted() {
+  let new_m = || {
+    let mut m = SparseMatrix::new_with_capacities(6, 3, Symmetric);
+    m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+    m.push(1,1, 4.); m.push(1,2, 5.);
+    m.push(2,2, 6.);
+    m
+  };
+
+  assert!(new_m().validate().is_ok());
+
+  let unsorted = new_m();
+  unsafe {
+    let (_, _, cols) = unsorted.csr3_ptrs();
+    *ptr::mut_offset(cast::transmute::<*lapack_int,*mut lapack_int>(cols), 2) = 1;
+  }
+  match unsorted.validate() {
+    Err(msg) => assert!(msg.contains("does not follow previous column")),
+    Ok(_) => fail!("Unsorted columns were not detected.")
+  }
+
+  let out_of_range = new_m();
+  unsafe {
+    let (_, _, cols) = out_of_range.csr3_ptrs();
+    *ptr::mut_offset(cast::transmute::<*lapack_int,*mut lapack_int>(cols), 4) = 7;
+  }
+  match out_of_range.validate() {
+    Err(msg) => assert!(msg.contains("out of range")),
+    Ok(_) => fail!("Out of range column was not detected.")
+  }
+
+  let non_monotonic = new_m();
+  unsafe {
+    let (_, row_begins, _) = non_monotonic.csr3_ptrs();
+    *ptr::mut_offset(cast::transmute::<*lapack_int,*mut lapack_int>(row_begins), 2) = 2;
+  }
+  match non_monotonic.validate() {
+    Err(msg) => assert!(msg.contains("invalid value index range")),
+    Ok(_) => fail!("Non-monotonic row beginnings were not detected.")
+  }
+}
+
+#[test]
+fn test_csr_binary_round_trip() {
+  let mut m = SparseMatrix::new_with_capacities(9, 3, StructurallySymmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.); m.push(0,2, 3.);
+  m.push(1,1, 4.);
+  m.push(2,2, 5.);
+  m.finish_struct_sym();
+
+  let mut w = MemWriter::new();
+  m.write_csr_binary(&mut w);
+  let restored = SparseMatrix::read_csr_binary(&mut MemReader::new(w.inner()));
+
+  assert_eq!(restored.num_rows(), m.num_rows());
+  assert_eq!(restored.num_values(), m.num_values());
+  match restored.matrix_type() { StructurallySymmetric => {}, _ => fail!("Matrix type was not preserved.") }
+  let nnz = m.num_values();
+  unsafe {
+    let (vals, row_begins, cols) = m.csr3_ptrs();
+    let (restored_vals, restored_row_begins, restored_cols) = restored.csr3_ptrs();
+    assert_eq!(vec::from_buf(restored_vals, nnz), vec::from_buf(vals, nnz));
+    assert_eq!(vec::from_buf(restored_cols, nnz), vec::from_buf(cols, nnz));
+    assert_eq!(vec::from_buf(restored_row_begins, 4), vec::from_buf(row_begins, 4));
+  }
+}
+
+#[test]
+fn test_matrix_market_round_trip() {
+  let mut m = SparseMatrix::new_with_capacities(4, 3, Symmetric);
+  m.push(0,0, 1./3.); m.push(0,2, -2.5e-11);
+  m.push(1,1, 4.);
+  m.push(2,2, 5.);
+
+  let mut w = MemWriter::new();
+  m.write_matrix_market(&mut w);
+  let restored = SparseMatrix::read_matrix_market(&mut BufferedReader::new(MemReader::new(w.inner())));
+
+  match restored.matrix_type() { Symmetric => {}, _ => fail!("Matrix type was not preserved.") }
+  assert_eq!(restored.entries().collect::<~[(uint,uint,R)]>(), m.entries().collect::<~[(uint,uint,R)]>());
+}
+
+#[test]
+fn test_matrix_market_symmetric_written_as_lower_triangle() {
+  let mut m = SparseMatrix::new_with_capacities(3, 2, Symmetric);
+  m.push(0,0, 1.); m.push(0,1, 2.);
+  m.push(1,1, 3.);
+
+  let mut w = MemWriter::new();
+  m.write_matrix_market(&mut w);
+  let text = str::from_utf8_owned(w.inner());
+  let lines: ~[&str] = text.lines().collect();
+  assert!(lines[0].starts_with("%%MatrixMarket matrix coordinate real symmetric"));
+  assert_eq!(lines[2], "2 2 3");
+  let entry_ixs: ~[~[&str]] = lines.slice_from(3).iter().map(|l| l.words().take(2).collect()).collect();
+  assert_eq!(entry_ixs, ~[~["1","1"], ~["2","1"], ~["2","2"]]);
+}
+
+#[test]
+#[should_fail]
+fn test_csr_binary_bad_version() {
+  let mut w = MemWriter::new();
+  w.write_le_u64(CSR_BINARY_FORMAT_VERSION + 1);
+  SparseMatrix::read_csr_binary(&mut MemReader::new(w.inner()));
+}
@@ -0,0 +1,32 @@
+use la;
+use common::R;
+use monomial::{Mon2d, MaxMonDeg};
+use rectangle_mesh::{RectMesh, MeshCoord};
+use wg_basis::WGBasis;
+use vbf_laplace::VBFLaplace;
+use variational_bilinear_form::VariationalBilinearForm;
+use dense_matrix::DenseMatrix;
+use static_condensation::CondensedSystem;
+
+use std::num::abs;
+
+#[test]
+fn test_condensed_solve_matches_full_solve_4x3() {
+  la::init();
+
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(4),MeshCoord(3)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+  let rhs = DenseMatrix::from_fn(num_els, 1, |i,_| 1. + (i % 7) as R);
+
+  let full_sol = la::solve_sparse(&vbf.basis_els_vs_basis_els_transpose(), &rhs);
+
+  let cond = CondensedSystem::new(&vbf, &rhs);
+  assert_eq!(cond.sys().num_rows(), num_els - vbf.basis().num_int_els());
+  let cond_sol = cond.solve();
+
+  assert_eq!(cond_sol.len(), full_sol.len());
+  for i in range(0, full_sol.len()) {
+    assert!(abs(cond_sol[i] - full_sol[i]) < 1e-8);
+  }
+}
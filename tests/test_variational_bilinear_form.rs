@@ -1,10 +1,13 @@
-use variational_bilinear_form::VariationalBilinearForm;
+use variational_bilinear_form::{VariationalBilinearForm, AssemblyCheckpoint};
 use common::{R};
 use monomial::{Monomial, Mon2d, MaxMonDeg};
 use mesh::{Mesh, OShape, SideFace};
 use rectangle_mesh::{RectMesh, MeshCoord};
 use wg_basis::{WGBasis, FaceMonNum, BasisElNum};
 
+use std::io::Decorator;
+use std::io::mem::{MemWriter, MemReader};
+
 
 struct AsymmetricTestVBF<Mon,MeshT> {
   basis: ~WGBasis<Mon,MeshT>,
@@ -240,3 +243,75 @@ fn test_symmetric() {
   }
 }
 
+
+#[test]
+fn test_single_fe_mesh_has_only_int_els() {
+  // All sides of the single finite element are boundary sides, which support no basis elements.
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  let vbf: SymmetricTestVBF<Mon2d,RectMesh<Mon2d>> = SymmetricTestVBF { basis: basis };
+
+  let m = vbf.basis_els_vs_basis_els_transpose();
+
+  let basis = vbf.basis();
+  assert_eq!(basis.num_els(), basis.mons_per_fe_int());
+  assert_eq!(m.num_rows(), basis.mons_per_fe_int());
+  for r in range(0, basis.num_els()) {
+    assert!(basis.is_int_supported(BasisElNum(r)));
+  }
+}
+
+#[test]
+fn test_asymmetric_assembly_resumed_from_checkpoint_in_two_halves() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(5),MeshCoord(4)]);
+  let basis = ~WGBasis::new(rmesh, MaxMonDeg(3), MaxMonDeg(2));
+  let vbf: AsymmetricTestVBF<Mon2d,RectMesh<Mon2d>> = AsymmetricTestVBF { basis: basis };
+
+  let full_m = vbf.basis_els_vs_basis_els_transpose();
+
+  // Interrupt assembly after the first half of the finite elements.
+  let checkpoint = match vbf.basis_els_vs_basis_els_transpose_resumable(None, 10, |_| false) {
+    Err(cp) => cp,
+    Ok(_) => fail!("Expected assembly to stop at the first checkpoint.")
+  };
+  assert_eq!(checkpoint.fes_completed, 10);
+  assert_eq!(checkpoint.nb_sides_completed, 0);
+
+  // Serialize and restore the checkpoint, then resume to completion.
+  let mut w = MemWriter::new();
+  checkpoint.write_to(&mut w);
+  let restored = AssemblyCheckpoint::read_from(&mut MemReader::new(w.inner()));
+  assert!(restored == checkpoint);
+
+  let resumed_m = match vbf.basis_els_vs_basis_els_transpose_resumable(Some(restored), 0, |_| true) {
+    Ok(m) => m, Err(_) => fail!("Expected resumed assembly to complete.")
+  };
+
+  assert_eq!(resumed_m.num_rows(), full_m.num_rows());
+  assert_eq!(resumed_m.entries().to_owned_vec(), full_m.entries().to_owned_vec());
+}
+
+#[test]
+fn test_symmetric_assembly_resumed_from_checkpoint_within_sides() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(5),MeshCoord(4)]);
+  let basis = ~WGBasis::new(rmesh, MaxMonDeg(3), MaxMonDeg(2));
+  let vbf: SymmetricTestVBF<Mon2d,RectMesh<Mon2d>> = SymmetricTestVBF { basis: basis };
+  let num_nb_sides = vbf.basis().mesh().num_nb_sides();
+
+  let full_m = vbf.basis_els_vs_basis_els_transpose();
+
+  // Continue through the interior supported rows, stopping halfway through the non-boundary sides.
+  let checkpoint = match vbf.basis_els_vs_basis_els_transpose_resumable(None, 1, |cp| cp.nb_sides_completed < num_nb_sides/2) {
+    Err(cp) => cp,
+    Ok(_) => fail!("Expected assembly to stop at a side checkpoint.")
+  };
+  assert_eq!(checkpoint.fes_completed, 20);
+  assert_eq!(checkpoint.nb_sides_completed, num_nb_sides/2);
+
+  let resumed_m = match vbf.basis_els_vs_basis_els_transpose_resumable(Some(checkpoint), 0, |_| true) {
+    Ok(m) => m, Err(_) => fail!("Expected resumed assembly to complete.")
+  };
+
+  assert_eq!(resumed_m.entries().to_owned_vec(), full_m.entries().to_owned_vec());
+}
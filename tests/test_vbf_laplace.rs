@@ -1,13 +1,15 @@
 use vbf_laplace::VBFLaplace;
 use variational_bilinear_form::VariationalBilinearForm;
-use common::{Deg};
+use common::{R, Deg};
 use monomial::{Mon2d, MaxMonDeg};
 use dense_matrix::DenseMatrix;
 use mesh::{Mesh, OShape, SideFace};
 use rectangle_mesh::{RectMesh, MeshCoord};
 use wg_basis::{WGBasis, FaceMonNum};
+use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric, General};
+use la;
 
-use std::num::{sqrt};
+use std::num::{sqrt, abs};
 
 #[test]
 fn test_is_symmetric() {
@@ -355,3 +357,311 @@ fn test_bottom_side_vs_bottom_side() {
              ips_term + stab_term);
 }
 
+
+#[test]
+fn test_sys_annihilates_const_4x4() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(4),MeshCoord(4)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let sys = vbf.basis_els_vs_basis_els_transpose();
+
+  assert!(vbf.basis().check_constant_nullspace(&sys, 1e-9));
+}
+
+#[test]
+fn test_sys_annihilates_const_on_graded_mesh() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new_graded(~[0.,0.], ~[2.,2.],
+                                                     ~[~[0., 0.25, 0.5, 1., 2.], ~[0., 0.5, 1., 1.5, 2.]]);
+  assert_eq!(rmesh.num_oriented_element_shapes(), 3);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let sys = vbf.basis_els_vs_basis_els_transpose();
+
+  assert!(vbf.basis().check_constant_nullspace(&sys, 1e-9));
+}
+
+#[test]
+fn test_sys_with_misapplied_stabilization_does_not_annihilate_const_4x4() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(4),MeshCoord(4)]);
+  let vbf = BadStabVBF { vbf: VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1))) };
+  let sys = vbf.basis_els_vs_basis_els_transpose();
+
+  assert!(!vbf.basis().check_constant_nullspace(&sys, 1e-9));
+}
+
+// A Laplace form with the stabilization term wrongly applied over element interiors in addition to element boundaries.
+struct BadStabVBF {
+  vbf: VBFLaplace<Mon2d,RectMesh<Mon2d>>
+}
+
+impl VariationalBilinearForm<Mon2d,RectMesh<Mon2d>> for BadStabVBF {
+
+  fn basis<'a>(&'a self) -> &'a WGBasis<Mon2d,RectMesh<Mon2d>> { self.vbf.basis() }
+
+  fn is_symmetric(&self) -> bool { true }
+
+  fn int_mon_vs_int_mon(&self, oshape: OShape, monn_1: FaceMonNum, monn_2: FaceMonNum) -> R {
+    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let int_mons = basis.ref_int_mons();
+    let bad_stab_term = mesh.shape_diameter_inv(oshape) *
+      mesh.intg_facerel_mon_on_oshape_int(int_mons[*monn_1] * int_mons[*monn_2], oshape);
+    self.vbf.int_mon_vs_int_mon(oshape, monn_1, monn_2) + bad_stab_term
+  }
+
+  fn side_mon_vs_int_mon(&self, oshape: OShape, side_monn: FaceMonNum, side_face: SideFace, int_monn: FaceMonNum) -> R {
+    self.vbf.side_mon_vs_int_mon(oshape, side_monn, side_face, int_monn)
+  }
+
+  fn int_mon_vs_side_mon(&self, oshape: OShape, int_monn: FaceMonNum, side_monn: FaceMonNum, side_face: SideFace) -> R {
+    self.vbf.int_mon_vs_side_mon(oshape, int_monn, side_monn, side_face)
+  }
+
+  fn side_mon_vs_side_mon_fe_contr(&self, oshape: OShape,
+                                   monn_1: FaceMonNum, side_face_1: SideFace,
+                                   monn_2: FaceMonNum, side_face_2: SideFace) -> R {
+    self.vbf.side_mon_vs_side_mon_fe_contr(oshape, monn_1, side_face_1, monn_2, side_face_2)
+  }
+}
+
+#[test]
+fn test_sys_blocks_reassemble_to_sys_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let basis = vbf.basis();
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  let (int_int, int_side, side_side) = vbf.basis_els_vs_basis_els_transpose_blocks();
+
+  let num_int_els = basis.num_int_els();
+  let num_side_els = basis.num_els() - num_int_els;
+  assert_eq!(int_int.num_rows(), num_int_els);
+  assert_eq!(int_side.num_rows(), num_int_els);
+  assert_eq!(side_side.num_rows(), num_side_els);
+
+  for r in range(0, basis.num_els()) {
+    for c in range(r, basis.num_els()) {
+      let block_val =
+        if c < num_int_els { int_int.get(r, c) }
+        else if r < num_int_els { int_side.get(r, c - num_int_els) }
+        else { side_side.get(r - num_int_els, c - num_int_els) };
+      assert_eq!(block_val, m.get(r, c));
+    }
+  }
+
+  // The interior-interior block is block diagonal by finite element.
+  for r in range(0, num_int_els) {
+    for c in range(r, num_int_els) {
+      if r / basis.mons_per_fe_int() != c / basis.mons_per_fe_int() {
+        assert_eq!(int_int.get(r, c), 0.);
+      }
+    }
+  }
+}
+
+fn assert_csr3_matches_sys(csr: &(~[la::mkl_int], ~[la::mkl_int], ~[R]), m: &SparseMatrix) {
+  let &(ref ia, ref ja, ref a) = csr;
+  assert_eq!(ia.len(), m.num_rows() + 1);
+  for r in range(0, m.num_rows()) {
+    for i in range(ia[r] as uint, ia[r+1] as uint) {
+      assert!(abs(a[i] - m.get(r, ja[i] as uint)) < 1e-12);
+    }
+  }
+  // Every entry of the assembled matrix which is not an explicit zero is in the pattern.
+  for (r, c, v) in m.entries() {
+    if v != 0. {
+      assert!(range(ia[r] as uint, ia[r+1] as uint).any(|i| ja[i] as uint == c));
+    }
+  }
+}
+
+#[test]
+fn test_assemble_stiffness_csr3_with_reused_pattern_matches_sys_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let pattern = vbf.stiffness_sparsity_pattern();
+
+  let csr = vbf.assemble_stiffness_csr3(&pattern);
+  assert_csr3_matches_sys(&csr, &vbf.basis_els_vs_basis_els_transpose());
+
+  // Assembling again with the same pattern gives the same arrays.
+  let csr_again = vbf.assemble_stiffness_csr3(&pattern);
+  assert_eq!(csr_again, csr);
+}
+
+#[test]
+fn test_assemble_stiffness_csr3_asym_matches_sys_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let k = DenseMatrix::from_fn(2,2, |r,c| { if r == c { 2. } else if r < c { 0.5 } else { 0. } });
+  let vbf = VBFLaplace::new(Some(k), ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  assert!(!vbf.is_symmetric());
+  let pattern = vbf.stiffness_sparsity_pattern();
+
+  assert_csr3_matches_sys(&vbf.assemble_stiffness_csr3(&pattern), &vbf.basis_els_vs_basis_els_transpose());
+}
+
+#[test]
+fn test_diffusion_tensor_identity_matches_sys_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  let m_k = vbf.assemble_with_diffusion_tensor(|_| ~[~[1.,0.],~[0.,1.]]);
+
+  assert_eq!(m_k.num_rows(), num_els);
+  for r in range(0, num_els) {
+    for c in range(r, num_els) {
+      assert!(abs(m_k.get(r, c) - m.get(r, c)) < 1e-10);
+      assert_eq!(m_k.get(c, r), m_k.get(r, c));
+    }
+  }
+}
+
+#[test]
+fn test_diffusion_tensor_anisotropic_sym_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  let m_k = vbf.assemble_with_diffusion_tensor(|x| ~[~[2.,0.],~[0.,1.+x[0]*x[0]]]);
+
+  let mut num_differing = 0u;
+  for r in range(0, num_els) {
+    for c in range(r, num_els) {
+      assert!(abs(m_k.get(c, r) - m_k.get(r, c)) < 1e-10);
+      if abs(m_k.get(r, c) - m.get(r, c)) > 1e-6 { num_differing += 1; }
+    }
+  }
+  assert!(num_differing > 0);
+}
+
+#[test]
+fn test_stiffness_triplet_iter_sums_to_sys_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+
+  // The lazily iterated triplets are those computed eagerly, and summing these per-element contributions of both
+  // triangles must give the independently assembled symmetric system.
+  let iterated: ~[(uint,uint,R)] = vbf.stiffness_triplet_iter().collect();
+  assert_eq!(&iterated, &vbf.stiffness_triplets(false));
+  let m_iterated = SparseMatrix::from_triplets(num_els, iterated, General);
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  for r in range(0, num_els) {
+    for c in range(r, num_els) {
+      assert!(abs(m_iterated.get(r, c) - m.get(r, c)) < 1e-10);
+      assert!(abs(m_iterated.get(c, r) - m.get(r, c)) < 1e-10);
+    }
+  }
+}
+
+#[test]
+fn test_upper_only_stiffness_triplets_are_upper_triplets_in_order_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+
+  let upper: ~[(uint,uint,R)] = vbf.stiffness_triplets(false).move_iter().filter(|&(r, c, _)| r <= c).collect();
+  assert_eq!(vbf.stiffness_triplets(true), upper);
+}
+
+#[test]
+fn test_stiffness_trace_matches_assembled_diagonal_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+
+  let diag = vbf.basis_els_vs_basis_els_transpose().diagonal();
+  assert_eq!(diag.len(), vbf.basis().num_els());
+  let diag_sum = diag.iter().fold(0 as R, |sum, &d| sum + d);
+
+  let trace = vbf.stiffness_trace();
+  assert!(trace > 0.);
+  assert!(abs(trace - diag_sum) <= 1e-10 * diag_sum);
+}
+
+#[test]
+fn test_assemble_with_drop_tol_reduces_nnz_keeping_solution_3x2() {
+  la::init();
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+
+  let m = vbf.assemble_with_drop_tol(0.);
+  let max_contr = vbf.stiffness_triplets(true).iter().fold(0 as R, |max, &(_, _, v)| if abs(v) > max { abs(v) } else { max });
+  let tol = 1e-10 * max_contr;
+  let m_dropped = vbf.assemble_with_drop_tol(tol);
+  // Elements supported on perpendicular sides of a finite element have weak gradients along different axes and no
+  // common side, so their entries are zero, and are dropped along with any other small summed entries.
+  let num_small = m.entries().count(|(r, c, v)| r != c && abs(v) < tol);
+  assert!(num_small > 0);
+  assert_eq!(m_dropped.num_values(), m.num_values() - num_small);
+  assert!(m_dropped.num_values() < m.num_values());
+  match m_dropped.matrix_type() { Symmetric => {}, _ => fail!("Expected a symmetric matrix.") }
+
+  let rhs = DenseMatrix::from_fn(num_els, 1, |i,_| 1. + (i % 3) as R);
+  let sol = la::solve_sparse(&m, &rhs);
+  let sol_dropped = la::solve_sparse(&m_dropped, &rhs);
+  for i in range(0, num_els) {
+    assert!(abs(sol[i] - sol_dropped[i]) < 1e-6);
+  }
+
+  // A tolerance exceeding all contributions leaves only the diagonal.
+  let m_diag = vbf.assemble_with_drop_tol(2. * max_contr);
+  assert_eq!(m_diag.num_values(), num_els);
+  assert!(m_diag.num_values() < m.num_values());
+}
+
+#[test]
+fn test_upper_stiffness_triplets_sym_solve_matches_full_3x2() {
+  la::init();
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let num_els = vbf.basis().num_els();
+
+  let full = vbf.stiffness_triplets(false);
+  let upper = vbf.stiffness_triplets(true);
+  assert!(upper.iter().all(|&(r, c, _)| r <= c));
+  assert_eq!(upper.len(), full.iter().count(|&(r, c, _)| r <= c));
+
+  let rhs = DenseMatrix::from_fn(num_els, 1, |i,_| 1. + (i % 3) as R);
+  let sym_sol = la::solve_sparse(&SparseMatrix::from_triplets(num_els, upper, Symmetric), &rhs);
+  let full_sol = la::solve_sparse(&SparseMatrix::from_triplets(num_els, full, StructurallySymmetric), &rhs);
+  for i in range(0, num_els) {
+    assert!(abs(sym_sol[i] - full_sol[i]) < 1e-8);
+  }
+}
+
+#[test]
+fn test_assembled_system_bandwidth_by_hand_3x1() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,1.], ~[MeshCoord(3),MeshCoord(1)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(1), MaxMonDeg(0)));
+  assert_eq!(vbf.basis().num_els(), 11);
+
+  /* Interior basis elements 0-2, 3-5 and 6-8 are on elements 0, 1 and 2, and the side basis elements 9 and 10 on the
+     sides between elements 0 and 1 and elements 1 and 2. Interior elements interact only with the basis elements of
+     their own finite element, so the farthest entry from the diagonal is for interior element 0 and side element 9. */
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  assert!(m.get(0, 9) != 0.);
+  assert_eq!(m.bandwidth(), 9);
+
+  let mut general = SparseMatrix::new_with_capacities(4, 3, General);
+  general.push(0,0, 1.);
+  general.push(1,1, 1.);
+  general.push(2,0, 2.); general.push(2,2, 1.);
+  assert_eq!(general.bandwidth(), 2);
+}
+
+// End to end assembly of the system matrix on two unit square elements, with interior monomials 1, y, x and one
+// constant side monomial, so the only side supported element is the constant on the shared side. The interior
+// constant on fe 0 (element 0) has zero weak gradient, since (wgrad v, q)_T = -(1, div q)_T = 0 for constant q, and
+// its side projections are 1 on all four sides, giving s(v,v) = 4/h with h = sqrt(2). The shared side constant
+// (element 6) has weak gradient (1,0) on fe 0 and (-1,0) on fe 1, and stabilization 1/h on each element. Their
+// interaction is only the stabilization <Q_b 1, -1>/h on the shared side of fe 0.
+#[test]
+fn test_assembled_system_local_block_by_hand_2x1() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,1.], ~[MeshCoord(2),MeshCoord(1)]);
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(1), MaxMonDeg(0)));
+  assert_eq!(vbf.basis().num_els(), 7);
+
+  let m = vbf.basis_els_vs_basis_els_transpose();
+  match m.matrix_type() { Symmetric => {}, _ => fail!("Expected a symmetric matrix.") }
+  let h_inv = 1./sqrt(2.);
+  assert!(abs(m.get(0, 0) - 4. * h_inv) < 1e-10);
+  assert!(abs(m.get(0, 6) + h_inv) < 1e-10);
+  assert!(abs(m.get(6, 6) - (2. + 2. * h_inv)) < 1e-10);
+}
@@ -1,9 +1,10 @@
 use weak_gradient::*;
 use polynomial;
-use polynomial::{Polynomial, PolyOwning, PolyBorrowingMons, approx_equiv};
+use polynomial::{Polynomial, PolyOwning, PolyBorrowing, PolyBorrowingMons, approx_equiv};
 use monomial::{Mon2d, MaxMonDeg}; 
-use mesh::{OShape};
-use rectangle_mesh::{RectMesh, MeshCoord};
+use mesh::{Mesh, OShape};
+use vector_monomial::VectorMonomial;
+use rectangle_mesh::{RectMesh, MeshCoord, Inward};
 use dense_matrix::DenseMatrix;
 use la;
 
@@ -302,6 +303,159 @@ fn test_wgrad_mdot() {
   assert_eq!(&canon_fast_dot_prod.mons,  &polys_dot_prod.mons);
 }
 
+#[test]
+fn test_inward_normal_convention_negates_side_wgrads() {
+  let outward_rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)]);
+  let inward_rmesh: ~RectMesh<Mon2d> = ~RectMesh::new_with_normal_convention(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)], Inward);
+  let mut outward_wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), outward_rmesh);
+  let mut inward_wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), inward_rmesh);
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+  let int_mons = [one, x, y, x*y];
+  let side_mons_by_side = [&[one, y], &[one, y], &[one, x], &[one, x]];
+
+  let (outward_int_wgrads, outward_side_wgrads) =
+    outward_wgrad_solver.wgrads_on_oshape(int_mons, side_mons_by_side, OShape(0), outward_rmesh);
+  let (inward_int_wgrads, inward_side_wgrads) =
+    inward_wgrad_solver.wgrads_on_oshape(int_mons, side_mons_by_side, OShape(0), inward_rmesh);
+
+  // Only the normal trace term is affected, which vanishes for interior supported functions.
+  for i in range(0, int_mons.len()) {
+    assert_eq!(&inward_int_wgrads[i].comp_mon_coefs, &outward_int_wgrads[i].comp_mon_coefs);
+  }
+  for sf in range(0, 4) {
+    for i in range(0, 2) {
+      let (outward_wgrad, inward_wgrad) = (&outward_side_wgrads[sf][i], &inward_side_wgrads[sf][i]);
+      for d in range(0, 2) {
+        let negated_outward = outward_wgrad.comp_mon_coefs[d].map(|&c| -c);
+        assert_eq!(&inward_wgrad.comp_mon_coefs[d], &negated_outward);
+      }
+    }
+  }
+}
+
+#[test]
+fn test_divergence_of_x2_wgrad_is_laplacian() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)]);
+  let mut wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), rmesh);
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+
+  let (int_mon_wgrads, side_mon_wgrads) =
+    wgrad_solver.wgrads_on_oshape([x*x],           // interior
+                                  [&[one, y, y*y], // left side
+                                   &[one, y, y*y], // right
+                                   &[one, x, x*x], // bottom
+                                   &[one, x, x*x]],// top
+                                  OShape(0),
+                                  rmesh);
+
+  /* The WG function with interior part x^2 on the unit square reference element, and side parts its traces: 0 on the
+   * left side, 1 on the right, and x^2 on the bottom and top sides. Its weak gradient is the projection of the
+   * classical gradient (2x, 0) onto the linear vector polynomials, which is exact, so its divergence should be the
+   * classical Laplacian of x^2. */
+  let wgrad = lcomb_wgrads([(1., &int_mon_wgrads[0]),
+                            (1., &side_mon_wgrads[1][0]),
+                            (1., &side_mon_wgrads[2][2]),
+                            (1., &side_mon_wgrads[3][2])]);
+
+  let div = wgrad.divergence(wgrad_solver.wgrad_comp_mons);
+
+  assert!(approx_equiv(&div, &PolyOwning::new(~[2.], ~[one]), 1e-10));
+}
+
+#[test]
+fn test_linear_wgrad_component_polys_and_eval() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[3f64, 3.], ~[MeshCoord(3), MeshCoord(3)]);
+  let mut wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), rmesh);
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+
+  let (int_mon_wgrads, side_mon_wgrads) =
+    wgrad_solver.wgrads_on_oshape([x, y],     // interior
+                                  [&[one, y], // left side
+                                   &[one, y], // right
+                                   &[one, x], // bottom
+                                   &[one, x]],// top
+                                  OShape(0),
+                                  rmesh);
+
+  // WG functions with interior parts x and y on the unit square reference element, and side parts their traces.
+  let x_wgrad = lcomb_wgrads([(1., &int_mon_wgrads[0]),
+                              (1., &side_mon_wgrads[1][0]),
+                              (1., &side_mon_wgrads[2][1]),
+                              (1., &side_mon_wgrads[3][1])]);
+  let y_wgrad = lcomb_wgrads([(1., &int_mon_wgrads[1]),
+                              (1., &side_mon_wgrads[0][1]),
+                              (1., &side_mon_wgrads[1][1]),
+                              (1., &side_mon_wgrads[3][0])]);
+
+  let comp_mons = wgrad_solver.wgrad_comp_mons();
+  let (one_coef, zero_coef) = ([1.], [0.]);
+  let (one_mons, zero_mons) = ([one], [one]);
+  let const_one = PolyBorrowing::new(one_coef, one_mons);
+  let const_zero = PolyBorrowing::new(zero_coef, zero_mons);
+
+  assert!(approx_equiv(&x_wgrad.component_poly(comp_mons, Dim(0)), &const_one, 1e-10));
+  assert!(approx_equiv(&x_wgrad.component_poly(comp_mons, Dim(1)), &const_zero, 1e-10));
+  assert!(approx_equiv(&y_wgrad.component_poly(comp_mons, Dim(0)), &const_zero, 1e-10));
+  assert!(approx_equiv(&y_wgrad.component_poly(comp_mons, Dim(1)), &const_one, 1e-10));
+
+  let x_wgrad_val = x_wgrad.eval(comp_mons, [0.3, 0.7]);
+  let y_wgrad_val = y_wgrad.eval(comp_mons, [0.9, 0.2]);
+  assert_eq!(x_wgrad_val.len(), 2);
+  assert!(approx_eq(x_wgrad_val[0], 1., 1e-10) && approx_eq(x_wgrad_val[1], 0., 1e-10));
+  assert!(approx_eq(y_wgrad_val[0], 0., 1e-10) && approx_eq(y_wgrad_val[1], 1., 1e-10));
+  assert_eq!(x_wgrad.component_poly(comp_mons, Dim(0)).value_at([0.3, 0.7]), x_wgrad_val[0]);
+}
+
+#[test]
+fn test_weak_div_of_identity_field_gives_boundary_flux() {
+  // Elements are 1 x 0.5 rectangles.
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0f64, 0.], ~[3f64, 1.], ~[MeshCoord(3), MeshCoord(2)]);
+  let wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), rmesh);
+
+  let one = Mon2d { exps: [Deg(0), Deg(0)] };
+  let x = Mon2d { exps: [Deg(1), Deg(0)] };
+  let y = Mon2d { exps: [Deg(0), Deg(1)] };
+
+  // The identity field (x, y) as the sum of the vector monomials (x, 0) and (0, y), followed by (x^2, 0).
+  let wdivs = wgrad_solver.weak_div_on_oshape([VectorMonomial::new(Dim(0), x),
+                                               VectorMonomial::new(Dim(1), y),
+                                               VectorMonomial::new(Dim(0), x*x)],
+                                              OShape(0), rmesh);
+  assert_eq!(wdivs.len(), 3);
+  assert!(approx_equiv(&wdivs[0], &PolyOwning::new(~[1.], ~[one]), 1e-10));
+  assert!(approx_equiv(&wdivs[1], &PolyOwning::new(~[1.], ~[one]), 1e-10));
+  // Divergence 2x is in the test polynomial space, so is reproduced exactly.
+  assert!(approx_equiv(&wdivs[2], &PolyOwning::new(~[2.], ~[x]), 1e-10));
+
+  // The integral of the weak divergence of the identity field is its boundary flux, space dims x element measure.
+  let intg_wdiv = |p: &PolyOwning<Mon2d>| {
+    p.foldl_terms(0 as R, |sum, (coef, mon)| sum + coef * rmesh.intg_facerel_mon_on_oshape_int(mon, OShape(0)))
+  };
+  let flux = intg_wdiv(&wdivs[0]) + intg_wdiv(&wdivs[1]);
+  assert!(approx_eq(flux, 2. * rmesh.oshape_interior_measure(OShape(0)), 1e-10));
+  assert!(approx_eq(flux, 1., 1e-10));
+
+  // Weak divergences use outward normals under either normal convention.
+  let inward_rmesh: ~RectMesh<Mon2d> = ~RectMesh::new_with_normal_convention(~[0f64, 0.], ~[3f64, 1.], ~[MeshCoord(3), MeshCoord(2)], Inward);
+  let inward_wgrad_solver: WeakGradSolver<Mon2d> = WeakGradSolver::new(MaxMonDeg(1), inward_rmesh);
+  let inward_wdivs = inward_wgrad_solver.weak_div_on_oshape([VectorMonomial::new(Dim(0), x),
+                                                             VectorMonomial::new(Dim(1), y),
+                                                             VectorMonomial::new(Dim(0), x*x)],
+                                                            OShape(0), inward_rmesh);
+  for i in range(0, 3) {
+    assert!(approx_equiv(&inward_wdivs[i], &wdivs[i], 1e-12));
+  }
+}
+
 
 fn lcomb_wgrads(terms: &[(R,&WeakGrad)]) -> WeakGrad {
   if terms.len() == 0 { fail!("lcomb_wgrads: At least one weak gradient is required.") }
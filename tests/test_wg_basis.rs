@@ -1,8 +1,19 @@
-use wg_basis::{WGBasis, BasisElNum, FaceMonNum};
-use common::{Deg, Dim};
-use mesh::{FENum, OShape, SideFace, NBSideNum, NBSideInclusions};
-use rectangle_mesh::{RectMesh, MeshCoord};
-use monomial::{Mon2d, MaxMonDeg};
+use wg_basis::{WGBasis, BasisElNum, FaceMonNum, wg_dof_count, Consistent, Lumped};
+use wg_basis::{excess_side_deg_lim_report, wgrad_comp_mons_deg_lim, FailOnExcessSideDeg};
+use common::{R, Deg, Dim};
+use mesh::{Mesh, FENum, OShape, SideFace, NBSideNum, NBSideInclusions};
+use rectangle_mesh::{RectMesh, MeshCoord, lesser_side_face_perp_to_axis, greater_side_face_perp_to_axis};
+use rectangle_mesh_wg_basis::RectMeshWGBasis;
+use monomial::{Mon2d, Mon3d, MaxMonDeg, MaxMonFactorDeg};
+use polynomial::{Polynomial, poly};
+use sparse_matrix::{SparseMatrix, Symmetric};
+use vbf_laplace::VBFLaplace;
+use variational_bilinear_form::VariationalBilinearForm;
+use projection::Projector;
+use la;
+
+use std::vec;
+use std::num::{abs, sqrt};
 
 
 /*
@@ -719,3 +730,760 @@ fn test_side_L2_inner_products_3x2_deg2() {
   assert_eq!(top_ips.get(1,1), 1./3.); // x vs x
 }
 
+
+#[test]
+fn test_integrate_const_solution_boundary_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // Set the constant monomial coefficient to 1.5 on each interior, the first monomial of the interior sequence.
+  let sol_coefs = vec::from_fn(basis.num_els(), |i| {
+    let beln = BasisElNum(i); 
+    if basis.is_int_supported(beln) && basis.int_rel_mon_num(beln) == FaceMonNum(0) { 1.5 } else { 0 as R }
+  });
+
+  assert_approx(basis.integrate_solution_boundary(sol_coefs), 1.5 * (3. + 2. + 3. + 2.));
+}
+
+#[test]
+fn test_constant_solution_coefs_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let sol_coefs = basis.constant_solution_coefs(2.5);
+
+  for fe in range(0, basis.mesh().num_fes()) { let fe = FENum(fe);
+    let int_poly = basis.fe_int_poly(fe, sol_coefs);
+    assert_approx(int_poly.value_at([0.,0.]), 2.5);
+    assert_approx(int_poly.value_at([0.3,0.7]), 2.5);
+    assert_approx(int_poly.value_at([1.,1.]), 2.5);
+    for sf in range(0, 4) { let sf = SideFace(sf);
+      if !basis.mesh().is_boundary_side(fe, sf) {
+        let side_poly = basis.fe_side_poly(fe, sf, sol_coefs);
+        assert_approx(side_poly.value_at([0.,0.]), 2.5);
+        assert_approx(side_poly.value_at([0.5,0.5]), 2.5);
+      }
+    }
+  }
+}
+
+#[test]
+fn test_approx_memory_bytes_grows_with_degree() {
+  let rmesh1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis_deg2 = WGBasis::new(rmesh1, MaxMonDeg(2), MaxMonDeg(1));
+  let rmesh2: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis_deg3 = WGBasis::new(rmesh2, MaxMonDeg(3), MaxMonDeg(2));
+
+  assert!(basis_deg2.approx_memory_bytes() < basis_deg3.approx_memory_bytes());
+}
+
+#[test]
+fn test_approx_memory_bytes_same_for_refined_rect_mesh() {
+  // The precomputed data is by oriented shape, and rectangular meshes have only one oriented shape.
+  let rmesh1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis_3x2 = WGBasis::new(rmesh1, MaxMonDeg(2), MaxMonDeg(1));
+  let rmesh2: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(30),MeshCoord(20)]);
+  let basis_30x20 = WGBasis::new(rmesh2, MaxMonDeg(2), MaxMonDeg(1));
+
+  assert_eq!(basis_3x2.approx_memory_bytes(), basis_30x20.approx_memory_bytes());
+}
+
+#[test]
+fn test_boundary_side_mon_el_num_opt_1x1_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  for sf in range(0u, 4) {
+    assert_eq!(basis.fe_side_mon_el_num_opt(FENum(0), SideFace(sf), FaceMonNum(0)), None);
+  }
+}
+
+#[test]
+fn test_nb_side_mon_el_num_opt_2x1_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,1.], ~[MeshCoord(2),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let right_face = greater_side_face_perp_to_axis(Dim(0));
+  assert_eq!(basis.fe_side_mon_el_num_opt(FENum(0), right_face, FaceMonNum(1)),
+             Some(basis.fe_side_mon_el_num(FENum(0), right_face, FaceMonNum(1))));
+}
+
+#[test]
+fn test_lumped_int_mass_preserves_total_mass_2x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(2),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let lumped = basis.assemble_lumped_int_mass();
+  assert_eq!(lumped.len(), 4 * basis.mons_per_fe_int());
+
+  let consistent_total = range(0, basis.mesh().num_fes()).fold(0 as R, |sum, fe| {
+    let int_mons = basis.ref_int_mons();
+    let fe_oshape = basis.mesh().oriented_shape_for_fe(FENum(fe));
+    int_mons.iter().fold(sum, |sum, m1| {
+      int_mons.iter().fold(sum, |sum, m2| sum + basis.mesh().intg_facerel_mon_on_oshape_int(*m1 * *m2, fe_oshape))
+    })
+  });
+  let lumped_total = lumped.iter().fold(0 as R, |sum, &d| sum + d);
+
+  assert_approx(lumped_total, consistent_total);
+}
+
+#[test]
+fn test_int_mass_forms_total_mass_2x2_deg1() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(2),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(1), MaxMonDeg(1));
+  let consistent = basis.assemble_int_mass_with(Consistent);
+  let lumped = basis.assemble_int_mass_with(Lumped);
+  assert_eq!(consistent.num_rows(), basis.num_int_els());
+  assert_eq!(lumped.num_rows(), basis.num_int_els());
+
+  // Total of all entries, counting the unstored lower triangle mirrors of off-diagonal entries.
+  let total = |m: &SparseMatrix| m.entries().fold(0 as R, |sum, (r, c, v)| sum + if r == c { v } else { 2. * v });
+  assert_approx(total(&lumped), total(&consistent));
+
+  let has_off_diag = |m: &SparseMatrix| m.entries().any(|(r, c, v)| r != c && v != 0.);
+  assert!(!has_off_diag(&lumped));
+  // The interior monomials 1, y, x on each fe are not orthogonal, so the consistent form is not diagonal. Only for
+  // a constant interior basis do the two forms agree, with the single entry of each fe block being its measure.
+  assert!(has_off_diag(&consistent));
+  assert_approx(consistent.get(0,0), lumped.get(0,0) - consistent.get(0,1) - consistent.get(0,2));
+
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(2),MeshCoord(2)]);
+  let const_basis = &WGBasis::new(rmesh, MaxMonDeg(0), MaxMonDeg(0));
+  let const_consistent = const_basis.assemble_int_mass_with(Consistent);
+  let const_lumped = const_basis.assemble_int_mass_with(Lumped);
+  for r in range(0, const_basis.num_int_els()) {
+    for c in range(0, const_basis.num_int_els()) {
+      assert_approx(const_consistent.get(r,c), const_lumped.get(r,c));
+    }
+  }
+  assert_approx(const_consistent.get(0,0), 1.5 * 1.);
+}
+
+#[test]
+fn test_no_side_basis_gaps_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  assert!(basis.report_side_basis_gaps().is_none());
+}
+
+#[test]
+fn test_side_basis_gaps_for_low_side_deg_3x2_deg3() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  // The weak gradient components have degree 2, so side monomials up to degree 2 are needed.
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(3), MaxMonDeg(1));
+  assert!(basis.report_side_basis_gaps().is_some());
+}
+
+#[test]
+fn test_sample_gradient_of_linear_solution_is_constant_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  // Interior monomials are [1, y, y^2, x, xy, x^2]: set each interior polynomial to 2x + 3y.
+  let mut sol_coefs = vec::from_elem(basis.num_els(), 0 as R);
+  for fe in range(0, basis.mesh().num_fes()) { let fe = FENum(fe);
+    sol_coefs[*basis.int_mon_el_num(fe, FaceMonNum(1))] = 3.;
+    sol_coefs[*basis.int_mon_el_num(fe, FaceMonNum(3))] = 2.;
+  }
+
+  let grads = basis.sample_gradient_on_grid(sol_coefs, 3);
+  assert_eq!(grads.len(), 6 * 9);
+  for grad in grads.iter() {
+    assert_eq!(grad.len(), 2);
+    assert_approx(grad[0], 2.);
+    assert_approx(grad[1], 3.);
+  }
+}
+
+#[test]
+fn test_linf_error_at_quad_points_for_exact_rep_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let exact = |x: &[R]| 1. + 2.*x[0] + 3.*x[1];
+  // Interior monomials are [1, y, y^2, x, xy, x^2], relative to the interior origin of each element.
+  let mut sol_coefs = vec::from_elem(basis.num_els(), 0 as R);
+  for fe in range(0, basis.mesh().num_fes()) { let fe = FENum(fe);
+    let (o_x, o_y) = (basis.mesh().fe_interior_origin_comp(fe, Dim(0)), basis.mesh().fe_interior_origin_comp(fe, Dim(1)));
+    sol_coefs[*basis.int_mon_el_num(fe, FaceMonNum(0))] = 1. + 2.*o_x + 3.*o_y;
+    sol_coefs[*basis.int_mon_el_num(fe, FaceMonNum(1))] = 3.;
+    sol_coefs[*basis.int_mon_el_num(fe, FaceMonNum(3))] = 2.;
+  }
+
+  assert!(basis.linf_error_at_quad_points(sol_coefs, |x| exact(x), 4) < 1e-12);
+  // Shifting the exact function by a constant gives that constant as the error.
+  assert_approx(basis.linf_error_at_quad_points(sol_coefs, |x| exact(x) + 0.25, 4), 0.25);
+}
+
+#[test]
+fn test_eval_value_and_gradient_matches_separate_evals_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  // Interior monomials are [1, y, y^2, x, xy, x^2]: set interior polynomials 1 + (fe+1) x^2 + y on each fe.
+  let mut sol_coefs = vec::from_elem(basis.num_els(), 0 as R);
+  for fe in range(0, basis.mesh().num_fes()) {
+    sol_coefs[*basis.int_mon_el_num(FENum(fe), FaceMonNum(0))] = 1.;
+    sol_coefs[*basis.int_mon_el_num(FENum(fe), FaceMonNum(1))] = 1.;
+    sol_coefs[*basis.int_mon_el_num(FENum(fe), FaceMonNum(5))] = (fe + 1) as R;
+  }
+
+  let pts = ~[~[0.,0.], ~[0.5,0.25], ~[1.5,1.], ~[2.75,1.9], ~[3.,2.]];
+  for x in pts.iter() {
+    let (v, grad) = basis.eval_value_and_gradient_at(sol_coefs, *x).unwrap();
+    assert_eq!(v, basis.eval_solution_at(sol_coefs, *x).unwrap());
+    assert_eq!(grad, basis.eval_gradient_at(sol_coefs, *x).unwrap());
+  }
+
+  // The point (1.5,1) is on the top side of fe 1 with interior origin (1,0), where the interior polynomial is
+  // 1 + 2 x^2 + y in interior relative coordinates.
+  let (v, grad) = basis.eval_value_and_gradient_at(sol_coefs, [1.5,1.]).unwrap();
+  assert_approx(v, 2.5);
+  assert_approx(grad[0], 2.);
+  assert_approx(grad[1], 1.);
+  // At (0.5,0.25) in fe 0, with interior polynomial 1 + x^2 + y.
+  let (v, grad) = basis.eval_value_and_gradient_at(sol_coefs, [0.5,0.25]).unwrap();
+  assert_approx(v, 1.5);
+  assert_approx(grad[0], 1.);
+  assert_approx(grad[1], 1.);
+
+  assert!(basis.eval_value_and_gradient_at(sol_coefs, [3.5,1.]).is_none());
+  assert!(basis.eval_solution_at(sol_coefs, [1.,-0.1]).is_none());
+}
+
+#[test]
+fn test_convection_with_constant_velocity_on_single_fe() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let m = basis.assemble_convection(|_| ~[1., 0.]);
+
+  /* Interior monomials are [1, y, y^2, x, xy, x^2], and weak gradient components are linear. With no non-boundary
+   * sides, for constant b and linear test monomial v the definition of the weak gradient gives
+   *   (b·wgrad(u), v)_T = (wgrad(u), b v)_T = -(u, div(b v))_T = -(u, b·grad(v))_T.
+   * So rows for the test monomials 1 and y are zero, and the row for x holds -(u, 1)_T for the trial monomials u. */
+  assert_eq!(m.num_rows(), 6);
+  let x_row = *basis.int_mon_el_num(FENum(0), FaceMonNum(3));
+  let neg_int_trial_mons = [-1., -1./2., -1./3., -1./2., -1./4., -1./3.];
+  for c in range(0, 6) {
+    assert!(abs(m.get(0, c)) < 1e-8);
+    assert!(abs(m.get(1, c)) < 1e-8);
+    assert!(abs(m.get(x_row, c) - neg_int_trial_mons[c]) < 1e-8);
+  }
+}
+
+#[test]
+fn test_biharmonic_diagonal_entry_on_single_fe() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let m = basis.assemble_biharmonic();
+
+  /* With no non-boundary sides, the weak gradient w of the interior constant 1 satisfies (w, q)_T = -(1, div q)_T for
+   * linear vector q. Its first component (a0 + a1 y + a2 x) is determined by the Gram system of [1, y, x] on the unit
+   * square with right hand side (0, 0, -1), giving (a0, a1, a2) = (6, 0, -12), and the second is 6 - 12y by symmetry.
+   * The discrete Laplacian is then the constant -24, whose square integrates to 576 over the unit square. */
+  assert_eq!(m.num_rows(), 6);
+  match m.matrix_type() { Symmetric => {}, _ => fail!("Biharmonic matrix should be symmetric.") }
+  let one_el = *basis.int_mon_el_num(FENum(0), FaceMonNum(0));
+  assert!(abs(m.get(one_el, one_el) - 576.) < 1e-6);
+}
+
+#[test]
+fn test_sample_solution_on_line_recovers_linear_profile_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // u(x,y) = x on each interior, which is the interior origin's x coordinate plus the interior monomial x (number 3).
+  let mut sol = vec::from_elem(basis.num_els(), 0 as R);
+  for fe in range(0, basis.mesh().num_fes()) { let fe = FENum(fe);
+    sol[*basis.int_mon_el_num(fe, FaceMonNum(0))] = basis.mesh().fe_interior_origin_comp(fe, Dim(0));
+    sol[*basis.int_mon_el_num(fe, FaceMonNum(3))] = 1.;
+  }
+
+  let samples = basis.sample_solution_on_line(sol, [0., 0.5], [3., 0.5], 7);
+
+  assert_eq!(samples.len(), 7);
+  for (i, &(s, v)) in samples.iter().enumerate() {
+    assert!(abs(s - (i as R)/2.) < 1e-12);
+    assert!(abs(v - (i as R)/2.) < 1e-12);
+  }
+}
+
+#[test]
+fn test_integrate_solution_on_slice_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // A constant solution integrates to the constant times the slice length.
+  let mut sol = vec::from_elem(basis.num_els(), 0 as R);
+  for fe in range(0, basis.mesh().num_fes()) {
+    sol[*basis.int_mon_el_num(FENum(fe), FaceMonNum(0))] = 2.5;
+  }
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(0), 1.5), 5.);
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(0), 1.), 5.);
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(0), 3.), 5.);
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(1), 0.3), 7.5);
+
+  // Adding the interior monomial x (number 3) on each element adds the plane's interior relative x coordinate.
+  for fe in range(0, basis.mesh().num_fes()) {
+    sol[*basis.int_mon_el_num(FENum(fe), FaceMonNum(3))] = 1.;
+  }
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(0), 1.25), 2. * 2.75);
+  // On the shared sides at x = 1, the elements of lesser x coordinate are used.
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(0), 1.), 2. * 3.5);
+  // Along y, the slice crosses all three columns of elements, with x integrating to 1/2 on each.
+  assert_approx(basis.integrate_solution_on_slice(sol, Dim(1), 1.5), 7.5 + 1.5);
+}
+
+#[test]
+fn test_prolongation_maps_coarse_constant_to_fine_constant_2x2_to_4x4() {
+  la::init();
+  let coarse_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(2),MeshCoord(2)]);
+  let fine_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(4),MeshCoord(4)]);
+  let coarse = &WGBasis::new(coarse_mesh, MaxMonDeg(2), MaxMonDeg(1));
+  let vbf = VBFLaplace::new(None, ~WGBasis::new(fine_mesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let fine = vbf.basis();
+  let p = fine.build_prolongation(coarse);
+  assert_eq!(p.num_rows(), fine.num_els());
+
+  // The constant 1 has coefficient 1 for the constant monomial (number 0) on each interior and side.
+  let const_coefs = |basis: &WGBasis<Mon2d,RectMesh<Mon2d>>| -> ~[R] {
+    let mut coefs = vec::from_elem(basis.num_els(), 0 as R);
+    for fe in range(0, basis.mesh().num_fes()) {
+      coefs[*basis.int_mon_el_num(FENum(fe), FaceMonNum(0))] = 1.;
+    }
+    for nbs in range(0, basis.mesh().num_nb_sides()) {
+      coefs[*basis.nb_side_mon_el_num(NBSideNum(nbs), FaceMonNum(0))] = 1.;
+    }
+    coefs
+  };
+  let (coarse_one, fine_one) = (const_coefs(coarse), const_coefs(fine));
+
+  let mut prolonged = vec::from_elem(fine.num_els(), 0 as R);
+  for (r, c, v) in p.entries() { prolonged[r] += v * coarse_one[c]; }
+  for i in range(0, fine.num_els()) {
+    assert!(abs(prolonged[i] - fine_one[i]) < 1e-10);
+  }
+
+  // The Galerkin coarse operator's form on coarse vectors equals the fine form on their prolongations.
+  let a = vbf.basis_els_vs_basis_els_transpose();
+  let coarse_a = a.galerkin_product(&p, coarse.num_els());
+  assert_eq!(coarse_a.num_rows(), coarse.num_els());
+  match coarse_a.matrix_type() { Symmetric => {}, _ => fail!("Expected a symmetric coarse operator.") }
+  let x = vec::from_fn(coarse.num_els(), |i| 1. + (i % 3) as R);
+  let mut p_x = vec::from_elem(fine.num_els(), 0 as R);
+  for (r, c, v) in p.entries() { p_x[r] += v * x[c]; }
+  let dot = |u: &[R], v: &[R]| range(0, u.len()).fold(0 as R, |sum, i| sum + u[i] * v[i]);
+  let (coarse_form, fine_form) = (dot(x.as_slice(), coarse_a.mat_vec(x.as_slice()).as_slice()),
+                                  dot(p_x.as_slice(), a.mat_vec(p_x.as_slice()).as_slice()));
+  assert!(abs(coarse_form - fine_form) < 1e-8 * abs(fine_form));
+}
+
+#[test]
+fn test_interpolate_exactly_representable_poly_2x2_to_4x4() {
+  la::init();
+  let coarse_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(2),MeshCoord(2)]);
+  let fine_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(4),MeshCoord(4)]);
+  let coarse = &WGBasis::new(coarse_mesh, MaxMonDeg(2), MaxMonDeg(1));
+  let fine = &WGBasis::new(fine_mesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // u has degree 2 with linear traces on all sides, so is in the WG spaces of both meshes.
+  let u = |x: &[R]| 1. + 2.*x[0] - x[1] + 0.5*x[0]*x[1];
+  let proj_coefs = |basis: &WGBasis<Mon2d,RectMesh<Mon2d>>| -> ~[R] {
+    let mut coefs = vec::from_elem(basis.num_els(), 0 as R);
+    let mut projector = Projector::new(basis);
+    for fe in range(0, basis.mesh().num_fes()) { let fe = FENum(fe);
+      let projs = projector.projs_to_int_supp_approx_spaces(|x| u(x), [fe], OShape(0));
+      for monn in range(0, basis.mons_per_fe_int()) {
+        let (coef, _) = projs[0].term(monn);
+        coefs[*basis.int_mon_el_num(fe, FaceMonNum(monn))] = coef;
+      }
+    }
+    for nbs in range(0, basis.mesh().num_nb_sides()) { let nbs = NBSideNum(nbs);
+      let incls = basis.fe_inclusions_of_side_support(basis.nb_side_mon_el_num(nbs, FaceMonNum(0)));
+      let projs = projector.projs_to_side_supp_approx_spaces(|x| u(x), [incls.fe1], OShape(0), incls.side_face_in_fe1);
+      for monn in range(0, basis.mons_per_fe_side()) {
+        let (coef, _) = projs[0].term(monn);
+        coefs[*basis.nb_side_mon_el_num(nbs, FaceMonNum(monn))] = coef;
+      }
+    }
+    coefs
+  };
+
+  let fine_sol = coarse.interpolate_to(fine, proj_coefs(coarse).as_slice());
+  let fine_u = proj_coefs(fine);
+  assert_eq!(fine_sol.len(), fine.num_els());
+  for i in range(0, fine.num_els()) {
+    assert!(abs(fine_sol[i] - fine_u[i]) < 1e-10);
+  }
+  assert_approx(fine.eval_solution_at(fine_sol.as_slice(), [0.3, 0.6]).unwrap(), u([0.3, 0.6]));
+}
+
+#[test]
+fn test_boundary_mass_symmetry_and_support_3x3_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,3.], ~[MeshCoord(3),MeshCoord(3)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let m = basis.assemble_boundary_mass(|x| 1. + x[0]);
+
+  match m.matrix_type() { Symmetric => {}, _ => fail!("Boundary mass matrix should be symmetric.") }
+  assert_eq!(m.num_rows(), basis.num_els());
+
+  // Only the interior supported elements of elements with boundary sides (all but the center fe 4) are supported.
+  for (r, c, v) in m.entries() {
+    assert!(r <= c);
+    if v != 0. {
+      let (el_r, el_c) = (BasisElNum(r), BasisElNum(c));
+      assert!(basis.is_int_supported(el_r) && basis.is_int_supported(el_c));
+      let fe = basis.support_int_fe_num(el_r);
+      assert!(fe == basis.support_int_fe_num(el_c));
+      assert!(fe != FENum(4));
+    }
+  }
+
+  // For the constant monomial on fe 0, the weight integrates to 1 over the left side and 3/2 over the bottom side,
+  // and on fe 1 to 5/2 over its bottom side.
+  let one_0 = *basis.int_mon_el_num(FENum(0), FaceMonNum(0));
+  let one_1 = *basis.int_mon_el_num(FENum(1), FaceMonNum(0));
+  assert!(abs(m.get(one_0, one_0) - (1. + 3./2.)) < 1e-8);
+  assert!(abs(m.get(one_1, one_1) - 5./2.) < 1e-8);
+}
+
+#[test]
+fn test_restrict_to_2x2_element_block_of_4x4() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[4.,4.], ~[MeshCoord(4),MeshCoord(4)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let sol = vec::from_fn(basis.num_els(), |i| i as R);
+
+  let restr = basis.restrict_to_element_block(sol, [MeshCoord(1),MeshCoord(1)], [MeshCoord(2),MeshCoord(2)]);
+
+  // 4 interiors with 6 monomials each, and 12 distinct sides, all non-boundary, with 2 monomials each.
+  assert_eq!(restr.len(), 4*6 + 12*2);
+  assert_eq!(restr.iter().count(|&(el, _)| basis.is_int_supported(el)), 4*6);
+  for i in range(1, restr.len()) {
+    match (restr[i-1], restr[i]) { ((el_1, _), (el_2, _)) => assert!(*el_1 < *el_2) }
+  }
+  for &(el, coef) in restr.iter() {
+    assert_eq!(coef, *el as R);
+  }
+}
+
+#[test]
+fn test_degree_consistency_for_standard_choices() {
+  let rmesh1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  assert!(WGBasis::new(rmesh1, MaxMonDeg(2), MaxMonDeg(1)).check_degree_consistency().is_ok());
+  let rmesh2: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  assert!(WGBasis::new(rmesh2, MaxMonDeg(2), MaxMonDeg(2)).check_degree_consistency().is_ok());
+}
+
+#[test]
+fn test_degree_consistency_for_pathological_choices() {
+  let rmesh1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  match WGBasis::new(rmesh1, MaxMonDeg(3), MaxMonDeg(0)).check_degree_consistency() {
+    Err(msg) => assert!(msg.contains("should be 3 or 2")),
+    Ok(_) => fail!("Expected side degree limit 0 to be rejected for interior degree limit 3.")
+  }
+  let rmesh2: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  assert!(WGBasis::new(rmesh2, MaxMonDeg(2), MaxMonFactorDeg(1)).check_degree_consistency().is_err());
+}
+
+#[test]
+fn test_wgrad_linearity_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  for seed in range(0u32, 4) {
+    assert!(basis.check_wgrad_linearity(OShape(0), seed, 1e-10));
+  }
+}
+
+#[test]
+fn test_wgrad_of_linear_int_poly_with_side_traces_is_classical_gradient_1x1_deg1() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(1), MaxMonDeg(1));
+  let (one, x, y) = (Mon2d { exps: [Deg(0), Deg(0)] }, Mon2d { exps: [Deg(1), Deg(0)] }, Mon2d { exps: [Deg(0), Deg(1)] });
+  let p = poly([(1., one), (2., x), (3., y)]);
+  let int_wgrad = basis.wgrad_of_int_poly(OShape(0), &p);
+
+  // The traces of p = 1 + 2x + 3y on the sides, as coefficients of the side-relative monomials 1 and x or y.
+  let traces = ~[(lesser_side_face_perp_to_axis(Dim(0)), 1., 3.), (greater_side_face_perp_to_axis(Dim(0)), 3., 3.),
+                 (lesser_side_face_perp_to_axis(Dim(1)), 1., 2.), (greater_side_face_perp_to_axis(Dim(1)), 4., 2.)];
+  let comp_mons = basis.wgrad_comp_mons();
+  for pt in [[0.,0.], [0.5,0.25], [1.,0.75]].iter() {
+    let grad = vec::from_fn(2, |d| {
+      traces.iter().fold(int_wgrad.comp_value_at(comp_mons, Dim(d), *pt), |sum, &(sf, const_coef, lin_coef)| {
+        let side_mons = basis.side_mons_for_oshape_side(OShape(0), sf);
+        range(0, side_mons.len()).fold(sum, |sum, monn| {
+          let coef = if side_mons[monn] == one { const_coef } else { lin_coef };
+          sum + coef * basis.side_mon_wgrad(FaceMonNum(monn), OShape(0), sf).comp_value_at(comp_mons, Dim(d), *pt)
+        })
+      })
+    });
+    assert_approx(grad[0], 2.);
+    assert_approx(grad[1], 3.);
+  }
+}
+
+#[test]
+fn test_wgrad_of_int_poly_projects_onto_int_mons_1x1_deg1() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(1),MeshCoord(1)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(1), MaxMonDeg(1));
+  let (one, x) = (Mon2d { exps: [Deg(0), Deg(0)] }, Mon2d { exps: [Deg(1), Deg(0)] });
+  let int_mons = basis.ref_int_mons();
+  let (one_monn, x_monn) = (int_mons.position_elem(&one).unwrap(), int_mons.position_elem(&x).unwrap());
+
+  // The L2 projection of x^2 onto the span of 1, x and y on the unit square is x - 1/6.
+  let wgrad = basis.wgrad_of_int_poly(OShape(0), &poly([(1., Mon2d { exps: [Deg(2), Deg(0)] })]));
+  let comp_mons = basis.wgrad_comp_mons();
+  for pt in [[0.,0.], [0.5,0.25], [1.,0.75]].iter() {
+    for d in range(0, 2) {
+      let expected = basis.int_mon_wgrad(FaceMonNum(x_monn), OShape(0)).comp_value_at(comp_mons, Dim(d), *pt)
+                     - basis.int_mon_wgrad(FaceMonNum(one_monn), OShape(0)).comp_value_at(comp_mons, Dim(d), *pt) / 6.;
+      assert_approx(wgrad.comp_value_at(comp_mons, Dim(d), *pt), expected);
+    }
+  }
+}
+
+fn assert_approx(a:R, b:R) -> () {
+  assert!(abs(a - b) < 10e-9)
+}
+
+#[test]
+fn test_wg_dof_count_matches_basis() {
+  let deg_lims = ~[(MaxMonDeg(2), MaxMonDeg(1)), (MaxMonDeg(3), MaxMonDeg(3)), (MaxMonFactorDeg(2), MaxMonFactorDeg(1))];
+  for &(int_lim, side_lim) in deg_lims.iter() {
+    let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+    let count = wg_dof_count::<Mon2d,RectMesh<Mon2d>>(&*rmesh, int_lim, side_lim);
+    let basis = WGBasis::new(rmesh, int_lim, side_lim);
+    assert_eq!(count, basis.num_els());
+  }
+}
+
+#[test]
+fn test_element_functional_contributions_sum_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // For u_h = 2 everywhere the functional with weight x is 2 ∫_[0,3]x[0,2] x = 18, with fe contributions 2(o_x + 1/2).
+  let const_sol = basis.constant_solution_coefs(2.);
+  let contrs = basis.element_functional_contributions(const_sol, |x| x[0]);
+  assert_eq!(contrs.len(), 6);
+  for fe in range(0, 6) {
+    assert_approx(contrs[fe], 2. * (basis.mesh().fe_interior_origin_comp(FENum(fe), Dim(0)) + 0.5));
+  }
+  assert_approx(contrs.iter().fold(0 as R, |sum, &c| sum + c), 18.);
+
+  // With unit weight the functional is the sum of the interior coefficients times the interior monomial integrals.
+  let sol: ~[R] = vec::from_fn(basis.num_els(), |i| (i % 5) as R - 1.);
+  let direct = range(0, basis.mesh().num_fes()).fold(0 as R, |sum, fe| { let fe = FENum(fe);
+    let oshape = basis.mesh().oriented_shape_for_fe(fe);
+    range(0, basis.mons_per_fe_int()).fold(sum, |sum, monn| {
+      sum + sol[*basis.int_mon_el_num(fe, FaceMonNum(monn))] *
+            basis.mesh().intg_facerel_mon_on_oshape_int(basis.ref_int_mons()[monn].clone(), oshape)
+    })
+  });
+  let contrs = basis.element_functional_contributions(sol, |_| 1.);
+  assert_approx(contrs.iter().fold(0 as R, |sum, &c| sum + c), direct);
+}
+
+#[test]
+fn test_solution_difference_l2_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let sol: ~[R] = vec::from_fn(basis.num_els(), |i| (i % 7) as R - 2.);
+  assert_eq!(basis.solution_difference_l2(sol, sol), 0.);
+
+  // A constant offset of 0.5 on all interiors gives the norm 0.5 |Ω|^(1/2) for the mesh of area 6.
+  let offset = basis.constant_solution_coefs(0.5);
+  let shifted: ~[R] = vec::from_fn(basis.num_els(), |i| sol[i] + offset[i]);
+  assert_approx(basis.solution_difference_l2(shifted, sol), 0.5 * sqrt(6.));
+  assert_approx(basis.solution_difference_l2(sol, shifted), 0.5 * sqrt(6.));
+}
+
+#[test]
+fn test_excess_side_deg_lim_report() {
+  assert!(excess_side_deg_lim_report::<Mon2d>(MaxMonDeg(2), MaxMonDeg(1)).is_none());
+  assert!(excess_side_deg_lim_report::<Mon2d>(MaxMonDeg(2), MaxMonDeg(2)).is_none());
+  assert!(excess_side_deg_lim_report::<Mon2d>(MaxMonFactorDeg(2), MaxMonDeg(2)).is_none());
+  // The interior traces 1, y, z and yz of factor degree 1 on sides perpendicular to the x axis include the side
+  // monomial yz of total degree 2 but not y^2 or z^2.
+  match excess_side_deg_lim_report::<Mon3d>(MaxMonFactorDeg(1), MaxMonDeg(2)) {
+    Some(msg) => assert!(msg.contains("admits 2 of the 6 side monomials for dependent dimension 0")),
+    None => fail!("Expected side degree limit 2 to be reported as excessive for interior factor degree limit 1.")
+  }
+  match excess_side_deg_lim_report::<Mon2d>(MaxMonDeg(1), MaxMonDeg(2)) {
+    // Of the side monomials 1, y and y^2 for sides perpendicular to the x axis, y^2 is not an interior trace.
+    Some(msg) => assert!(msg.contains("admits 1 of the 3 side monomials for dependent dimension 0")),
+    None => fail!("Expected side degree limit 2 to be reported as excessive for interior degree limit 1.")
+  }
+}
+
+#[test]
+#[should_fail]
+fn test_excess_side_deg_fails_when_requested() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  WGBasis::new_with_excess_side_deg_handling(rmesh, MaxMonDeg(1), MaxMonDeg(2), FailOnExcessSideDeg);
+}
+
+#[test]
+fn test_int_side_trace_products_match_fresh_integrals_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let oshape = OShape(0);
+  for sf in range(0, basis.mesh().num_side_faces_for_oshape(oshape)) { let sf = SideFace(sf);
+    let side_mons = basis.side_mons_for_oshape_side(oshape, sf);
+    let prods = basis.int_side_trace_products(oshape, sf);
+    assert_eq!(prods.num_rows(), basis.mons_per_fe_int());
+    assert_eq!(prods.num_cols(), side_mons.len());
+    for i in range(0, basis.mons_per_fe_int()) {
+      for j in range(0, side_mons.len()) {
+        let fresh = basis.mesh().intg_intrel_mon_x_siderel_mon_on_oshape_side(basis.ref_int_mons()[i].clone(),
+                                                                             side_mons[j].clone(), oshape, sf);
+        assert_eq!(prods.get(i,j), fresh);
+      }
+    }
+  }
+}
+
+#[test]
+fn test_element_peclet_numbers_scale_with_element_size() {
+  let coarse_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(2),MeshCoord(2)]);
+  let fine_mesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(4),MeshCoord(4)]);
+  let coarse = &WGBasis::new(coarse_mesh, MaxMonDeg(1), MaxMonDeg(1));
+  let fine = &WGBasis::new(fine_mesh, MaxMonDeg(1), MaxMonDeg(1));
+
+  // With b = (3,4), |b| = 5, and the coarse elements have diameter sqrt(2)/2.
+  let coarse_pes = coarse.element_peclet_numbers(|_| ~[3., 4.], 0.5);
+  let fine_pes = fine.element_peclet_numbers(|_| ~[3., 4.], 0.5);
+  assert_eq!(coarse_pes.len(), 4);
+  assert_eq!(fine_pes.len(), 16);
+  for &pe in coarse_pes.iter() { assert_approx(pe, 5. * sqrt(2.) / 2.); }
+  for &pe in fine_pes.iter() { assert_approx(pe, coarse_pes[0] / 2.); }
+}
+
+#[test]
+fn test_face_mon_inner_products_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,1.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+
+  // Elements have dimensions 1 x 0.5.
+  let int_ips = basis.int_mon_inner_products(OShape(0));
+  assert_eq!(int_ips.num_rows(), 6);
+  assert_eq!(int_ips.num_cols(), 6);
+  assert_approx(int_ips.get(0,0), 0.5);
+  for r in range(0, 6) {
+    for c in range(0, 6) { assert_eq!(int_ips.get(r,c), int_ips.get(c,r)); }
+  }
+  // Interior monomials are [1, y, y^2, x, xy, x^2], with the integral of x*y being 1/2 * 1/8.
+  assert_approx(int_ips.get(3,1), 1./16.);
+
+  let right_side = greater_side_face_perp_to_axis(Dim(0));
+  let side_ips = basis.side_mon_inner_products(OShape(0), right_side);
+  assert_eq!(side_ips.num_rows(), 2);
+  assert_approx(side_ips.get(0,0), 0.5);
+  assert_eq!(side_ips.get(0,1), side_ips.get(1,0));
+}
+
+#[test]
+fn test_element_dof_ranges_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let mesh = basis.mesh();
+
+  // Middle element of the bottom row: left, right and top sides are non-boundary sides.
+  let ((int_first, int_end), side_ranges) = basis.element_dof_ranges(FENum(1));
+  assert_eq!((int_first, int_end), (BasisElNum(6), BasisElNum(12)));
+  assert_eq!(side_ranges.len(), 3);
+  assert!(side_ranges.iter().all(|&(sf, _)| !mesh.is_boundary_side(FENum(1), sf)));
+
+  let mut int_ranges = ~[];
+  for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+    let ((int_first, int_end), side_ranges) = basis.element_dof_ranges(fe);
+    assert_eq!(*int_end - *int_first, basis.mons_per_fe_int());
+    for i in range(*int_first, *int_end) {
+      assert!(basis.is_int_supported(BasisElNum(i)));
+      assert_eq!(basis.support_int_fe_num(BasisElNum(i)), fe);
+    }
+    let num_nb_sides = range(0, 4).count(|sf| !mesh.is_boundary_side(fe, SideFace(sf)));
+    assert_eq!(side_ranges.len(), num_nb_sides);
+    for &(sf, (side_first, side_end)) in side_ranges.iter() {
+      assert_eq!(*side_end - *side_first, basis.mons_per_fe_side());
+      for i in range(*side_first, *side_end) {
+        let incls = basis.fe_inclusions_of_side_support(BasisElNum(i));
+        assert!((incls.fe1 == fe && incls.side_face_in_fe1 == sf) || (incls.fe2 == fe && incls.side_face_in_fe2 == sf));
+      }
+    }
+    int_ranges.push((int_first, int_end));
+  }
+
+  // Interior ranges of distinct elements are disjoint.
+  for i in range(0, int_ranges.len()) {
+    for j in range(i+1, int_ranges.len()) {
+      let (ref fi, ref ei) = int_ranges[i];
+      let (ref fj, ref ej) = int_ranges[j];
+      assert!(*ei <= *fj || *ej <= *fi);
+    }
+  }
+}
+
+#[test]
+fn test_total_vs_factor_deg_basis_and_wgrad_sizes_2x2_deg2() {
+  let rmesh1: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(2),MeshCoord(2)]);
+  let rmesh2: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[2.,2.], ~[MeshCoord(2),MeshCoord(2)]);
+  let total_basis = &WGBasis::new(rmesh1, MaxMonDeg(2), MaxMonDeg(2));
+  let factor_basis = &WGBasis::new(rmesh2, MaxMonFactorDeg(2), MaxMonFactorDeg(2));
+
+  // 4 elements and 4 non-boundary sides, with 6 vs 9 interior monomials and 3 side monomials.
+  assert_eq!(total_basis.mons_per_fe_int(), 6);
+  assert_eq!(factor_basis.mons_per_fe_int(), 9);
+  assert_eq!(total_basis.mons_per_fe_side(), 3);
+  assert_eq!(factor_basis.mons_per_fe_side(), 3);
+  assert_eq!(total_basis.num_els(), 4*6 + 4*3);
+  assert_eq!(factor_basis.num_els(), 4*9 + 4*3);
+
+  // Weak gradient components are linear for total degree 2, and of factor degree 2 for factor degree 2, which
+  // includes the partial derivatives such as 2xy^2 of the interior monomials.
+  assert!(wgrad_comp_mons_deg_lim::<Mon2d>(MaxMonDeg(2)) == MaxMonDeg(1));
+  assert!(wgrad_comp_mons_deg_lim::<Mon2d>(MaxMonFactorDeg(2)) == MaxMonFactorDeg(2));
+  assert!(wgrad_comp_mons_deg_lim::<Mon2d>(MaxMonDeg(0)) == MaxMonDeg(0));
+  assert_eq!(total_basis.wgrad_comp_mons().len(), 3);
+  assert_eq!(factor_basis.wgrad_comp_mons().len(), 9);
+
+  // The WG function with interior x^2 y on the unit square and its traces, y on the right side and x^2 on the top,
+  // has weak gradient equal to its classical gradient (2xy, x^2), which is in the factor degree 2 space.
+  let basis = factor_basis;
+  let (right, top) = (greater_side_face_perp_to_axis(Dim(0)), greater_side_face_perp_to_axis(Dim(1)));
+  let (x, y) = (Mon2d { exps: [Deg(1), Deg(0)] }, Mon2d { exps: [Deg(0), Deg(1)] });
+  let int_monn = basis.ref_int_mons().iter().position(|m| *m == x*x*y).unwrap();
+  let right_monn = basis.side_mons_for_oshape_side(OShape(0), right).iter().position(|m| *m == y).unwrap();
+  let top_monn = basis.side_mons_for_oshape_side(OShape(0), top).iter().position(|m| *m == x*x).unwrap();
+  let comp_mons = basis.wgrad_comp_mons();
+  let wgrads = [basis.int_mon_wgrad(FaceMonNum(int_monn), OShape(0)),
+                basis.side_mon_wgrad(FaceMonNum(right_monn), OShape(0), right),
+                basis.side_mon_wgrad(FaceMonNum(top_monn), OShape(0), top)];
+  let pt = [0.3, 0.7];
+  let wgrad_val = wgrads.iter().fold(~[0 as R, 0.], |sum, wgrad| {
+    let val = wgrad.eval(comp_mons, pt);
+    ~[sum[0] + val[0], sum[1] + val[1]]
+  });
+  assert_approx(wgrad_val[0], 2. * 0.3 * 0.7);
+  assert_approx(wgrad_val[1], 0.3 * 0.3);
+}
+
+#[test]
+fn test_element_dof_incidence_3x2_deg2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,2.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let incidence = basis.element_dof_incidence();
+  assert_eq!(incidence.num_rows(), 6);
+  // 36 interior basis elements with one element each, and 14 side basis elements with two each.
+  assert_eq!(incidence.num_values(), 36 + 2*14);
+
+  let mut col_counts = vec::from_elem(basis.num_els(), 0u);
+  for (fe, i, v) in incidence.entries() {
+    assert_eq!(v, 1.);
+    col_counts[i] += 1;
+    if basis.is_int_supported(BasisElNum(i)) {
+      assert_eq!(basis.support_int_fe_num(BasisElNum(i)), FENum(fe));
+    } else {
+      let incls = basis.fe_inclusions_of_side_support(BasisElNum(i));
+      assert!(incls.fe1 == FENum(fe) || incls.fe2 == FENum(fe));
+    }
+  }
+  for i in range(0, basis.num_els()) {
+    assert_eq!(col_counts[i], if basis.is_int_supported(BasisElNum(i)) { 1 } else { 2 });
+  }
+  // The middle element of the bottom row has 6 interior and 3 non-boundary sides with 2 side basis elements each.
+  assert_eq!(incidence.entries().count(|(fe, _, _)| fe == 1), 6 + 3*2);
+}
@@ -0,0 +1,184 @@
+use la;
+use common::{R, Dim};
+use monomial::{Mon1d, Mon2d, Mon3d, MaxMonDeg};
+use polynomial::Polynomial;
+use mesh::{Mesh, FENum, SideFace, NBSideNum};
+use rectangle_mesh::{RectMesh, MeshCoord, Inward, lesser_side_face_perp_to_axis, greater_side_face_perp_to_axis};
+use wg_basis::{WGBasis, BasisElNum, FaceMonNum};
+use dense_matrix::DenseMatrix;
+use sparse_matrix::{SparseMatrix, Symmetric};
+use vbf_laplace::VBFLaplace;
+use variational_bilinear_form::VariationalBilinearForm;
+use wg_solver;
+use wg_error_estimates;
+
+use std::num::{abs, sin};
+use std::f64::consts::PI;
+use std::vec;
+
+// Linear solution which does not vary along the extrusion axis, with -Δu = 0.
+fn u(x: &[R]) -> R { 1. + x[0] + 2.*x[1] }
+fn f(_: &[R]) -> R { 0 as R }
+
+#[test]
+fn test_extruded_10x10x1_mesh_sides_on_extrusion_axis_are_boundary() {
+  let rmesh: ~RectMesh<Mon3d> = ~RectMesh::new(~[0.,0.,0.], ~[1.,1.,0.1], ~[MeshCoord(10),MeshCoord(10),MeshCoord(1)]);
+
+  assert_eq!(rmesh.num_fes(), 100);
+  assert_eq!(rmesh.num_nb_sides(), 2 * 9 * 10);
+  assert_eq!(rmesh.num_boundary_sides_by_perp_axis(), ~[20, 20, 200]);
+  for fe in range(0, rmesh.num_fes()) { let fe = FENum(fe);
+    assert!(rmesh.is_boundary_side(fe, SideFace(4)));
+    assert!(rmesh.is_boundary_side(fe, SideFace(5)));
+  }
+}
+
+#[test]
+fn test_extruded_10x10x1_solve_matches_10x10_solve() {
+  la::init();
+
+  let rmesh2d: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(10),MeshCoord(10)]);
+  let vbf2d = &VBFLaplace::new(None, ~WGBasis::new(rmesh2d, MaxMonDeg(2), MaxMonDeg(1)));
+  let (sol2d, _) = wg_solver::solve(vbf2d, f, u);
+
+  let rmesh3d: ~RectMesh<Mon3d> = ~RectMesh::new(~[0.,0.,0.], ~[1.,1.,0.1], ~[MeshCoord(10),MeshCoord(10),MeshCoord(1)]);
+  let vbf3d = &VBFLaplace::new(None, ~WGBasis::new(rmesh3d, MaxMonDeg(2), MaxMonDeg(1)));
+  let (sol3d, _) = wg_solver::solve(vbf3d, f, u);
+
+  // With a single layer, finite elements are numbered identically in the 2D and extruded meshes.
+  for fe in range(0, 100) { let fe = FENum(fe);
+    let (col, row) = ((*fe % 10) as R, (*fe / 10) as R);
+    let val2d = sol2d.value_at_int_rel(fe, [0.05, 0.05]);
+    let val3d = sol3d.value_at_int_rel(fe, [0.05, 0.05, 0.05]);
+    assert_approx(val2d, val3d);
+    assert_approx(val3d, u([0.1*col + 0.05, 0.1*row + 0.05, 0.05]));
+  }
+}
+
+#[test]
+fn test_discrete_conservation_for_manufactured_solution_20x20() {
+  la::init();
+
+  // u = sin(πx) sin(πy) vanishes on the boundary of the unit square, with -Δu = 2π² u integrating to 8.
+  fn u_sin(x: &[R]) -> R { sin(PI * x[0]) * sin(PI * x[1]) }
+  fn f_sin(x: &[R]) -> R { 2. * PI * PI * sin(PI * x[0]) * sin(PI * x[1]) }
+
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(20),MeshCoord(20)]);
+  let vbf = &VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let (sol, _) = wg_solver::solve(vbf, f_sin, u_sin);
+
+  assert!(vbf.basis().check_discrete_conservation(sol.basis_coefs(), f_sin, 0.1));
+
+  // A solution which is zero on the interiors conserves nothing.
+  let zero_sol = vec::from_elem(vbf.basis().num_els(), 0 as R);
+  assert!(!vbf.basis().check_discrete_conservation(zero_sol.as_slice(), f_sin, 0.1));
+
+  // On a mesh with the inward normal convention, side supported weak gradients are negated, so negating the side
+  // coefficients of the solution gives the same weak gradients. The flux is still through the outward normals.
+  let inward_rmesh: ~RectMesh<Mon2d> = ~RectMesh::new_with_normal_convention(~[0.,0.], ~[1.,1.], ~[MeshCoord(20),MeshCoord(20)], Inward);
+  let inward_basis = WGBasis::new(inward_rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let inward_coefs = vec::from_fn(inward_basis.num_els(), |i| {
+    let coef = sol.basis_coefs()[i];
+    if inward_basis.is_side_supported(BasisElNum(i)) { -coef } else { coef }
+  });
+  assert!(inward_basis.check_discrete_conservation(inward_coefs.as_slice(), f_sin, 0.1));
+  assert!(!inward_basis.check_discrete_conservation(zero_sol.as_slice(), f_sin, 0.1));
+}
+
+#[test]
+fn test_parallel_l2_error_matches_serial_10x10() {
+  la::init();
+
+  fn u_sin(x: &[R]) -> R { sin(PI * x[0]) * sin(PI * x[1]) }
+  fn f_sin(x: &[R]) -> R { 2. * PI * PI * sin(PI * x[0]) * sin(PI * x[1]) }
+
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[1.,1.], ~[MeshCoord(10),MeshCoord(10)]);
+  let vbf = &VBFLaplace::new(None, ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1)));
+  let (sol, _) = wg_solver::solve(vbf, f_sin, u_sin);
+
+  let serial = wg_error_estimates::err_L2_norm(u_sin, &sol);
+  assert!(serial > 0.);
+  // Thread counts dividing the elements evenly and unevenly, and exceeding the number of elements.
+  for &num_threads in [1u, 3, 4, 200].iter() {
+    let parallel = wg_error_estimates::l2_error_parallel(vbf.basis(), sol.basis_coefs(), u_sin, num_threads);
+    assert!(abs(parallel - serial) <= 1e-14 * serial);
+  }
+}
+
+#[test]
+fn test_periodic_poisson_with_mean_constraint_16() {
+  la::init();
+
+  // u = sin(2πx) is periodic on [0,1] with zero mean, and -u'' = 4π² u.
+  fn u_per(x: &[R]) -> R { sin(2. * PI * x[0]) }
+  fn f_per(x: &[R]) -> R { 4. * PI * PI * sin(2. * PI * x[0]) }
+
+  let rmesh: ~RectMesh<Mon1d> = ~RectMesh::new_periodic(~[0.], ~[1.], ~[MeshCoord(16)], ~[true]);
+  let basis = ~WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let (left, right) = (lesser_side_face_perp_to_axis(Dim(0)), greater_side_face_perp_to_axis(Dim(0)));
+
+  // The side on the bounds joins the last and first elements, supporting a side basis element like the other sides.
+  assert_eq!(basis.mesh().num_nb_sides(), 16);
+  assert_eq!(basis.mesh().num_boundary_sides(), 0);
+  assert_eq!(basis.num_els(), 16*3 + 16);
+  let wrap_incls = basis.mesh().fe_inclusions_of_nb_side(NBSideNum(15));
+  assert!(wrap_incls.fe1 == FENum(0) && wrap_incls.fe2 == FENum(15));
+  let wrap_el = basis.fe_side_mon_el_num(FENum(0), left, FaceMonNum(0));
+  assert_eq!(basis.fe_side_mon_el_num(FENum(15), right, FaceMonNum(0)), wrap_el);
+  assert_eq!(wrap_el, basis.nb_side_mon_el_num(NBSideNum(15), FaceMonNum(0)));
+
+  let vbf = &VBFLaplace::new(None, basis);
+  let basis = vbf.basis();
+  let n = basis.num_els();
+
+  // The constants are in the null space of the periodic system, so it is extended by a Lagrange multiplier for the
+  // constraint that the solution's interior polynomials have zero mean.
+  let int_integrals = wg_solver::assemble_wg_load(basis, |_| 1.);
+  let stiffness = vbf.basis_els_vs_basis_els_transpose();
+  let mut triplets: ~[(uint,uint,R)] = stiffness.entries().collect();
+  for i in range(0, n) {
+    if int_integrals[i] != 0. { triplets.push((i, n, int_integrals[i])); }
+  }
+  triplets.push((n, n, 0.));
+  let sys = SparseMatrix::from_triplets(n+1, triplets, Symmetric);
+  let load = wg_solver::assemble_wg_load(basis, f_per);
+  let rhs = DenseMatrix::from_fn(n+1, 1, |i,_| if i < n { load[i] } else { 0. });
+  let aug_sol = la::solve_sparse(&sys, &rhs);
+  let sol_coefs = aug_sol.slice(0, n);
+
+  let mean = range(0, n).fold(0 as R, |sum, i| sum + int_integrals[i] * sol_coefs[i]);
+  assert!(abs(mean) < 1e-10);
+  let h = 1./16.;
+  for fe in range(0, 16) {
+    let val = basis.fe_int_poly(FENum(fe), sol_coefs).value_at([h/2.]);
+    assert!(abs(val - u_per([(fe as R + 0.5) * h])) < 1e-2);
+  }
+
+  // The solution is periodic, the traces of the first and last interiors at the bounds agreeing with each other
+  // and with the value on the side joining them.
+  let first_trace = basis.fe_int_poly(FENum(0), sol_coefs).value_at([0.]);
+  let last_trace = basis.fe_int_poly(FENum(15), sol_coefs).value_at([h]);
+  assert!(abs(first_trace - last_trace) < 1e-2);
+  assert!(abs(sol_coefs[*wrap_el] - first_trace) < 1e-2);
+}
+
+#[test]
+fn test_load_vector_for_constant_source_3x2() {
+  let rmesh: ~RectMesh<Mon2d> = ~RectMesh::new(~[0.,0.], ~[3.,1.], ~[MeshCoord(3),MeshCoord(2)]);
+  let basis = &WGBasis::new(rmesh, MaxMonDeg(2), MaxMonDeg(1));
+  let load = wg_solver::assemble_wg_load(basis, |_| 2.);
+
+  assert_eq!(load.len(), basis.num_els());
+  for i in range(0, basis.num_els()) { let el = BasisElNum(i);
+    if basis.is_int_supported(el) {
+      let oshape = basis.mesh().oriented_shape_for_fe(basis.support_int_fe_num(el));
+      assert_approx(load[i], 2. * basis.mesh().intg_facerel_mon_on_oshape_int(basis.int_mon(el), oshape));
+    } else {
+      assert_eq!(load[i], 0.);
+    }
+  }
+}
+
+fn assert_approx(a:R, b:R) -> () {
+  assert!(abs(a - b) < 10e-6)
+}
@@ -117,6 +117,23 @@ impl<Mon:Monomial> Mesh<Mon> for TriMesh<Mon> {
     self.oshapes[*os].dep_dims_by_side_face[*sf]
   }
 
+  fn outward_normal_for_oshape_side(&self, os: OShape, sf: SideFace) -> ~[R] {
+    let n = self.oshapes[*os].outward_normals_by_side_face[*sf];
+    ~[n.n0(), n.n1()]
+  }
+
+  fn oshape_side_measure(&self, os: OShape, sf: SideFace) -> R {
+    let ref_tri = &self.oshapes[*os];
+    let (v0, v1, v2) = ((0.,0.), ref_tri.v01, ref_tri.v02);
+    let (a,b) = side_face_endpoint_pair(sf, v0,v1,v2, ref_tri.nums_side_faces_between_vertexes, VertexOrder);
+    dist(a, b)
+  }
+
+  fn oshape_interior_measure(&self, os: OShape) -> R {
+    let ref_tri = &self.oshapes[*os];
+    abs(ref_tri.v01.n0() * ref_tri.v02.n1() - ref_tri.v01.n1() * ref_tri.v02.n0()) / 2.
+  }
+
   #[inline]
   fn fe_inclusions_of_nb_side(&self, nbsn: NBSideNum) -> NBSideInclusions {
     self.nbsideincls_by_nbsidenum[*nbsn]
@@ -333,6 +350,38 @@ impl<Mon:Monomial> Mesh<Mon> for TriMesh<Mon> {
   }
 
 
+  #[inline]
+  fn intg_facerel_mon_x_facerel_mon_on_oshape_face
+     ( &self,
+       mon_1: Mon,
+       mon_2: Mon,
+       os:    OShape,
+       face:  Face )
+     -> R
+  {
+    match face {
+      Interior => self.intg_facerel_mon_on_oshape_int(mon_1 * mon_2, os),
+      Side(sf) => self.intg_facerel_mon_on_oshape_side(mon_1 * mon_2, os, sf)
+    }
+  }
+
+  #[inline]
+  fn intg_facerel_mon_x_facerel_poly_on_oshape_face <P: Polynomial<Mon>>
+     ( &self,
+       mon:  Mon,
+       p:    &P,
+       os:   OShape,
+       face: Face )
+     -> R
+  {
+    match face {
+      Interior =>
+        intg_facerel_poly_fn_on_reftri_int(|x| mon.value_at(x) * p.value_at(x), Deg(*mon.max_var_deg() + *p.max_var_deg()),
+                                           &self.oshapes[*os]),
+      Side(sf) => self.intg_facerel_mon_x_facerel_poly_on_oshape_side(mon, p, os, sf)
+    }
+  }
+
   #[inline]
   fn intg_facerel_mon_x_facerel_poly_on_oshape_side <P: Polynomial<Mon>>
      ( &self,
@@ -1,9 +1,10 @@
 use common::{R, R_NaN};
 use mesh::{Mesh, FENum, OShape, SideFace, NBSideNum, NBSideInclusions};
-use wg_basis::{WGBasis, FaceMonNum};
+use wg_basis::{WGBasis, BasisElNum, FaceMonNum};
 use monomial::Monomial;
 use storage_by_ints::{StorageByInts3, StorageByInts4, StorageByInts5};
-use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric};
+use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric, General};
+use la::mkl_int;
 
 use std::vec;
 use std::option::{Option};
@@ -34,7 +35,27 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
                                    monn_1: FaceMonNum, side_face_1: SideFace,
                                    monn_2: FaceMonNum, side_face_2: SideFace) -> R;
 
+  /// Assemble the transpose of the matrix of this form's values between pairs of basis elements, which is the
+  /// system matrix for the WG problem. To assemble many systems having the same sparsity pattern, see
+  /// stiffness_sparsity_pattern and assemble_stiffness_csr3.
   fn basis_els_vs_basis_els_transpose(&self) -> SparseMatrix {
+    match self.basis_els_vs_basis_els_transpose_resumable(None, 0, |_| true) {
+      Ok(m) => m,
+      Err(_) => fail!("Assembly was stopped although no checkpoints were requested.")
+    }
+  }
+
+  /// Assemble the system matrix as in basis_els_vs_basis_els_transpose, as a resumable job. Every checkpoint_interval
+  /// completed elements (finite elements while assembling interior supported rows, then non-boundary sides while
+  /// assembling side supported rows), a checkpoint of the progress is passed to on_checkpoint, which may serialize
+  /// it and returns whether assembly should continue. If it returns false, assembly stops and the checkpoint is
+  /// returned as the error value. Assembly can be resumed from such a checkpoint, skipping the completed elements,
+  /// by passing it as resume_from. A checkpoint interval of 0 disables checkpoints.
+  fn basis_els_vs_basis_els_transpose_resumable(&self,
+                                                resume_from: Option<AssemblyCheckpoint>,
+                                                checkpoint_interval: uint,
+                                                on_checkpoint: |&AssemblyCheckpoint| -> bool)
+                                                -> Result<SparseMatrix, AssemblyCheckpoint> {
 
     let (basis, mesh) = (self.basis(), self.basis().mesh());
 
@@ -48,6 +69,25 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
       SparseMatrix::new_with_capacities(ub_est_interactions, basis.num_els(), mtype)
     };
 
+    // The checkpoint of the progress, whose entries are extended at each checkpoint by those pushed since the last.
+    let mut checkpoint = AssemblyCheckpoint { entries: ~[], fes_completed: 0, nb_sides_completed: 0 };
+
+    // Restore the matrix entries of completed elements when resuming from a checkpoint.
+    let (first_fe, first_nbs) = match resume_from {
+      Some(AssemblyCheckpoint { entries: entries, fes_completed: fes_completed, nb_sides_completed: nb_sides_completed }) => {
+        if fes_completed > mesh.num_fes() || nb_sides_completed > mesh.num_nb_sides() ||
+           nb_sides_completed > 0 && fes_completed < mesh.num_fes() {
+          fail!("Assembly checkpoint is not consistent with the basis being assembled.");
+        }
+        for &(r, c, v) in entries.iter() {
+          m.push(r, c, v);
+        }
+        checkpoint.entries = entries;
+        (fes_completed, nb_sides_completed)
+      }
+      None => (0, 0)
+    };
+
     // Buffer to store non-boundary sides' numbers and fe side faces for either one or two finite elements.
     let mut fe_nb_sides_buf = vec::from_elem(mesh.max_num_shape_sides()*2, (NBSideNum(0), FENum(0), SideFace(0)));
     // Buffer to store the non-boundary side interactions with a single given non-boundary side.
@@ -75,7 +115,7 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
 
     // Iterate basis element pairs beginning with an interior supported element. 
     
-    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+    for fe in range(first_fe, mesh.num_fes()) { let fe = FENum(fe);
       let oshape = mesh.oriented_shape_for_fe(fe);
 
       for monn_1 in range(0, num_int_mons) { let monn_1 = FaceMonNum(monn_1);
@@ -104,11 +144,18 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
           }
         }
       }
+
+      let fes_completed = *fe + 1;
+      if checkpoint_interval > 0 && fes_completed % checkpoint_interval == 0 {
+        for entry in m.entries_from(checkpoint.entries.len()) { checkpoint.entries.push(entry); }
+        checkpoint.fes_completed = fes_completed;
+        if !on_checkpoint(&checkpoint) { return Err(checkpoint); }
+      }
     }
 
     // Iterate basis element pairs beginning with a side supported element.
 
-    for nbs in range(0, mesh.num_nb_sides()) { let nbs = NBSideNum(nbs);
+    for nbs in range(first_nbs, mesh.num_nb_sides()) { let nbs = NBSideNum(nbs);
       // Get the representations of our nbs as the side faces of finite elements.
       let nbs_incls = mesh.fe_inclusions_of_nb_side(nbs);
 
@@ -175,9 +222,138 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
           }
         } // side-side interactions
       } // monn_1
+
+      let nb_sides_completed = *nbs + 1;
+      if checkpoint_interval > 0 && nb_sides_completed % checkpoint_interval == 0 {
+        for entry in m.entries_from(checkpoint.entries.len()) { checkpoint.entries.push(entry); }
+        checkpoint.fes_completed = mesh.num_fes();
+        checkpoint.nb_sides_completed = nb_sides_completed;
+        if !on_checkpoint(&checkpoint) { return Err(checkpoint); }
+      }
     } // nbs
 
-    m
+    Ok(m)
+  }
+
+  /// Assemble the system matrix as in basis_els_vs_basis_els_transpose, partitioned into its interior-interior,
+  /// interior-side and side-side blocks, for Schur complement solvers. The blocks are indexed relative to the
+  /// beginnings of the interior supported and side supported basis element number ranges. The form must be
+  /// symmetric, so that the omitted side-interior block is the transpose of the interior-side block. The diagonal
+  /// blocks are Symmetric matrices. The interior-interior block is block diagonal, with a block for each finite
+  /// element, since interior supported elements only interact on a common supporting finite element. A row of the
+  /// interior-side block for an element without non-boundary sides holds a single explicit zero in column 0.
+  fn basis_els_vs_basis_els_transpose_blocks(&self) -> (SparseMatrix, SparseMatrix, SparseMatrix) {
+    if !self.is_symmetric() { fail!("Block assembly requires a symmetric variational form."); }
+
+    let m = self.basis_els_vs_basis_els_transpose();
+    let num_int_els = self.basis().num_int_els();
+    let num_side_els = self.basis().num_els() - num_int_els;
+
+    let (num_int_int_vals, num_int_side_vals) = m.entries().fold((0u, 0u), |(ii, is), (r, c, _)| {
+      if r >= num_int_els { (ii, is) } else if c < num_int_els { (ii+1, is) } else { (ii, is+1) }
+    });
+    let mut int_int = SparseMatrix::new_with_capacities(num_int_int_vals, num_int_els, Symmetric);
+    let mut int_side = SparseMatrix::new_with_capacities(num_int_side_vals + num_int_els, num_int_els, General);
+    let mut side_side = SparseMatrix::new_with_capacities(m.num_values() - num_int_int_vals - num_int_side_vals,
+                                                          num_side_els, Symmetric);
+
+    // Next interior-side block row to begin, for filling rows which have no values.
+    let mut next_int_side_row = 0u;
+    for (r, c, v) in m.entries() {
+      if r >= num_int_els {
+        side_side.push(r - num_int_els, c - num_int_els, v);
+      }
+      else if c < num_int_els {
+        int_int.push(r, c, v);
+      }
+      else {
+        while next_int_side_row < r { int_side.push(next_int_side_row, 0, 0 as R); next_int_side_row += 1; }
+        int_side.push(r, c - num_int_els, v);
+        next_int_side_row = r + 1;
+      }
+    }
+    while next_int_side_row < num_int_els { int_side.push(next_int_side_row, 0, 0 as R); next_int_side_row += 1; }
+
+    (int_int, int_side, side_side)
+  }
+
+  /// Compute the sparsity pattern of the system matrix of this form, which has an entry for each pair of basis
+  /// elements supported on a common finite element, or only for those pairs in the upper triangle if the form is
+  /// symmetric. The pattern depends only on the basis and the symmetry of the form, so it can be computed once and
+  /// reused by assemble_stiffness_csr3 for any number of assemblies.
+  fn stiffness_sparsity_pattern(&self) -> SparsityPattern {
+    let basis = self.basis();
+    let sym = self.is_symmetric();
+    let num_els = basis.num_els();
+    let mut row_first_value_ixs = vec::with_capacity(num_els + 1);
+    let mut value_cols = vec::with_capacity(basis.est_num_el_el_pairs_with_common_supp_fes(sym));
+    let mut row_cols = ~[];
+    for r in range(0, num_els) {
+      row_cols.clear();
+      let (num_supps, supps) = basis_el_supports(basis, BasisElNum(r));
+      for &(fe, _, _) in supps.slice_to(num_supps).iter() {
+        let ((int_first, int_end), side_ranges) = basis.element_dof_ranges(fe);
+        for c in range(*int_first, *int_end) { row_cols.push(c); }
+        for &(_, (side_first, side_end)) in side_ranges.iter() {
+          for c in range(*side_first, *side_end) { row_cols.push(c); }
+        }
+      }
+      row_cols.sort();
+      row_cols.dedup();
+      row_first_value_ixs.push(value_cols.len() as mkl_int);
+      for &c in row_cols.iter() {
+        if !sym || c >= r { value_cols.push(c as mkl_int); }
+      }
+    }
+    row_first_value_ixs.push(value_cols.len() as mkl_int);
+    SparsityPattern { row_first_value_ixs: row_first_value_ixs, value_cols: value_cols, upper_only: sym }
+  }
+
+  /// Assemble the system matrix of this form as in basis_els_vs_basis_els_transpose, writing its values directly
+  /// into CSR3 arrays with the given sparsity pattern, which must have been computed by stiffness_sparsity_pattern
+  /// for a form of the same symmetry on the same basis. Returns the row beginning indexes (capped with the number of
+  /// values), the column numbers and the values, in the form taken by the sparse solvers. Entries of the pattern
+  /// which are zero are stored as explicit zeros.
+  fn assemble_stiffness_csr3(&self, pattern: &SparsityPattern) -> (~[mkl_int], ~[mkl_int], ~[R]) {
+    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let sym = self.is_symmetric();
+    let num_els = basis.num_els();
+    if pattern.upper_only != sym || pattern.row_first_value_ixs.len() != num_els + 1 {
+      fail!("Sparsity pattern does not match the basis and symmetry of the variational form.");
+    }
+
+    let (int_vs_int_vbf_vals, int_vs_side_vbf_vals, side_vs_int_vbf_vals, side_vs_side_vbf_fe_contrs) =
+      (self.ref_int_vs_int_vbf_values(sym),
+       if !sym { self.ref_int_vs_side_vbf_values() } else { StorageByInts4::from_elem(0,0,0,0,0 as R) },
+       self.ref_side_vs_int_vbf_values(),
+       self.ref_side_vs_side_vbf_fe_contrs(sym));
+
+    let mut values = vec::with_capacity(pattern.value_cols.len());
+    for r in range(0, num_els) {
+      let (num_r_supps, r_supps) = basis_el_supports(basis, BasisElNum(r));
+      for i in range(pattern.row_first_value_ixs[r] as uint, pattern.row_first_value_ixs[r+1] as uint) {
+        let (num_c_supps, c_supps) = basis_el_supports(basis, BasisElNum(pattern.value_cols[i] as uint));
+        // Sum the contributions of the finite elements supporting both elements. As in the assembly of
+        // basis_els_vs_basis_els_transpose, values are fetched with the column element in first position.
+        let mut v = 0 as R;
+        for &(fe, r_sf, r_monn) in r_supps.slice_to(num_r_supps).iter() {
+          for &(c_fe, c_sf, c_monn) in c_supps.slice_to(num_c_supps).iter() {
+            if c_fe != fe { continue; }
+            let oshape = mesh.oriented_shape_for_fe(fe);
+            v += match (c_sf, r_sf) {
+              (None, None) => int_vs_int_vbf_vals.get(*oshape, *c_monn, *r_monn),
+              (Some(c_sf), None) => side_vs_int_vbf_vals.get(*oshape, *c_monn, *c_sf, *r_monn),
+              (None, Some(r_sf)) => int_vs_side_vbf_vals.get(*oshape, *c_monn, *r_monn, *r_sf),
+              (Some(c_sf), Some(r_sf)) =>
+                self.get_side_vs_side_vbf_contr(oshape, c_monn, c_sf, r_monn, r_sf, sym, &side_vs_side_vbf_fe_contrs),
+            };
+          }
+        }
+        values.push(v);
+      }
+    }
+
+    (pattern.row_first_value_ixs.clone(), pattern.value_cols.clone(), values)
   }
 
   /* Returns a collection of interior monomial vs interior monomial vbf values.  Results are indexed by oshape,
@@ -275,6 +451,76 @@ pub trait VariationalBilinearForm<Mon:Monomial,MeshT:Mesh<Mon>> {
 } // trait VariationalBilinearForm
 
 
+/// Sparsity pattern of a system matrix in the CSR3 format, as computed by
+/// VariationalBilinearForm::stiffness_sparsity_pattern: the row beginning indexes into the values, capped with the
+/// number of values, and the column numbers of the values. Only upper triangle entries are included in the pattern
+/// for a symmetric form.
+pub struct SparsityPattern {
+  row_first_value_ixs: ~[mkl_int],
+  value_cols: ~[mkl_int],
+  upper_only: bool,
+}
+
+/// A checkpoint of a partially completed system matrix assembly, see
+/// VariationalBilinearForm::basis_els_vs_basis_els_transpose_resumable. The matrix entries pushed for the
+/// completed elements are held as (row, column, value) triplets in row-major order, as given by
+/// SparseMatrix::entries. Interior supported rows are assembled for all finite elements before side supported rows
+/// are assembled for any non-boundary side, so nb_sides_completed is only non-zero once fes_completed is the number of finite elements in the mesh.
+#[deriving(Eq, Clone)]
+pub struct AssemblyCheckpoint {
+  entries: ~[(uint, uint, R)],
+  fes_completed: uint,
+  nb_sides_completed: uint,
+}
+
+impl AssemblyCheckpoint {
+
+  /// Write the checkpoint in a binary little-endian format which can be read back by read_from.
+  pub fn write_to<W:Writer>(&self, w: &mut W) {
+    w.write_le_u64(self.fes_completed as u64);
+    w.write_le_u64(self.nb_sides_completed as u64);
+    w.write_le_u64(self.entries.len() as u64);
+    for &(r, c, v) in self.entries.iter() {
+      w.write_le_u64(r as u64);
+      w.write_le_u64(c as u64);
+      w.write_le_f64(v);
+    }
+  }
+
+  /// Read a checkpoint previously written by write_to.
+  pub fn read_from<Rd:Reader>(rd: &mut Rd) -> AssemblyCheckpoint {
+    let fes_completed = rd.read_le_u64() as uint;
+    let nb_sides_completed = rd.read_le_u64() as uint;
+    let num_entries = rd.read_le_u64() as uint;
+    let entries = vec::from_fn(num_entries, |_| {
+      let r = rd.read_le_u64() as uint;
+      let c = rd.read_le_u64() as uint;
+      (r, c, rd.read_le_f64())
+    });
+    AssemblyCheckpoint { entries: entries, fes_completed: fes_completed, nb_sides_completed: nb_sides_completed }
+  }
+
+}
+
+
+// Return the finite elements supporting a basis element, with the supporting side face of each for a side supported
+// element (None for an interior supported element) and the face monomial number of the element, as the number of
+// supporting finite elements and a buffer holding them in its first positions.
+#[inline]
+fn basis_el_supports<Mon:Monomial, MeshT:Mesh<Mon>>
+   (basis: &WGBasis<Mon,MeshT>, i: BasisElNum)
+   -> (uint, [(FENum, Option<SideFace>, FaceMonNum), ..2]) {
+  if basis.is_int_supported(i) {
+    let supp = (basis.support_int_fe_num(i), None, basis.int_rel_mon_num(i));
+    (1, [supp, supp])
+  }
+  else {
+    let incls = basis.fe_inclusions_of_side_support(i);
+    let monn = basis.side_rel_mon_num(i);
+    (2, [(incls.fe1, Some(incls.side_face_in_fe1), monn), (incls.fe2, Some(incls.side_face_in_fe2), monn)])
+  }
+}
+
 // Return (non-boundary side number, finite element, side face) triplets for all non-boundary sides of both of the given
 // finite elements, in ascending order.
 #[inline]
@@ -1,15 +1,17 @@
-use common::{R};
+use common::{R, Dim};
 use monomial::Monomial;
 use polynomial::{PolyOwning};
 use dense_matrix::DenseMatrix;
-use mesh::{Mesh, OShape, SideFace};
-use wg_basis::{WGBasis, FaceMonNum};
+use mesh::{Mesh, FENum, OShape, SideFace};
+use wg_basis::{WGBasis, BasisElNum, FaceMonNum};
 use weak_gradient::{WeakGrad, WeakGradOps};
 use projection::Projector;
 use variational_bilinear_form::VariationalBilinearForm;
+use sparse_matrix::{SparseMatrix, Symmetric, General};
 
 use std::vec;
 use std::cast;
+use std::num::abs;
 use std::iter::{AdditiveIterator};
 
 /*  Definition
@@ -66,6 +68,215 @@ impl<Mon:Monomial, MeshT:Mesh<Mon>> VBFLaplace<Mon,MeshT> {
     self.basis.mesh().intg_facerel_poly_on_oshape_int(&wgrads_prod, oshape)
   }
 
+  fn int_mon_vs_int_mon_stab_term(&self, oshape: OShape, monn_1: FaceMonNum, monn_2: FaceMonNum) -> R {
+    let (basis, mesh) = (&*self.basis, self.basis.mesh());
+    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
+    // The values of v and w on the boundary are 0, leaving only
+    // s(v,w) = (1/h_T) <Q_b v_T0, Q_b w_T0>_bnd(T)
+    //        = (1/h_T) sum_{s in sides(T)} {<Q_b v_T0, Q_b w_T0>_s}
+    //        = (1/h_T) sum_{s in sides(T)} {<Q_b v_T0, w_T0>_s}
+    // by the definition of the projection, with <Q_b v_T0, w_T0>_s = sum_k {a_k <m_k, w_T0>_s} for the projection
+    // coefficients a_k of the side monomials m_k, the <m_k, w_T0>_s being the basis' precomputed trace products.
+    let h_inv = mesh.shape_diameter_inv(oshape);
+    h_inv * range(0, mesh.num_side_faces_for_oshape(oshape)).map(|sf| {
+      let proj_coefs = &self.int_mon_side_projs[*oshape][sf][*monn_1].coefs;
+      let trace_prods = basis.int_side_trace_products(oshape, SideFace(sf));
+      range(0, proj_coefs.len()).map(|k| proj_coefs[k] * trace_prods.get(*monn_2, k)).sum()
+    }).sum()
+  }
+
+  fn side_mon_vs_int_mon_stab_term(&self, oshape: OShape, side_monn: FaceMonNum, side_face: SideFace, int_monn: FaceMonNum) -> R {
+    let (basis, mesh) = (&*self.basis, self.basis.mesh());
+    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
+    // With v being our side supported basis element and w the interior supported element,
+    // s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
+    //        = (1/h_T) <-v, Q_b w_T0>_bnd(T)
+    //        = (1/h_T) <-v, Q_b w_T0>_s where s is the supporting side face of v
+    //        = (1/h_T) <-v, w_T0>_s by the definition of the projection, as precomputed by the basis.
+    let ip = -basis.int_side_trace_products(oshape, side_face).get(*int_monn, *side_monn);
+    let h_inv = mesh.shape_diameter_inv(oshape);
+    h_inv * ip
+  }
+
+  fn side_mon_vs_side_mon_stab_term(&self, oshape: OShape,
+                                    monn_1: FaceMonNum, side_face_1: SideFace,
+                                    monn_2: FaceMonNum, side_face_2: SideFace) -> R {
+    let (basis, mesh) = (&*self.basis, self.basis.mesh());
+    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
+    // With v and w being our side supported elements, the projections of the interior extensions are 0, so
+    // s(v,w) = (1/h_T) <-v, -w>_bnd(T)
+    //        = (1/h_T) sum_{s in sides(T)} <v, w>_s
+    //        = | (1/h_T) <v, w>_s,  if v and w have a common support side face s
+    //          | 0, otherwise
+    if side_face_1 != side_face_2 { 0 as R }
+    else {
+      let common_supp_side = side_face_1;
+      let side_mons = basis.side_mons_for_oshape_side(oshape, common_supp_side);
+      let ip = mesh.intg_facerel_mon_on_oshape_side(side_mons[*monn_1] * side_mons[*monn_2], oshape, common_supp_side);
+      mesh.shape_diameter_inv(oshape) * ip
+    }
+  }
+
+  // Create a buffer large enough to hold the basis elements supported on any single finite element, see fe_els_into.
+  fn new_fe_els_buf(&self) -> ~[(BasisElNum, Option<SideFace>, FaceMonNum)] {
+    vec::with_capacity(self.basis.mons_per_fe_int() + self.basis.mesh().max_num_shape_sides() * self.basis.mons_per_fe_side())
+  }
+
+  // Fill the buffer with the basis elements supported on the given finite element, as (basis element number,
+  // supporting side face if side supported, face monomial number), with interior supported elements first.
+  fn fe_els_into(&self, fe: FENum, fe_els: &mut ~[(BasisElNum, Option<SideFace>, FaceMonNum)]) {
+    let (basis, mesh) = (&*self.basis, self.basis.mesh());
+    let oshape = mesh.oriented_shape_for_fe(fe);
+    fe_els.clear();
+    for monn in range(0, basis.mons_per_fe_int()) { let monn = FaceMonNum(monn);
+      fe_els.push((basis.int_mon_el_num(fe, monn), None, monn));
+    }
+    for sf in range(0, mesh.num_side_faces_for_oshape(oshape)) { let sf = SideFace(sf);
+      if !mesh.is_boundary_side(fe, sf) {
+        for monn in range(0, basis.mons_per_fe_side()) { let monn = FaceMonNum(monn);
+          fe_els.push((basis.fe_side_mon_el_num(fe, sf, monn), Some(sf), monn));
+        }
+      }
+    }
+  }
+
+  #[inline]
+  fn el_wgrad<'a>(&'a self, oshape: OShape, side_face: Option<SideFace>, monn: FaceMonNum) -> &'a WeakGrad {
+    match side_face {
+      Some(sf) => self.basis.side_mon_wgrad(monn, oshape, sf),
+      None => self.basis.int_mon_wgrad(monn, oshape)
+    }
+  }
+
+  /** Assemble the system matrix for the form with the spatially varying diffusion tensor K in place of the left
+   *  weak gradient multiplier, so that the weak gradient terms are (K wgrad v, wgrad w)_T, with K(x) given as rows
+   *  for global points x in finite element interiors. The stabilization terms are as for this form. As for
+   *  basis_els_vs_basis_els_transpose, entry (i,j) is the form applied to (b_j, b_i). With K the identity this is
+   *  the system matrix of the form without a multiplier. The matrix is General, and is symmetric when K(x) is
+   *  symmetric at each point.
+   */
+  pub fn assemble_with_diffusion_tensor(&self, k: |&[R]| -> ~[~[R]]) -> SparseMatrix {
+    let (basis, mesh) = (&*self.basis, self.basis.mesh());
+    let wgrad_comp_mons = basis.wgrad_comp_mons();
+    let mut triplets = vec::with_capacity(basis.est_num_el_el_pairs_with_common_supp_fes(false));
+    let mut fe_els = self.new_fe_els_buf();
+
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let oshape = mesh.oriented_shape_for_fe(fe);
+      self.fe_els_into(fe, &mut fe_els);
+
+      for &(test_el, test_sf, test_monn) in fe_els.iter() {
+        let test_wgrad = self.el_wgrad(oshape, test_sf, test_monn);
+        for &(trial_el, trial_sf, trial_monn) in fe_els.iter() {
+          let trial_wgrad = self.el_wgrad(oshape, trial_sf, trial_monn);
+
+          let ip_wgrads_term = mesh.intg_mixed_global_and_facerel_fn_on_fe_int(|x, x_int_rel| {
+            let k_x = k(x);
+            range(0, k_x.len()).fold(0 as R, |sum, r| {
+              let k_trial_wgrad_r = range(0, k_x[r].len()).fold(0 as R, |sum, c| {
+                sum + k_x[r][c] * trial_wgrad.comp_value_at(wgrad_comp_mons, Dim(c), x_int_rel)
+              });
+              sum + k_trial_wgrad_r * test_wgrad.comp_value_at(wgrad_comp_mons, Dim(r), x_int_rel)
+            })
+          }, fe);
+
+          let stab_term = match (trial_sf, test_sf) {
+            (None, None) => self.int_mon_vs_int_mon_stab_term(oshape, trial_monn, test_monn),
+            (Some(sf), None) => self.side_mon_vs_int_mon_stab_term(oshape, trial_monn, sf, test_monn),
+            (None, Some(sf)) => self.side_mon_vs_int_mon_stab_term(oshape, test_monn, sf, trial_monn),
+            (Some(sf_1), Some(sf_2)) => self.side_mon_vs_side_mon_stab_term(oshape, trial_monn, sf_1, test_monn, sf_2),
+          };
+
+          triplets.push((*test_el, *trial_el, ip_wgrads_term + stab_term));
+        }
+      }
+    }
+
+    SparseMatrix::from_triplets(basis.num_els(), triplets, General)
+  }
+
+  /// Iterate lazily over the contributions of single finite elements to the system matrix of this form, as
+  /// (row, column, value) triplets with the row and column numbers being those of the test and trial basis
+  /// elements as in basis_els_vs_basis_els_transpose. The triplets are produced finite element by finite element,
+  /// for all pairs of basis elements supported on the element, and are computed only as they are requested. Since
+  /// side supported elements are supported on two finite elements, triplets for the same row and column may be
+  /// produced for each of the elements, and their values must be summed to obtain the matrix entry.
+  pub fn stiffness_triplet_iter<'a>(&'a self) -> StiffnessTripletIter<'a,Mon,MeshT> {
+    self.new_stiffness_triplet_iter(false)
+  }
+
+  // Make a triplet iterator as for stiffness_triplet_iter, which if upper_only is true skips the pairs of basis
+  // elements with test element number exceeding the trial element number, without computing their values.
+  fn new_stiffness_triplet_iter<'a>(&'a self, upper_only: bool) -> StiffnessTripletIter<'a,Mon,MeshT> {
+    StiffnessTripletIter {
+      vbf: self,
+      upper_only: upper_only,
+      next_fe: 0,
+      oshape: OShape(0),
+      fe_els: self.new_fe_els_buf(),
+      test_ix: 0,
+      trial_ix: 0,
+    }
+  }
+
+  /// Compute all finite element contributions to the system matrix of this form as (row, column, value) triplets,
+  /// as produced by stiffness_triplet_iter. The matrix can be constructed from them via SparseMatrix::from_triplets.
+  /// If upper_only is true, only the triplets with row not exceeding column are included, which suffice to build
+  /// a Symmetric matrix when the form is symmetric, and the values of the others are not computed.
+  pub fn stiffness_triplets(&self, upper_only: bool) -> ~[(uint, uint, R)] {
+    let mut triplets = vec::with_capacity(self.basis.est_num_el_el_pairs_with_common_supp_fes(upper_only));
+    for t in self.new_stiffness_triplet_iter(upper_only) {
+      triplets.push(t);
+    }
+    triplets
+  }
+
+  /** Assemble the system matrix of this form from its finite element contributions as produced by
+   *  stiffness_triplet_iter, omitting off-diagonal entries with magnitude below drop_tol, such as the tiny spurious
+   *  values left by floating point integration, which would otherwise bloat the sparsity pattern. The contributions
+   *  of the finite elements are summed before entries are dropped, so an entry is dropped only if its total is
+   *  small. Diagonal entries are always kept. The matrix is Symmetric, built from the upper triangle contributions,
+   *  when the form is symmetric, and General otherwise. A tolerance of 0 omits nothing. An overly large tolerance
+   *  degrades the accuracy of solutions, besides making the sparsity pattern of a General matrix unsymmetric.
+   */
+  pub fn assemble_with_drop_tol(&self, drop_tol: R) -> SparseMatrix {
+    if drop_tol < 0 as R { fail!("Drop tolerance must be non-negative."); }
+    let sym = self.is_symmetric();
+    let mtype = if sym { Symmetric } else { General };
+    let summed = SparseMatrix::from_triplets(self.basis.num_els(), self.stiffness_triplets(sym), mtype);
+    let kept: ~[(uint, uint, R)] = summed.entries().filter(|&(r, c, v)| r == c || abs(v) >= drop_tol).collect();
+    SparseMatrix::from_triplets(self.basis.num_els(), kept, mtype)
+  }
+
+  /// Compute the trace of the system matrix of this form without assembling it, by summing the form's values for
+  /// each basis element against itself over the finite elements supporting it.
+  pub fn stiffness_trace(&self) -> R {
+    let mesh = self.basis.mesh();
+    let mut fe_els = self.new_fe_els_buf();
+    let mut trace = 0 as R;
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let oshape = mesh.oriented_shape_for_fe(fe);
+      self.fe_els_into(fe, &mut fe_els);
+      for &(_, sf, monn) in fe_els.iter() {
+        trace += self.fe_contr(oshape, sf, monn, sf, monn);
+      }
+    }
+    trace
+  }
+
+  // The form's value for the trial and test basis elements, restricted to a finite element with the given oriented
+  // shape, for elements given as in fe_els_into.
+  fn fe_contr(&self, oshape: OShape,
+              trial_sf: Option<SideFace>, trial_monn: FaceMonNum,
+              test_sf: Option<SideFace>, test_monn: FaceMonNum) -> R {
+    match (trial_sf, test_sf) {
+      (None, None) => self.int_mon_vs_int_mon(oshape, trial_monn, test_monn),
+      (Some(sf), None) => self.side_mon_vs_int_mon(oshape, trial_monn, sf, test_monn),
+      (None, Some(sf)) => self.int_mon_vs_side_mon(oshape, trial_monn, test_monn, sf),
+      (Some(sf_1), Some(sf_2)) => self.side_mon_vs_side_mon_fe_contr(oshape, trial_monn, sf_1, test_monn, sf_2),
+    }
+  }
+
   pub fn left_wgrad_multiplier<'a>(&'a self) -> &'a Option<DenseMatrix> {
     &self.left_wgrad_multiplier 
   }
@@ -89,23 +300,13 @@ impl<Mon:Monomial, MeshT:Mesh<Mon>> VariationalBilinearForm<Mon,MeshT>
                         oshape: OShape,
                         monn_1: FaceMonNum,
                         monn_2: FaceMonNum) -> R {
-    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let basis = self.basis();
 
     let ip_wgrads_term = self.ip_wgrads_term(basis.int_mon_wgrad(monn_1, oshape),
                                              basis.int_mon_wgrad(monn_2, oshape),
                                              oshape);
 
-    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    // The values of v and w on the boundary are 0, leaving only
-    // s(v,w) = (1/h_T) <Q_b v_T0, Q_b w_T0>_bnd(T)
-    //        = (1/h_T) sum_{s in sides(T)} {<Q_b v_T0, Q_b w_T0>_s}
-    let h_inv = mesh.shape_diameter_inv(oshape);
-    let stab_term = h_inv * range(0, mesh.num_side_faces_for_oshape(oshape)).map(|sf| {
-      let projs = &self.int_mon_side_projs[*oshape][sf];
-      mesh.intg_facerel_poly_x_facerel_poly_on_oshape_side(&projs[*monn_1], &projs[*monn_2], oshape, SideFace(sf))
-    }).sum();
-
-    ip_wgrads_term + stab_term
+    ip_wgrads_term + self.int_mon_vs_int_mon_stab_term(oshape, monn_1, monn_2)
   }
 
   #[inline]
@@ -113,26 +314,13 @@ impl<Mon:Monomial, MeshT:Mesh<Mon>> VariationalBilinearForm<Mon,MeshT>
                          oshape: OShape,
                          side_monn: FaceMonNum, side_face: SideFace,
                          int_monn: FaceMonNum) -> R {
-    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let basis = self.basis();
     
     let ip_wgrads_term = self.ip_wgrads_term(basis.side_mon_wgrad(side_monn, oshape, side_face),
                                              basis.int_mon_wgrad(int_monn, oshape),
                                              oshape);
 
-    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    // With v being our side supported basis element and w the interior supported element,
-    // s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    //        = (1/h_T) <-v, Q_b w_T0>_bnd(T)
-    //        = (1/h_T) <-v, Q_b w_T0>_s where s is the supporting side face of v
-    let stab_term = {
-      let side_mon = basis.side_mons_for_oshape_side(oshape, side_face)[*side_monn].clone();
-      let int_proj = &self.int_mon_side_projs[*oshape][*side_face][*int_monn];
-      let ip = -mesh.intg_facerel_mon_x_facerel_poly_on_oshape_side(side_mon, int_proj, oshape, side_face);
-      let h_inv = mesh.shape_diameter_inv(oshape);
-      h_inv * ip
-    };
-
-    ip_wgrads_term + stab_term
+    ip_wgrads_term + self.side_mon_vs_int_mon_stab_term(oshape, side_monn, side_face, int_monn)
   }
   
   #[inline]
@@ -140,26 +328,15 @@ impl<Mon:Monomial, MeshT:Mesh<Mon>> VariationalBilinearForm<Mon,MeshT>
                          oshape: OShape,
                          int_monn: FaceMonNum,
                          side_monn: FaceMonNum, side_face: SideFace) -> R {
-    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let basis = self.basis();
 
     let ip_wgrads_term = self.ip_wgrads_term(basis.int_mon_wgrad(int_monn, oshape),
                                              basis.side_mon_wgrad(side_monn, oshape, side_face),
                                              oshape);
 
-    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    // With v being our interior supported basis element and w the side supported element, 
-    // s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    //        = (1/h_T) <Q_b v_T0, -w>_bnd(T)
-    //        = (1/h_T) <Q_b v_T0, -w>_s where s is the supporting side face of w
-    let stab_term = {
-      let side_mon = basis.side_mons_for_oshape_side(oshape, side_face)[*side_monn].clone();
-      let int_proj = &self.int_mon_side_projs[*oshape][*side_face][*int_monn];
-      let ip = -mesh.intg_facerel_mon_x_facerel_poly_on_oshape_side(side_mon, int_proj, oshape, side_face);
-      let h_inv = mesh.shape_diameter_inv(oshape);
-      h_inv * ip
-    };
-
-    ip_wgrads_term + stab_term
+    // The stabilization term is symmetric in its arguments, being (1/h_T) <Q_b v_T0, -w>_s where s is the
+    // supporting side face of w.
+    ip_wgrads_term + self.side_mon_vs_int_mon_stab_term(oshape, side_monn, side_face, int_monn)
   }
 
   #[inline]
@@ -167,28 +344,58 @@ impl<Mon:Monomial, MeshT:Mesh<Mon>> VariationalBilinearForm<Mon,MeshT>
                                    oshape: OShape,
                                    monn_1: FaceMonNum, side_face_1: SideFace,
                                    monn_2: FaceMonNum, side_face_2: SideFace) -> R {
-    let (basis, mesh) = (self.basis(), self.basis().mesh());
+    let basis = self.basis();
     
     let ip_wgrads_term = self.ip_wgrads_term(basis.side_mon_wgrad(monn_1, oshape, side_face_1),
                                              basis.side_mon_wgrad(monn_2, oshape, side_face_2),
                                              oshape);
 
-    // stabilization term: s(v,w) = (1/h_T) <Q_b v_T0 - v, Q_b w_T0 - w>_bnd(T)
-    // With v and w being our side supported elements, the projections of the interior extensions are 0, so
-    // s(v,w) = (1/h_T) <-v, -w>_bnd(T)
-    //        = (1/h_T) sum_{s in sides(T)} <v, w>_s
-    //        = | (1/h_T) <v, w>_s,  if v and w have a common support side face s
-    //          | 0, otherwise
-    let stab_term =
-      if side_face_1 != side_face_2 { 0 as R }
-      else {
-        let common_supp_side = side_face_1;
-        let side_mons = basis.side_mons_for_oshape_side(oshape, common_supp_side);
-        let ip = mesh.intg_facerel_mon_on_oshape_side(side_mons[*monn_1] * side_mons[*monn_2], oshape, common_supp_side);
-        mesh.shape_diameter_inv(oshape) * ip
-      };
-
-    ip_wgrads_term + stab_term
+    ip_wgrads_term + self.side_mon_vs_side_mon_stab_term(oshape, monn_1, side_face_1, monn_2, side_face_2)
   }
 }
 
+
+
+/// Lazy iterator over the finite element contributions to the system matrix of a Laplace form, see
+/// VBFLaplace::stiffness_triplet_iter.
+pub struct StiffnessTripletIter<'a,Mon,MeshT> {
+  priv vbf: &'a VBFLaplace<Mon,MeshT>,
+  priv upper_only: bool, // whether pairs with test element number exceeding the trial element number are skipped
+  priv next_fe: uint,
+  priv oshape: OShape,
+  priv fe_els: ~[(BasisElNum, Option<SideFace>, FaceMonNum)], // elements supported on the current finite element
+  priv test_ix: uint,
+  priv trial_ix: uint,
+}
+
+impl<'a, Mon:Monomial, MeshT:Mesh<Mon>> Iterator<(uint, uint, R)> for StiffnessTripletIter<'a,Mon,MeshT> {
+  fn next(&mut self) -> Option<(uint, uint, R)> {
+    loop {
+      // Move on to the next finite element when the pairs of the current one are exhausted.
+      while self.test_ix >= self.fe_els.len() {
+        let mesh = self.vbf.basis.mesh();
+        if self.next_fe >= mesh.num_fes() { return None; }
+        let fe = FENum(self.next_fe);
+        self.oshape = mesh.oriented_shape_for_fe(fe);
+        self.vbf.fe_els_into(fe, &mut self.fe_els);
+        self.next_fe += 1;
+        self.test_ix = 0;
+        self.trial_ix = 0;
+      }
+
+      let (test_el, test_sf, test_monn) = self.fe_els[self.test_ix];
+      let (trial_el, trial_sf, trial_monn) = self.fe_els[self.trial_ix];
+
+      self.trial_ix += 1;
+      if self.trial_ix == self.fe_els.len() {
+        self.trial_ix = 0;
+        self.test_ix += 1;
+      }
+
+      if self.upper_only && *test_el > *trial_el { continue; }
+
+      let val = self.vbf.fe_contr(self.oshape, trial_sf, trial_monn, test_sf, test_monn);
+      return Some((*test_el, *trial_el, val));
+    }
+  }
+}
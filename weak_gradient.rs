@@ -1,14 +1,15 @@
 use common::*;
 use vector_monomial::VectorMonomial;
 use monomial;
-use monomial::{Monomial, DegLim, MaxMonDeg, MaxMonFactorDeg};
-use polynomial::{PolyBorrowing};
+use monomial::{Monomial, DegLim};
+use polynomial::{Polynomial, PolyOwning, PolyBorrowing, PolyBorrowingMons};
 use mesh::{Mesh, OShape, SideFace};
 use dense_matrix::DenseMatrix;
 use la;
 use la::lapack_int;
 
 use std::vec;
+use std::mem::size_of;
 
 /*
  * For a function v on a finite element T, the weak gradient of degree r of v on T
@@ -41,6 +42,46 @@ pub struct WeakGrad {
   comp_mon_coefs: ~[~[R]]
 }
 
+impl WeakGrad {
+
+  /// Approximate number of bytes of memory used by this weak gradient, including its component coefficients.
+  pub fn approx_memory_bytes(&self) -> uint {
+    size_of::<WeakGrad>() +
+      self.comp_mon_coefs.iter().fold(0u, |sum, coefs| sum + size_of::<~[R]>() + coefs.len() * size_of::<R>())
+  }
+
+  /// Evaluate component r of this weak gradient at the given interior-relative point, for the component monomials
+  /// obtained from WeakGradSolver::wgrad_comp_mons.
+  #[inline]
+  pub fn comp_value_at<Mon:Monomial>(&self, comp_mons: &[Mon], r: Dim, x: &[R]) -> R {
+    let coefs = &self.comp_mon_coefs[*r];
+    range(0, coefs.len()).fold(0 as R, |sum, i| sum + coefs[i] * comp_mons[i].value_at(x))
+  }
+
+  /// Evaluate all components of this weak gradient at the given interior-relative point, for the component monomials
+  /// obtained from WeakGradSolver::wgrad_comp_mons.
+  pub fn eval<Mon:Monomial>(&self, comp_mons: &[Mon], x: &[R]) -> ~[R] {
+    vec::from_fn(self.comp_mon_coefs.len(), |r| self.comp_value_at(comp_mons, Dim(r), x))
+  }
+
+  /// Component r of this weak gradient as an interior-relative polynomial, borrowing the passed component monomials,
+  /// as obtained from WeakGradSolver::wgrad_comp_mons.
+  pub fn component_poly<'a,Mon:Monomial>(&'a self, comp_mons: &'a [Mon], r: Dim) -> PolyBorrowing<'a,Mon> {
+    PolyBorrowing::new(self.comp_mon_coefs[*r].as_slice(), comp_mons)
+  }
+
+  /// Compute the divergence of this weak gradient, the sum of the partial derivatives of its component polynomials.
+  /// Weak gradients do not hold their component monomials, which are shared by all weak gradients produced by the
+  /// same solver, so these must be passed, as obtained from WeakGradSolver::wgrad_comp_mons.
+  pub fn divergence<Mon:Monomial>(&self, comp_mons: &[Mon]) -> PolyOwning<Mon> {
+    let partials = vec::from_fn(self.comp_mon_coefs.len(), |r| {
+      PolyBorrowingMons::new(self.comp_mon_coefs[r].clone(), comp_mons).partial_deriv(Dim(r))
+    });
+    let terms: ~[(R,&PolyOwning<Mon>)] = partials.iter().map(|p| (1 as R, p)).collect();
+    PolyOwning::from_polys_lcomb(terms)
+  }
+}
+
 pub struct WeakGradSolver<Mon> {
 
   wgrad_comp_mons_deg_lim: DegLim,
@@ -95,13 +136,13 @@ impl <Mon:Monomial> WeakGradSolver<Mon> {
   *   WGRAD_DEF_RHS:    -(v_0, div q)_T + <v_b, q.n>_bnd(T),
   * on a reference finite element of the mesh for an interior or side supported monomial v.
   */
-  fn wgrad_def_rhs_for_int_mon<MESHT:Mesh<Mon>>(&self, v: Mon, oshape: OShape, q: &VectorMonomial<Mon>, mesh: &MESHT) -> R {
+  pub fn wgrad_def_rhs_for_int_mon<MESHT:Mesh<Mon>>(&self, v: Mon, oshape: OShape, q: &VectorMonomial<Mon>, mesh: &MESHT) -> R {
     // Interior supported v: only the -(v_0, div q)_T term can be non-zero in the rhs of (WGRAD_DEF).
     let (div_q_coef, div_q_mon) = q.divergence_coef_and_mon();
     -div_q_coef * mesh.intg_facerel_mon_on_oshape_int(v * div_q_mon, oshape)
   }
   
-  fn wgrad_def_rhs_for_side_mon<MESHT:Mesh<Mon>>(&self, v: Mon, oshape: OShape, side_face: SideFace,
+  pub fn wgrad_def_rhs_for_side_mon<MESHT:Mesh<Mon>>(&self, v: Mon, oshape: OShape, side_face: SideFace,
                                                         q: &VectorMonomial<Mon>, mesh: &MESHT) -> R {
     // Side supported v: only the <v_b, q.n>_bnd(T) term can be non-zero in the rhs of (WGRAD_DEF).
     mesh.intg_siderel_mon_x_intrel_vmon_dot_normal_on_oshape_side(v, q, oshape, side_face)
@@ -146,7 +187,60 @@ impl <Mon:Monomial> WeakGradSolver<Mon> {
 
     (int_wgrads, side_wgrads_by_side)
   }
- 
+
+ /*
+  * Weak divergences are defined analogously to weak gradients: for a vector function w on a finite element T, the
+  * weak divergence of degree r of w on T is the polynomial wdiv(w) in P_r(T), such that:
+  *
+  * [WDIV_DEF]
+  *   (wdiv(w), phi)_T = -(w_0, grad phi)_T + <w_b.n, phi>_bnd(T), for all phi in P_r(T)
+  *
+  * where n is the outward normal, regardless of the normal convention of the mesh for normal trace integrals.
+  * This function computes weak divergences for vector monomials whose side parts are their traces, with the
+  * weak gradient component monomials of this solver as the basis for P_r(T). The weak divergences are returned
+  * in the order of the passed vector monomials, as interior-relative polynomials over the component monomials.
+  */
+  pub fn weak_div_on_oshape<MESHT:Mesh<Mon>>(&self, vmons: &[VectorMonomial<Mon>], oshape: OShape, mesh: &MESHT) -> ~[PolyOwning<Mon>] {
+    let comp_mons = self.wgrad_comp_mons.as_slice();
+    let num_comp_mons = comp_mons.len();
+    let num_side_faces = mesh.num_side_faces_for_oshape(oshape);
+    let one: Mon = Monomial::one();
+    let normals = vec::from_fn(num_side_faces, |sf| mesh.outward_normal_for_oshape_side(oshape, SideFace(sf)));
+
+    let sols = unsafe {
+      let ips = DenseMatrix::upper_triangle_from_fn(num_comp_mons, |i,j| {
+        mesh.intg_facerel_mon_on_oshape_int(comp_mons[i] * comp_mons[j], oshape)
+      });
+      // WDIV_DEF_RHS:  -(w_0, grad phi)_T + <w_b.n, phi>_bnd(T), for phi the comp monomial of the row.
+      let rhss = DenseMatrix::from_fn(num_comp_mons, vmons.len(), |i, c| {
+        let ref q = vmons[c];
+        let phi = comp_mons[i].clone();
+        let int_term = match phi.exp(q.mon_dim()) {
+          Deg(0) => 0 as R,
+          Deg(e) => {
+            let dphi_mon = phi.map_exp(q.mon_dim(), |e| Deg(*e-1));
+            -(e as R) * mesh.intg_facerel_mon_on_oshape_int(q.mon() * dphi_mon, oshape)
+          }
+        };
+        // The side term integrates the interior-relative product q phi, dotted with the outward normal, on each side.
+        // The outward normal is used directly, so the mesh's normal convention for normal traces does not apply.
+        let q_phi = q.mon() * phi;
+        range(0, num_side_faces).fold(int_term, |sum, sf| {
+          let n_q = normals[sf][*q.mon_dim()];
+          if n_q == 0 as R { sum }
+          else { sum + n_q * mesh.intg_intrel_mon_x_siderel_mon_on_oshape_side(q_phi.clone(), one.clone(), oshape, SideFace(sf)) }
+        })
+      });
+      let mut pivots = vec::from_elem(num_comp_mons, 0 as lapack_int);
+      la::solve_symmetric_as_col_maj_with_ut_sys(ips.mut_col_maj_data_ptr(), num_comp_mons as lapack_int,
+                                                 rhss.mut_col_maj_data_ptr(), vmons.len() as lapack_int,
+                                                 pivots.as_mut_ptr());
+      rhss
+    };
+
+    vec::from_fn(vmons.len(), |c| PolyOwning::new(sols.col(c), self.wgrad_comp_mons.clone()))
+  }
+
   // Pack WGRAD_DEF right hand side computations as column vectors in a combined rhs matrix suitable for an LAPACK solver.
   // Returns a matrix consisting of right hand side column vectors of the WGRAD_DEF system evaluated over the sequence of
   // basis vector monomials which are represented by the rows.  The first columns represent the interior supported
@@ -225,16 +319,26 @@ impl <Mon:Monomial> WeakGradSolver<Mon> {
   }
 
 
+  /// Get the monomial sequence for the components of the weak gradients produced by this solver.
+  #[inline]
+  pub fn wgrad_comp_mons<'a>(&'a self) -> &'a [Mon] {
+    self.wgrad_comp_mons.as_slice()
+  }
+
+  /// Get the vector monomials forming the basis of the weak gradient space, against which the WGRAD_DEF
+  /// equations are taken.
+  #[inline]
+  pub fn basis_vmons<'a>(&'a self) -> &'a [VectorMonomial<Mon>] {
+    self.basis_vmons.as_slice()
+  }
+
   pub fn new_weak_grad_ops(&self) -> WeakGradOps<Mon> {
     use std::hashmap::HashMap;
 
     let comp_mons = self.wgrad_comp_mons.clone();
 
     let prod_mons = { 
-      let prod_mons_deg_lim = match self.wgrad_comp_mons_deg_lim {
-        MaxMonDeg(l) => MaxMonDeg(2*l),
-        MaxMonFactorDeg(l) => MaxMonFactorDeg(2*l) 
-      };
+      let prod_mons_deg_lim = self.wgrad_comp_mons_deg_lim.product_with(&self.wgrad_comp_mons_deg_lim);
       Monomial::mons_with_deg_lim_asc(prod_mons_deg_lim)
     };
       
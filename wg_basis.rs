@@ -1,11 +1,18 @@
 use common::*;
-use monomial::{Monomial, DegLim, MaxMonDeg, MaxMonFactorDeg, domain_space_dims};
-use polynomial::{PolyBorrowing};
-use mesh::{Mesh, FENum, NBSideNum, NBSideInclusions, OShape, SideFace};
+use monomial::{Monomial, DegLim, MaxMonDeg, MaxMonFactorDeg, domain_space_dims, num_mons_with_deg_lim};
+use polynomial::{Polynomial, PolyBorrowing};
+use mesh::{Mesh, FENum, NBSideNum, NBSideInclusions, OShape, SideFace, Interior};
 use weak_gradient::{WeakGradSolver, WeakGrad, WeakGradOps};
+use vector_monomial::VectorMonomial;
 use dense_matrix::DenseMatrix;
+use la;
+use la::lapack_int;
+use sparse_matrix::{SparseMatrix, Symmetric, StructurallySymmetric, General};
 
 use std::vec;
+use std::num::{sqrt, abs};
+use std::mem::size_of;
+use std::rand::{Rng, SeedableRng, IsaacRng};
 
 /* Overview
  * --------
@@ -78,6 +85,20 @@ pub struct BasisElNum(uint);
 #[deriving(Eq,TotalEq,Ord,TotalOrd,Clone)]
 pub struct FaceMonNum(uint);
 
+/// How a side degree limit admitting monomials beyond those of the interior degree limit is handled when
+/// constructing a basis, see WGBasis::new_with_excess_side_deg_handling. Such side monomials cannot be
+/// represented by the weak gradients and make the basis inconsistent.
+pub enum ExcessSideDegHandling {
+  WarnOnExcessSideDeg,
+  FailOnExcessSideDeg,
+}
+
+/// The form of an assembled mass matrix, see WGBasis::assemble_int_mass_with.
+pub enum MassForm {
+  Consistent,
+  Lumped,
+}
+
 
 // A type representing a basis for Weak Galerkin approximating polynomials on an arbitrary mesh.
 pub struct WGBasis<Mon,Mesh> {
@@ -119,21 +140,39 @@ pub struct WGBasis<Mon,Mesh> {
   // Only the upper triangle part of each matrix should be used, the contents of the lower parts are undefined.
   ips_int_mons_by_oshape: ~[DenseMatrix],          // by fe oriented shape, then (int mon num, int mon num)
   ips_side_mons_by_oshape_side: ~[~[DenseMatrix]], // by fe oriented shape, then side face, then (side mon #, side mon #)
+
+  // Pre-calculated integrals over the sides of reference oriented shapes of the products of interior monomial traces
+  // and side monomials, as full matrices.
+  int_side_trace_products_by_oshape_side: ~[~[DenseMatrix]], // by fe oriented shape, then side face, then (int mon #, side mon #)
 }
 
 
 impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
 
+  /// Construct a basis for the mesh with the given interior and side degree limits. A side degree limit admitting
+  /// monomials beyond those of the interior degree limit is reported as a warning, see excess_side_deg_lim_report.
   pub fn new(mesh: ~MeshT, int_polys_deg_lim: DegLim, side_polys_deg_lim: DegLim) -> WGBasis<Mon,MeshT> {
+    WGBasis::new_with_excess_side_deg_handling(mesh, int_polys_deg_lim, side_polys_deg_lim, WarnOnExcessSideDeg)
+  }
+
+  /// Construct a basis as in new, handling a side degree limit which admits monomials beyond those of the interior
+  /// degree limit as indicated, either by logging a warning or by failing.
+  pub fn new_with_excess_side_deg_handling(mesh: ~MeshT,
+                                           int_polys_deg_lim: DegLim,
+                                           side_polys_deg_lim: DegLim,
+                                           excess_side_deg_handling: ExcessSideDegHandling) -> WGBasis<Mon,MeshT> {
+
+    match excess_side_deg_lim_report::<Mon>(int_polys_deg_lim, side_polys_deg_lim) {
+      Some(msg) => match excess_side_deg_handling {
+        WarnOnExcessSideDeg => warn!("{}", msg),
+        FailOnExcessSideDeg => fail!(msg)
+      },
+      None => {}
+    }
     
     let int_mons = Monomial::mons_with_deg_lim_asc(int_polys_deg_lim);
     
-    let side_mons_by_dep_dim: ~[~[Mon]] = { 
-      let mons_for_deg_lim: ~[Mon] = Monomial::mons_with_deg_lim_asc(side_polys_deg_lim);
-      vec::from_fn(domain_space_dims::<Mon>(), |r|
-        mons_for_deg_lim.iter().filter(|mon| mon.exp(Dim(r)) == Deg(0)).map(|m|m.clone()).collect()
-      )
-    };
+    let side_mons_by_dep_dim = side_mons_by_dep_dim_for_deg_lim::<Mon>(side_polys_deg_lim);
     
     let mons_per_fe_int = int_mons.len();
     let mons_per_fe_side = side_mons_by_dep_dim[0].len();
@@ -142,10 +181,7 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
     let total_els = num_int_els + mesh.num_nb_sides() * mons_per_fe_side;
     let first_nb_side_beln = BasisElNum(num_int_els);
 
-    let mut wgrad_solver = {
-      let k = match int_polys_deg_lim { MaxMonDeg(k) | MaxMonFactorDeg(k) => k };
-      WeakGradSolver::new(MaxMonDeg(k-1), mesh)
-    };
+    let mut wgrad_solver = WeakGradSolver::new(wgrad_comp_mons_deg_lim::<Mon>(int_polys_deg_lim), mesh);
 
     let (int_mon_wgrads, side_mon_wgrads) = compute_wgrads(&mut wgrad_solver, int_mons, side_mons_by_dep_dim, mesh);
     
@@ -167,6 +203,16 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
       })
     });
 
+    let int_side_trace_products_by_oshape_side = vec::from_fn(mesh.num_oriented_element_shapes(), |os| {
+      vec::from_fn(mesh.num_side_faces_for_oshape(OShape(os)), |sf| {
+        let side_dep_dim = mesh.dependent_dim_for_oshape_side(OShape(os), SideFace(sf));
+        let side_mons = side_mons_by_dep_dim[*side_dep_dim].as_slice();
+        DenseMatrix::from_fn(int_mons.len(), side_mons.len(), |i,j| {
+          mesh.intg_intrel_mon_x_siderel_mon_on_oshape_side(int_mons[i].clone(), side_mons[j].clone(), OShape(os), SideFace(sf))
+        })
+      })
+    });
+
     WGBasis {
       mesh: mesh,
       int_polys_deg_lim: int_polys_deg_lim,
@@ -183,6 +229,7 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
       side_mon_wgrads: side_mon_wgrads,
       ips_int_mons_by_oshape: ips_int_mons_by_oshape,
       ips_side_mons_by_oshape_side: ips_side_mons_by_oshape_side,
+      int_side_trace_products_by_oshape_side: int_side_trace_products_by_oshape_side,
     }
   }
   
@@ -198,6 +245,12 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
     self.total_els
   }
 
+  /// Get the number of interior supported elements in this basis, which are numbered before all side supported elements.
+  #[inline]
+  pub fn num_int_els(&self) -> uint {
+    self.num_int_els
+  }
+
   /** Estimate the number of interacting basis element pairs. Provides an upper bound of the number of
    ordered pairs (el1, el2) where el1 and el2 are basis elements for which there exists a common supporting
    finite element. If non_decreasing_pairs_only is true, then exclude from the count the pairs where the
@@ -338,18 +391,66 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
   }
 
   /// Get the basis element number for the given finite element side face and face monomial number.
+  /// Boundary sides support no basis elements, so the side must not be a boundary side, for which the result is
+  /// undefined. Callers iterating over finite element sides should skip sides for which the mesh's is_boundary_side
+  /// is true, and callers with sides not known to be non-boundary should use fe_side_mon_el_num_opt.
   #[inline]
   pub fn fe_side_mon_el_num(&self, fe: FENum, side_face: SideFace, monn: FaceMonNum) -> BasisElNum {
     let nbsn = self.mesh.nb_side_num_for_fe_side(fe, side_face);
     self.nb_side_mon_el_num(nbsn, monn)
   }
 
+  /// Get the basis element number for the given finite element side face and face monomial number as in
+  /// fe_side_mon_el_num, or None if the side is a boundary side, which supports no basis elements.
+  pub fn fe_side_mon_el_num_opt(&self, fe: FENum, side_face: SideFace, monn: FaceMonNum) -> Option<BasisElNum> {
+    if self.mesh.is_boundary_side(fe, side_face) { None }
+    else { Some(self.fe_side_mon_el_num(fe, side_face, monn)) }
+  }
+
   /// Get the basis element number for the given finite element non-boundary side and face monomial number.
   #[inline(always)]
   pub fn nb_side_mon_el_num(&self, nbsn: NBSideNum, monn: FaceMonNum) -> BasisElNum {
     BasisElNum(*self.first_nb_side_beln + (*nbsn * self.mons_per_fe_side) + *monn)
   }
 
+  /// Get the basis element numbers supported on the given finite element, as half-open ranges (first, one past last)
+  /// of contiguous basis element numbers: first the range for the element's interior, then a range for each of its
+  /// non-boundary sides, together with the side face. Boundary sides support no basis elements and are omitted.
+  pub fn element_dof_ranges(&self, fe: FENum) -> ((BasisElNum, BasisElNum), ~[(SideFace, (BasisElNum, BasisElNum))]) {
+    let int_range = (self.int_mon_el_num(fe, FaceMonNum(0)),
+                     BasisElNum(*self.int_mon_el_num(fe, FaceMonNum(0)) + self.mons_per_fe_int));
+    let num_side_faces = self.mesh.num_side_faces_for_oshape(self.mesh.oriented_shape_for_fe(fe));
+    let side_ranges = range(0, num_side_faces).filter_map(|sf| {
+      let sf = SideFace(sf);
+      if self.mesh.is_boundary_side(fe, sf) { None }
+      else {
+        let first = self.fe_side_mon_el_num(fe, sf, FaceMonNum(0));
+        Some((sf, (first, BasisElNum(*first + self.mons_per_fe_side))))
+      }
+    }).collect();
+    (int_range, side_ranges)
+  }
+
+  /// Build the incidence matrix of finite elements and basis elements, having a row for each finite element and a
+  /// column for each basis element, with entry (fe, i) equal to 1 if basis element i is supported on finite element
+  /// fe and not stored otherwise. Interior supported basis element columns thus have a single entry, and side
+  /// supported columns two entries, for the two finite elements including the supporting side. The matrix is General.
+  pub fn element_dof_incidence(&self) -> SparseMatrix {
+    let mut triplets = vec::with_capacity(self.num_int_els + 2 * (self.total_els - self.num_int_els));
+    for fe in range(0, self.mesh.num_fes()) {
+      let ((int_first, int_end), side_ranges) = self.element_dof_ranges(FENum(fe));
+      for i in range(*int_first, *int_end) {
+        triplets.push((fe, i, 1 as R));
+      }
+      for &(_, (side_first, side_end)) in side_ranges.iter() {
+        for i in range(*side_first, *side_end) {
+          triplets.push((fe, i, 1 as R));
+        }
+      }
+    }
+    SparseMatrix::from_triplets(self.mesh.num_fes(), triplets, General)
+  }
+
 
   /// Get the polynomial representing the passed full WG solution restricted to a particular finite element interior.
   #[inline]
@@ -368,6 +469,481 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
     PolyBorrowing::new(fe_side_coefs, fe_side_mons)
   }
 
+  /** Integrate the passed WG solution over the outside boundary of the mesh. Since boundary sides do not support
+   *  basis elements, the traces of the interior polynomials of the finite elements including the boundary sides
+   *  are integrated.
+   */
+  pub fn integrate_solution_boundary(&self, sol_basis_coefs: &[R]) -> R {
+    let b_fes_by_oshape_side = self.mesh.boundary_fes_by_oshape_side();
+    let mut sum = 0 as R;
+    for (os, oshape_b_fes_by_side) in b_fes_by_oshape_side.iter().enumerate() {
+      for (sf, b_fes) in oshape_b_fes_by_side.iter().enumerate() {
+        // Integrals of the interior monomial traces over the side, which are the same for all fes of this oriented shape,
+        // being their products with the constant side monomial which is first in the side monomial sequence.
+        let trace_products = self.int_side_trace_products(OShape(os), SideFace(sf));
+        let int_mon_side_intgs = vec::from_fn(self.mons_per_fe_int, |i| trace_products.get(i,0));
+        for &fe in b_fes.iter() {
+          let fe_first_int_beln = self.int_mon_el_num(fe, FaceMonNum(0));
+          let fe_int_coefs = sol_basis_coefs.slice(*fe_first_int_beln, *fe_first_int_beln + self.mons_per_fe_int);
+          sum += fe_int_coefs.iter().zip(int_mon_side_intgs.iter()).fold(0 as R, |sum, (&coef, &intg)| sum + coef * intg);
+        }
+      }
+    }
+    sum
+  }
+
+  /// Get the basis coefficients representing the function which has the given constant value on every finite element
+  /// interior and non-boundary side. The constant monomial is the first in both the interior and side monomial sequences.
+  pub fn constant_solution_coefs(&self, value: R) -> ~[R] {
+    vec::from_fn(self.total_els, |i| {
+      let beln = BasisElNum(i);
+      let monn = if self.is_int_supported(beln) { self.int_rel_mon_num(beln) } else { self.side_rel_mon_num(beln) };
+      if monn == FaceMonNum(0) { value } else { 0 as R }
+    })
+  }
+
+  /// Compute the contribution of each finite element to the linear functional of the solution given by the integral
+  /// of weight times u_h over the mesh, where u_h is the solution's interior polynomial on each element. The weight
+  /// function is evaluated at global points. The contributions are indexed by finite element number, and sum to the
+  /// value of the functional.
+  pub fn element_functional_contributions(&self, sol_basis_coefs: &[R], weight: |&[R]| -> R) -> ~[R] {
+    vec::from_fn(self.mesh.num_fes(), |fe| { let fe = FENum(fe);
+      let int_poly = self.fe_int_poly(fe, sol_basis_coefs);
+      self.mesh.intg_mixed_global_and_facerel_fn_on_fe_int(|x, x_int_rel| {
+        weight(x) * int_poly.value_at(x_int_rel)
+      }, fe)
+    })
+  }
+
+  /// Compute the L2 norm over the mesh of the difference of the functions represented by two solution coefficient
+  /// vectors, whose values on finite element interiors are their interior polynomials. The norm is computed exactly
+  /// from the interior monomial inner products on each element.
+  pub fn solution_difference_l2(&self, sol_basis_coefs_a: &[R], sol_basis_coefs_b: &[R]) -> R {
+    assert!(sol_basis_coefs_a.len() == self.total_els && sol_basis_coefs_b.len() == self.total_els);
+    let n = self.mons_per_fe_int;
+    let mut diff = vec::from_elem(n, 0 as R);
+    let mut sum_sq = 0 as R;
+    for fe in range(0, self.mesh.num_fes()) { let fe = FENum(fe);
+      let ips = &self.ips_int_mons_by_oshape[*self.mesh.oriented_shape_for_fe(fe)];
+      let fe_first_int_beln = *self.int_mon_el_num(fe, FaceMonNum(0));
+      for i in range(0, n) {
+        diff[i] = sol_basis_coefs_a[fe_first_int_beln + i] - sol_basis_coefs_b[fe_first_int_beln + i];
+      }
+      for i in range(0, n) {
+        sum_sq += diff[i] * diff[i] * ips.get(i,i);
+        for j in range(i+1, n) {
+          sum_sq += 2. * diff[i] * diff[j] * ips.get(i,j);
+        }
+      }
+    }
+    sqrt(sum_sq)
+  }
+
+  /** Check whether the side monomials of this basis are consistent with the weak gradient component space, whose
+   *  traces on a side with dependent dimension r are spanned by the weak gradient component monomials which are
+   *  constant in coordinate r. If any side dependent dimension has fewer or more side monomials than these, which
+   *  usually results from mismatched interior and side degree limits, a description of the mismatch is logged as a
+   *  warning and returned.
+   */
+  pub fn report_side_basis_gaps(&self) -> Option<~str> {
+    let comparisons = side_mons_vs_traces(self.side_mons_by_dep_dim.as_slice(), self.weak_grad_solver.wgrad_comp_mons());
+    for (r, &(num_side_mons, _, num_wgrad_side_trace_mons)) in comparisons.iter().enumerate() {
+      if num_side_mons != num_wgrad_side_trace_mons {
+        let msg = format!("Side basis for dependent dimension {} has {} monomials, but the weak gradient component \
+                           traces require {}: the side degree limit is likely too {} for the interior degree limit.",
+                          r, num_side_mons, num_wgrad_side_trace_mons,
+                          if num_side_mons < num_wgrad_side_trace_mons { "low" } else { "high" });
+        warn!("{}", msg);
+        return Some(msg);
+      }
+    }
+    None
+  }
+
+  /** Check that the interior and side degree limits of this basis form a known-stable Weak Galerkin combination:
+   *  limits of the same kind, with interior degree k >= 1 and side degree k or k-1, the weak gradient components
+   *  having the degree limit given by wgrad_comp_mons_deg_lim. Otherwise an explanation of the problem is logged as a warning and returned as the error.
+   */
+  pub fn check_degree_consistency(&self) -> Result<(), ~str> {
+    let res = match (self.int_polys_deg_lim, self.side_polys_deg_lim) {
+      (MaxMonDeg(k), MaxMonDeg(s)) | (MaxMonFactorDeg(k), MaxMonFactorDeg(s)) => {
+        if k == 0 {
+          Err(~"Interior degree limit must be at least 1, since weak gradient components have degree one less.")
+        }
+        else if s != k && s != k-1 {
+          Err(format!("Side degree limit {} is not stable for interior degree limit {}: the side degree limit \
+                       should be {} or {}.", s, k, k, k-1))
+        }
+        else { Ok(()) }
+      }
+      _ => Err(~"Interior and side degree limits should be of the same kind, both total degree or both factor degree limits.")
+    };
+    match res {
+      Err(ref msg) => { warn!("{}", *msg); }
+      Ok(_) => {}
+    }
+    res
+  }
+
+  /** Check, as a test support facility, that the precomputed weak gradients of the shape functions on the given
+   *  oriented shape combine linearly. For random pairs u, v of interior or side supported shape functions and random
+   *  coefficients a, b, drawn from a generator seeded with the given seed so that failures can be reproduced, the combination a wgrad(u) + b wgrad(v) must satisfy the defining equations of the weak
+   *  gradient of a u + b v,
+   *    (a wgrad(u) + b wgrad(v), q)_T = -(a u_0 + b v_0, div q)_T + <a u_b + b v_b, q.n>_bnd(T),
+   *  for each vector monomial q of the weak gradient space, to within the given tolerance. No new weak gradients
+   *  need to be solved for, the equations being checked directly.
+   */
+  pub fn check_wgrad_linearity(&self, oshape: OShape, seed: u32, tol: R) -> bool {
+    let mesh = &*self.mesh;
+    let solver = &self.weak_grad_solver;
+    let comp_mons = solver.wgrad_comp_mons();
+
+    let shape_fns = self.oshape_shape_fns(oshape);
+
+    let wgrad_def_rhs = |shape_fn: (Option<SideFace>, FaceMonNum), q: &VectorMonomial<Mon>| -> R {
+      match shape_fn {
+        (None, monn) => solver.wgrad_def_rhs_for_int_mon(self.int_mons[*monn].clone(), oshape, q, mesh),
+        (Some(sf), monn) => {
+          let side_mon = self.side_mons_for_oshape_side(oshape, sf)[*monn].clone();
+          solver.wgrad_def_rhs_for_side_mon(side_mon, oshape, sf, q, mesh)
+        }
+      }
+    };
+
+    let seed = [seed];
+    let mut rng: IsaacRng = SeedableRng::from_seed(seed.as_slice());
+    for _ in range(0, shape_fns.len()) {
+      let (u, v) = (shape_fns[rng.gen_range(0u, shape_fns.len())], shape_fns[rng.gen_range(0u, shape_fns.len())]);
+      let (a, b): (R, R) = (rng.gen::<R>() * 2. - 1., rng.gen::<R>() * 2. - 1.);
+      let (wgrad_u, wgrad_v) = (self.shape_fn_wgrad(u, oshape), self.shape_fn_wgrad(v, oshape));
+      for q in solver.basis_vmons().iter() {
+        let d = *q.mon_dim();
+        let lhs = range(0, comp_mons.len()).fold(0 as R, |sum, i| {
+          let coef = a * wgrad_u.comp_mon_coefs[d][i] + b * wgrad_v.comp_mon_coefs[d][i];
+          sum + coef * mesh.intg_facerel_mon_on_oshape_int(comp_mons[i] * q.mon(), oshape)
+        });
+        let rhs = a * wgrad_def_rhs(u, q) + b * wgrad_def_rhs(v, q);
+        if abs(lhs - rhs) > tol { return false; }
+      }
+    }
+    true
+  }
+
+  // Get the shape functions on the oshape, as the side face for side supported functions (None for interior supported
+  // functions) and the monomial number, with interior supported functions first followed by those of each side in
+  // side face order.
+  fn oshape_shape_fns(&self, oshape: OShape) -> ~[(Option<SideFace>, FaceMonNum)] {
+    let mut shape_fns: ~[(Option<SideFace>, FaceMonNum)] = vec::from_fn(self.mons_per_fe_int, |monn| (None, FaceMonNum(monn)));
+    for sf in range(0, self.mesh.num_side_faces_for_oshape(oshape)) {
+      for monn in range(0, self.mons_per_fe_side) {
+        shape_fns.push((Some(SideFace(sf)), FaceMonNum(monn)));
+      }
+    }
+    shape_fns
+  }
+
+  // Get the basis element number for a shape function on a finite element, or None if it is supported on a boundary
+  // side of the finite element, on which no basis elements are supported.
+  fn fe_shape_fn_el_num(&self, fe: FENum, shape_fn: (Option<SideFace>, FaceMonNum)) -> Option<BasisElNum> {
+    match shape_fn {
+      (None, monn) => Some(self.int_mon_el_num(fe, monn)),
+      (Some(sf), monn) => self.fe_side_mon_el_num_opt(fe, sf, monn)
+    }
+  }
+
+  // Get the weak gradient of an interior supported shape function (side face None) or side supported shape function.
+  fn shape_fn_wgrad<'a>(&'a self, shape_fn: (Option<SideFace>, FaceMonNum), oshape: OShape) -> &'a WeakGrad {
+    match shape_fn {
+      (None, monn) => self.int_mon_wgrad(monn, oshape),
+      (Some(sf), monn) => self.side_mon_wgrad(monn, oshape, sf)
+    }
+  }
+
+  /** Check that the solution satisfies the discrete counterpart of the conservation law for -Δu = f, that the total
+   *  source, the integral of f over the domain, balances the net inflow of the flux through the outside boundary,
+   *  -∮ ∂u/∂n, to within the given tolerance. The flux through each boundary side is computed from the weak gradient
+   *  of the solution on the side's finite element, dotted with the side's outward normal. Since boundary sides support no basis elements, the solution's
+   *  boundary values are taken to be zero, so this is intended for solutions with homogeneous Dirichlet boundary
+   *  conditions. The balance holds only up to the stabilization on boundary sides, which vanishes as the interior
+   *  traces of the solution approach its zero boundary values under refinement.
+   */
+  pub fn check_discrete_conservation(&self, sol_coefs: &[R], f: |&[R]| -> R, tol: R) -> bool {
+    let mesh = &*self.mesh;
+    let comp_mons = self.weak_grad_solver.wgrad_comp_mons();
+    let space_dims = domain_space_dims::<Mon>();
+    let one: Mon = Monomial::one();
+    let mut total_source = 0 as R;
+    let mut net_outflow = 0 as R;
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      total_source += mesh.intg_global_fn_on_fe_int(|x| f(x), fe);
+
+      let oshape = mesh.oriented_shape_for_fe(fe);
+      let b_sides: ~[SideFace] = range(0, mesh.num_side_faces_for_oshape(oshape)).map(|sf| SideFace(sf))
+                                   .filter(|&sf| mesh.is_boundary_side(fe, sf)).collect();
+      if b_sides.len() == 0 { continue; }
+
+      // The weak gradient of the solution on the fe, as the solution's combination of its shape function weak gradients.
+      let mut wgrad_coefs = vec::from_elem(space_dims, vec::from_elem(comp_mons.len(), 0 as R));
+      for &shape_fn in self.oshape_shape_fns(oshape).iter() {
+        match self.fe_shape_fn_el_num(fe, shape_fn) {
+          Some(el) => {
+            let (coef, wgrad) = (sol_coefs[*el], self.shape_fn_wgrad(shape_fn, oshape));
+            for d in range(0, space_dims) {
+              for i in range(0, comp_mons.len()) {
+                wgrad_coefs[d][i] += coef * wgrad.comp_mon_coefs[d][i];
+              }
+            }
+          }
+          None => {}
+        }
+      }
+
+      // The flux is through the outward normal regardless of the mesh's normal convention for normal traces.
+      for &sf in b_sides.iter() {
+        let n = mesh.outward_normal_for_oshape_side(oshape, sf);
+        for d in range(0, space_dims) {
+          if n[d] == 0 as R { continue; }
+          for i in range(0, comp_mons.len()) {
+            net_outflow += wgrad_coefs[d][i] * n[d] * mesh.intg_intrel_mon_x_siderel_mon_on_oshape_side(comp_mons[i].clone(), one.clone(), oshape, sf);
+          }
+        }
+      }
+    }
+    abs(total_source + net_outflow) <= tol
+  }
+
+  /** Check that the passed system matrix, assembled for a diffusion-type operator over this basis, annihilates the
+   *  constant function, to within the given tolerance in the Euclidean norm. Because boundary sides support no basis
+   *  elements, the basis coefficients of a constant function represent a function which is zero on the outside
+   *  boundary, so only the rows for basis elements whose supporting finite elements have no boundary sides are
+   *  checked. This is intended as a test of stiffness matrix assembly.
+   */
+  pub fn check_constant_nullspace(&self, sys: &SparseMatrix, tol: R) -> bool {
+    let prod = sys.mat_vec(self.constant_solution_coefs(1 as R));
+    let sq_norm = range(0, self.total_els).fold(0 as R, |sum, i| {
+      if self.supp_fes_have_no_boundary_sides(BasisElNum(i)) { sum + sq(prod[i]) } else { sum }
+    });
+    sqrt(sq_norm) <= tol
+  }
+
+  fn supp_fes_have_no_boundary_sides(&self, i: BasisElNum) -> bool {
+    let has_no_b_sides = |fe: FENum| {
+      self.mesh.num_nb_sides_for_fe(fe) == self.mesh.num_side_faces_for_oshape(self.mesh.oriented_shape_for_fe(fe))
+    };
+    if self.is_int_supported(i) {
+      has_no_b_sides(self.support_int_fe_num(i))
+    }
+    else {
+      let incls = self.fe_inclusions_of_side_support(i);
+      has_no_b_sides(incls.fe1) && has_no_b_sides(incls.fe2)
+    }
+  }
+
+
+  /** Assemble the lumped (row-summed) mass matrix for the interior supported basis elements, returning its diagonal.
+   *  The diagonal entry for an interior supported basis element is the sum of the entries in its row of the
+   *  consistent interior mass matrix, whose entries are the L2 inner products of the element with the other basis
+   *  elements supported on the same interior. The total mass of the consistent matrix is thus preserved.
+   */
+  pub fn assemble_lumped_int_mass(&self) -> ~[R] {
+    let n = self.mons_per_fe_int;
+    // Row sums of the interior inner products by oriented shape, using only the upper triangles of the matrices.
+    let row_sums_by_oshape = self.ips_int_mons_by_oshape.map(|ips| {
+      vec::from_fn(n, |i| {
+        range(0, n).fold(0 as R, |sum, j| sum + if i <= j { ips.get(i,j) } else { ips.get(j,i) })
+      })
+    });
+    vec::from_fn(self.num_int_els, |i| {
+      let beln = BasisElNum(i);
+      let oshape = self.mesh.oriented_shape_for_fe(self.support_int_fe_num(beln));
+      row_sums_by_oshape[*oshape][*self.int_rel_mon_num(beln)]
+    })
+  }
+
+  /** Assemble the mass matrix for the interior supported basis elements in the given form, as a Symmetric matrix
+   *  with the interior supported elements' numbers as row and column numbers. The Consistent form has entries
+   *  which are the L2 inner products of elements supported on the same interior, so it is block diagonal by finite
+   *  element. The Lumped form is the diagonal matrix of the consistent form's row sums, see assemble_lumped_int_mass,
+   *  which preserves the total mass (the sum of all entries).
+   */
+  pub fn assemble_int_mass_with(&self, form: MassForm) -> SparseMatrix {
+    let n = self.mons_per_fe_int;
+    match form {
+      Consistent => {
+        let mut m = SparseMatrix::new_with_capacities(self.mesh.num_fes() * (n * (n+1) / 2), self.num_int_els, Symmetric);
+        for fe in range(0, self.mesh.num_fes()) { let fe = FENum(fe);
+          let ips = &self.ips_int_mons_by_oshape[*self.mesh.oriented_shape_for_fe(fe)];
+          for i in range(0, n) {
+            let r = *self.int_mon_el_num(fe, FaceMonNum(i));
+            for j in range(i, n) {
+              m.push(r, *self.int_mon_el_num(fe, FaceMonNum(j)), ips.get(i,j));
+            }
+          }
+        }
+        m
+      }
+      Lumped => {
+        let diag = self.assemble_lumped_int_mass();
+        let mut m = SparseMatrix::new_with_capacities(self.num_int_els, self.num_int_els, Symmetric);
+        for (i, &d) in diag.iter().enumerate() {
+          m.push(i, i, d);
+        }
+        m
+      }
+    }
+  }
+
+  /// Assemble the convection matrix for the velocity field b, with entry (i,j) the integral over finite element
+  /// interiors of (b·wgrad(b_j)) times the interior part of basis element b_i, so that rows are indexed by test
+  /// and columns by trial basis elements. Only interior supported basis elements have non-zero interior parts,
+  /// so the rows of side supported elements hold only the zero mirror entries of the structurally symmetric
+  /// result, which in general is not symmetric.
+  pub fn assemble_convection(&self, b: |&[R]| -> ~[R]) -> SparseMatrix {
+    let mesh = &*self.mesh;
+    let wgrad_comp_mons = self.weak_grad_solver.wgrad_comp_mons();
+    let mut m = SparseMatrix::new_with_capacities(self.est_num_el_el_pairs_with_common_supp_fes(false),
+                                                  self.total_els, StructurallySymmetric);
+    let mut fe_nb_sides = vec::with_capacity(mesh.max_num_shape_sides());
+
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let oshape = mesh.oriented_shape_for_fe(fe);
+
+      // The fe's non-boundary sides in increasing side number order, so their basis elements are also in order.
+      fe_nb_sides.clear();
+      for sf in range(0, mesh.num_side_faces_for_oshape(oshape)) { let sf = SideFace(sf);
+        if !mesh.is_boundary_side(fe, sf) {
+          fe_nb_sides.push((mesh.nb_side_num_for_fe_side(fe, sf), sf));
+        }
+      }
+      fe_nb_sides.sort();
+
+      for test_monn in range(0, self.mons_per_fe_int) { let test_monn = FaceMonNum(test_monn);
+        let r = *self.int_mon_el_num(fe, test_monn);
+        let test_mon = self.int_mons[*test_monn].clone();
+
+        let convection = |wgrad: &WeakGrad| {
+          mesh.intg_mixed_global_and_facerel_fn_on_fe_int(|x, x_int_rel| {
+            let b_x = b(x);
+            let b_dot_wgrad = range(0, b_x.len()).fold(0 as R, |sum, d| {
+              sum + b_x[d] * wgrad.comp_value_at(wgrad_comp_mons, Dim(d), x_int_rel)
+            });
+            b_dot_wgrad * test_mon.value_at(x_int_rel)
+          }, fe)
+        };
+
+        for trial_monn in range(0, self.mons_per_fe_int) { let trial_monn = FaceMonNum(trial_monn);
+          let c = *self.int_mon_el_num(fe, trial_monn);
+          m.push(r, c, convection(self.int_mon_wgrad(trial_monn, oshape)));
+        }
+        for &(nbs, sf) in fe_nb_sides.iter() {
+          for trial_monn in range(0, self.mons_per_fe_side) { let trial_monn = FaceMonNum(trial_monn);
+            let c = *self.nb_side_mon_el_num(nbs, trial_monn);
+            m.push(r, c, convection(self.side_mon_wgrad(trial_monn, oshape, sf)));
+          }
+        }
+      }
+    }
+
+    m.finish_struct_sym();
+    m
+  }
+
+  /// Compute the element Peclet number |b| h / (2 diffusivity) for each finite element, by finite element number,
+  /// to identify where an advection-diffusion discretization with convection matrix from assemble_convection is
+  /// advection dominated, where the number exceeds 1. Here h is the element diameter and |b| is the average over the
+  /// element interior of the Euclidean norm of the velocity field b.
+  pub fn element_peclet_numbers(&self, b: |&[R]| -> ~[R], diffusivity: R) -> ~[R] {
+    if diffusivity <= 0 as R { fail!("Diffusivity must be positive for Peclet numbers."); }
+    let mesh = &*self.mesh;
+    vec::from_fn(mesh.num_fes(), |fe| { let fe = FENum(fe);
+      let oshape = mesh.oriented_shape_for_fe(fe);
+      let b_norm_intg = mesh.intg_global_fn_on_fe_int(|x| {
+        let b_x = b(x);
+        sqrt(b_x.iter().fold(0 as R, |sum, &b_r| sum + b_r * b_r))
+      }, fe);
+      let b_norm_avg = b_norm_intg / mesh.oshape_interior_measure(oshape);
+      b_norm_avg / (2. * diffusivity * mesh.shape_diameter_inv(oshape))
+    })
+  }
+
+  /** Assemble the matrix for the biharmonic (fourth order) problem, with entry (i,j) the sum over finite elements of
+   *  the integrals over their interiors of the products of the discrete Laplacians of basis elements b_i and b_j.
+   *  The discrete Laplacian of a basis element on a finite element is the divergence of its weak gradient there, see
+   *  WeakGrad::divergence. The result is Symmetric, with only the upper triangle stored. These integrals depend only
+   *  on the oriented shape and the shape functions involved, so they are computed once per oriented shape and then
+   *  distributed to the finite elements of the shape.
+   */
+  pub fn assemble_biharmonic(&self) -> SparseMatrix {
+    let mesh = &*self.mesh;
+    let comp_mons = self.weak_grad_solver.wgrad_comp_mons();
+    let num_oshapes = mesh.num_oriented_element_shapes();
+
+    let shape_fns_by_oshape = vec::from_fn(num_oshapes, |os| self.oshape_shape_fns(OShape(os)));
+
+    // Interior integrals of products of the shape functions' discrete Laplacians, in the upper triangles by oshape.
+    let wlap_ips_by_oshape = vec::from_fn(num_oshapes, |os| {
+      let oshape = OShape(os);
+      let shape_fns = &shape_fns_by_oshape[os];
+      let wlaps = shape_fns.map(|&shape_fn| self.shape_fn_wgrad(shape_fn, oshape).divergence(comp_mons));
+      DenseMatrix::upper_triangle_from_fn(shape_fns.len(), |i,j| {
+        wlaps[i].foldl_terms(0 as R, |sum, (coef, mon)| {
+          sum + coef * mesh.intg_facerel_mon_x_facerel_poly_on_oshape_face(mon, &wlaps[j], oshape, Interior)
+        })
+      })
+    });
+
+    let mut triplets = vec::with_capacity(self.est_num_el_el_pairs_with_common_supp_fes(true));
+    for fe in range(0, mesh.num_fes()) { let fe = FENum(fe);
+      let os = *mesh.oriented_shape_for_fe(fe);
+      let (shape_fns, ips) = (&shape_fns_by_oshape[os], &wlap_ips_by_oshape[os]);
+      for i in range(0, shape_fns.len()) {
+        match self.fe_shape_fn_el_num(fe, shape_fns[i]) {
+          None => {}
+          Some(el_i) => for j in range(i, shape_fns.len()) {
+            match self.fe_shape_fn_el_num(fe, shape_fns[j]) {
+              None => {}
+              Some(el_j) => {
+                let (r, c) = if *el_i <= *el_j { (*el_i, *el_j) } else { (*el_j, *el_i) };
+                triplets.push((r, c, ips.get(i,j)));
+              }
+            }
+          }
+        }
+      }
+    }
+
+    SparseMatrix::from_triplets(self.total_els, triplets, Symmetric)
+  }
+
+  /** Get a rough estimate of the number of bytes of memory used by this basis, including its reference monomial
+   *  sequences, precomputed weak gradients, and face inner product matrices. The mesh itself is not included. The
+   *  precomputed data is stored per oriented shape, so the estimate grows with the polynomial degrees but does not
+   *  grow with mesh refinement for meshes having a fixed number of oriented shapes, such as rectangular meshes.
+   */
+  pub fn approx_memory_bytes(&self) -> uint {
+    let mons_bytes = size_of::<Mon>() *
+      (self.int_mons.len() + self.side_mons_by_dep_dim.iter().fold(0u, |sum, mons| sum + mons.len()));
+    let wgrads_bytes =
+      self.int_mon_wgrads.iter().fold(0u, |sum, os_wgrads| {
+        os_wgrads.iter().fold(sum, |sum, wgrad| sum + wgrad.approx_memory_bytes())
+      }) +
+      self.side_mon_wgrads.iter().fold(0u, |sum, os_wgrads| {
+        os_wgrads.iter().fold(sum, |sum, sf_wgrads| {
+          sf_wgrads.iter().fold(sum, |sum, wgrad| sum + wgrad.approx_memory_bytes())
+        })
+      });
+    let dense_matrix_bytes = |m: &DenseMatrix| { size_of::<DenseMatrix>() + m.num_rows() * m.num_cols() * size_of::<R>() };
+    let ips_bytes =
+      self.ips_int_mons_by_oshape.iter().fold(0u, |sum, m| sum + dense_matrix_bytes(m)) +
+      self.ips_side_mons_by_oshape_side.iter().fold(0u, |sum, os_ips| {
+        os_ips.iter().fold(sum, |sum, m| sum + dense_matrix_bytes(m))
+      }) +
+      self.int_side_trace_products_by_oshape_side.iter().fold(0u, |sum, os_prods| {
+        os_prods.iter().fold(sum, |sum, m| sum + dense_matrix_bytes(m))
+      });
+    size_of::<WGBasis<Mon,MeshT>>() + mons_bytes + wgrads_bytes + ips_bytes
+  }
+
 
   // weak gradient accessors
 
@@ -383,6 +959,45 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
     &self.side_mon_wgrads[*oshape][*side_face][*monn]
   }
 
+  /** Compute the weak gradient of the function which is the given polynomial on the interior of the given oriented
+   *  shape and zero on its sides, as the combination of the interior monomial weak gradients. The polynomial is
+   *  first L2 projected onto the span of the interior monomials on the oriented shape, so it may contain monomials
+   *  beyond the interior degree limit. The weak gradient of a function with non-zero side values is obtained by
+   *  adding the weak gradients of its side parts.
+   */
+  pub fn wgrad_of_int_poly<P:Polynomial<Mon>>(&self, oshape: OShape, p: &P) -> WeakGrad {
+    let num_int_mons = self.int_mons.len();
+    let proj_coefs = unsafe {
+      let mut ips = DenseMatrix::of_size(num_int_mons, num_int_mons);
+      ips.fill_upper_triangle_from(&self.ips_int_mons_by_oshape[*oshape]);
+      let rhs = DenseMatrix::from_fn(num_int_mons, 1, |i,_| {
+        p.foldl_terms(0 as R, |sum, (coef, mon)| {
+          sum + coef * self.mesh.intg_facerel_mon_on_oshape_int(mon * self.int_mons[i].clone(), oshape)
+        })
+      });
+      let mut pivots = vec::from_elem(num_int_mons, 0 as lapack_int);
+      la::solve_symmetric_as_col_maj_with_ut_sys(ips.mut_col_maj_data_ptr(), num_int_mons as lapack_int,
+                                                 rhs.mut_col_maj_data_ptr(), 1 as lapack_int,
+                                                 pivots.as_mut_ptr());
+      rhs.col(0)
+    };
+    let num_comp_mons = self.wgrad_comp_mons().len();
+    let int_mon_wgrads = &self.int_mon_wgrads[*oshape];
+    WeakGrad {
+      comp_mon_coefs: vec::from_fn(domain_space_dims::<Mon>(), |d| {
+        vec::from_fn(num_comp_mons, |i| {
+          range(0, num_int_mons).fold(0 as R, |sum, monn| sum + proj_coefs[monn] * int_mon_wgrads[monn].comp_mon_coefs[d][i])
+        })
+      })
+    }
+  }
+
+  /// Get the monomials defining the components of all weak gradients in the basis.
+  #[inline]
+  pub fn wgrad_comp_mons<'a>(&'a self) -> &'a [Mon] {
+    self.weak_grad_solver.wgrad_comp_mons()
+  }
+
   #[inline]
   pub fn new_weak_grad_ops(&self) -> WeakGradOps<Mon> {
     self.weak_grad_solver.new_weak_grad_ops()
@@ -400,12 +1015,101 @@ impl <Mon:Monomial, MeshT:Mesh<Mon>> WGBasis<Mon,MeshT> {
     &self.ips_side_mons_by_oshape_side[*oshape][*side_face]
   }
 
+  /// Get the integrals over the given side of the oriented shape of the products of the interior monomial traces and
+  /// the side's monomials, with entry (i,j) for interior monomial i and side monomial j.
+  #[inline]
+  pub fn int_side_trace_products<'a>(&'a self, oshape: OShape, side_face: SideFace) -> &'a DenseMatrix {
+    &self.int_side_trace_products_by_oshape_side[*oshape][*side_face]
+  }
+
+  /// Get the full symmetric matrix of L2 inner products of the interior monomials on the interior of the given
+  /// oriented shape, with entry (i,j) for interior monomials i and j. The matrix is filled from the inner products
+  /// precomputed for each oriented shape, whose upper triangles are returned by ips_int_mons_for_oshape.
+  pub fn int_mon_inner_products(&self, oshape: OShape) -> DenseMatrix {
+    symmetric_from_upper_triangle(&self.ips_int_mons_by_oshape[*oshape])
+  }
+
+  /// Get the full symmetric matrix of L2 inner products of the side monomials on the given side of the oriented
+  /// shape, filled from the precomputed inner products as for int_mon_inner_products.
+  pub fn side_mon_inner_products(&self, oshape: OShape, side_face: SideFace) -> DenseMatrix {
+    symmetric_from_upper_triangle(&self.ips_side_mons_by_oshape_side[*oshape][*side_face])
+  }
+
 }  // WGBasis impl
 
+// Fill a full symmetric matrix from the upper triangle of the given square matrix.
+fn symmetric_from_upper_triangle(m: &DenseMatrix) -> DenseMatrix {
+  DenseMatrix::from_fn(m.num_rows(), m.num_rows(), |r,c| if r <= c { m.get(r,c) } else { m.get(c,r) })
+}
+
+
+/// Compute the number of basis elements, which are the degrees of freedom, of the weak Galerkin basis which would be
+/// constructed for the given mesh and degree limits, without constructing the basis. Each finite element interior
+/// supports the monomials of the interior degree limit, and each non-boundary side supports the monomials of the
+/// side degree limit which are constant in the side's dependent dimension.
+pub fn wg_dof_count<Mon:Monomial,MeshT:Mesh<Mon>>(mesh: &MeshT, int_polys_deg_lim: DegLim, side_polys_deg_lim: DegLim) -> uint {
+  let d = domain_space_dims::<Mon>();
+  mesh.num_fes() * num_mons_with_deg_lim(int_polys_deg_lim, d) +
+    mesh.num_nb_sides() * num_mons_with_deg_lim(side_polys_deg_lim, d-1)
+}
+
+/// Determine the degree limit of the weak gradient component monomials for the given interior degree limit, being the
+/// least degree limit admitting all partial derivatives of the interior monomials. For a total degree limit k this is
+/// total degree k-1. For a factor degree limit k in two or more dimensions, a partial derivative lowers the degree of
+/// only the differentiated factor, so it is factor degree k, and in one dimension factor degree k-1. Piecewise constant
+/// interiors, with degree limit 0, have constant weak gradient components.
+pub fn wgrad_comp_mons_deg_lim<Mon:Monomial>(int_polys_deg_lim: DegLim) -> DegLim {
+  let lowered = |k: u8| if k > 0 { k-1 } else { 0 };
+  match int_polys_deg_lim {
+    MaxMonDeg(k) => MaxMonDeg(lowered(k)),
+    MaxMonFactorDeg(k) => if domain_space_dims::<Mon>() == 1 { MaxMonFactorDeg(lowered(k)) } else { MaxMonFactorDeg(k) }
+  }
+}
+
+/// Describe the side monomials admitted by the side degree limit which are not traces of interior monomials admitted by
+/// the interior degree limit, if there are any. The side monomials for sides with dependent dimension r are those
+/// constant in coordinate r, as are their interior monomial traces. Such side monomials exceed what the weak gradient
+/// space, derived from the interior degree limit, can represent on the sides. The comparison is that made of the built
+/// basis against the weak gradient component traces by report_side_basis_gaps, made here against the interior
+/// monomials before the basis is built.
+pub fn excess_side_deg_lim_report<Mon:Monomial>(int_polys_deg_lim: DegLim, side_polys_deg_lim: DegLim) -> Option<~str> {
+  let int_mons: ~[Mon] = Monomial::mons_with_deg_lim_asc(int_polys_deg_lim);
+  let side_mons_by_dep_dim = side_mons_by_dep_dim_for_deg_lim::<Mon>(side_polys_deg_lim);
+  let comparisons = side_mons_vs_traces(side_mons_by_dep_dim.as_slice(), int_mons.as_slice());
+  for (r, &(num_side_mons, num_excess, _)) in comparisons.iter().enumerate() {
+    if num_excess > 0 {
+      return Some(format!("Side degree limit {:?} admits {} of the {} side monomials for dependent dimension {} which are \
+                           not traces of interior monomials for interior degree limit {:?}, and which the weak gradients \
+                           cannot represent: the side degree limit should not exceed the interior degree limit.",
+                          side_polys_deg_lim, num_excess, num_side_mons, r, int_polys_deg_lim));
+    }
+  }
+  None
+}
 
 
 // construction helpers
 
+// The monomials admitted by the side degree limit for each side dependent dimension r, being those which are constant in
+// coordinate r.
+fn side_mons_by_dep_dim_for_deg_lim<Mon:Monomial>(side_polys_deg_lim: DegLim) -> ~[~[Mon]] {
+  let mons_for_deg_lim: ~[Mon] = Monomial::mons_with_deg_lim_asc(side_polys_deg_lim);
+  vec::from_fn(domain_space_dims::<Mon>(), |r|
+    mons_for_deg_lim.iter().filter(|mon| mon.exp(Dim(r)) == Deg(0)).map(|m|m.clone()).collect()
+  )
+}
+
+// Compare the side monomials for each side dependent dimension r with the traces on such sides of the monomials of a
+// space, which are the space's monomials constant in coordinate r. Gives for each dependent dimension the number of
+// side monomials, the number of those which are not traces, and the number of traces.
+fn side_mons_vs_traces<Mon:Monomial>(side_mons_by_dep_dim: &[~[Mon]], space_mons: &[Mon]) -> ~[(uint, uint, uint)] {
+  side_mons_by_dep_dim.iter().enumerate().map(|(r, side_mons)| {
+    let traces: ~[&Mon] = space_mons.iter().filter(|mon| mon.exp(Dim(r)) == Deg(0)).collect();
+    let num_excess = side_mons.iter().count(|side_mon| !traces.iter().any(|&trace| *trace == *side_mon));
+    (side_mons.len(), num_excess, traces.len())
+  }).collect()
+}
+
 
 fn compute_wgrads<Mon:Monomial,MeshT:Mesh<Mon>>(wgrad_solver: &mut WeakGradSolver<Mon>,
                                                 int_mons: &[Mon],
@@ -1,9 +1,13 @@
 use common::{R, sq};
 use monomial::Monomial;
 use mesh::{Mesh, FENum};
+use wg_basis::{WGBasis, FaceMonNum};
 use wg_solution::WGSolution;
 
 use std::num::sqrt;
+use std::vec;
+use std::task;
+use std::comm::SharedChan;
 
 pub fn err_L2_norm<Mon:Monomial,MeshT:Mesh<Mon>>(exact_sol: |&[R]| -> R, approx: &WGSolution<Mon,MeshT>) -> R {
   let mesh = approx.basis().mesh();
@@ -18,6 +22,51 @@ pub fn err_L2_norm<Mon:Monomial,MeshT:Mesh<Mon>>(exact_sol: |&[R]| -> R, approx:
   sqrt(sum_fe_sq_err_intgs)
 }
 
+/// Compute the L2 norm of the error of the interior polynomials of the solution with the given basis coefficients,
+/// as err_L2_norm does, with the finite elements split into contiguous chunks integrated by up to num_threads tasks.
+/// The integration functions of meshes use mutable work buffers, so each task is given its own copy of the mesh and
+/// of the interior coefficients of its elements. The squared errors of the chunks are summed in element order, so
+/// the result differs from err_L2_norm's only by rounding.
+pub fn l2_error_parallel<Mon:Monomial+Send,MeshT:Mesh<Mon>+Clone+Send>(basis: &WGBasis<Mon,MeshT>,
+                                                                      sol_coefs: &[R],
+                                                                      exact_sol: fn(&[R]) -> R,
+                                                                      num_threads: uint) -> R {
+  if num_threads == 0 { fail!("At least one thread is required for parallel error evaluation."); }
+  let num_fes = basis.mesh().num_fes();
+  let num_chunks = if num_threads < num_fes { num_threads } else { num_fes };
+  let mons_per_fe_int = basis.mons_per_fe_int();
+
+  let (port, chan) = SharedChan::new();
+  for chunk in range(0, num_chunks) {
+    let (fe_begin, fe_end) = (chunk * num_fes / num_chunks, (chunk+1) * num_fes / num_chunks);
+    let mesh = basis.mesh().clone();
+    let int_mons = basis.ref_int_mons().to_owned();
+    let chunk_int_coefs: ~[R] = vec::from_fn((fe_end - fe_begin) * mons_per_fe_int, |i| {
+      sol_coefs[*basis.int_mon_el_num(FENum(fe_begin + i / mons_per_fe_int), FaceMonNum(i % mons_per_fe_int))]
+    });
+    let chunk_chan = chan.clone();
+    task::spawn(proc() {
+      let chunk_sum_sq_err_intgs = range(fe_begin, fe_end).fold(0 as R, |sum_fe_sq_err_intgs, fe| {
+        let fe_int_coefs = chunk_int_coefs.slice((fe - fe_begin) * mons_per_fe_int, (fe - fe_begin + 1) * mons_per_fe_int);
+        let sq_err_at_pt = |x: &[R], x_int_rel: &[R]| {
+          let approx = fe_int_coefs.iter().zip(int_mons.iter()).fold(0 as R, |sum, (&coef, mon)| sum + coef * mon.value_at(x_int_rel));
+          sq(exact_sol(x) - approx)
+        };
+        sum_fe_sq_err_intgs + mesh.intg_mixed_global_and_facerel_fn_on_fe_int(sq_err_at_pt, FENum(fe))
+      });
+      chunk_chan.send((chunk, chunk_sum_sq_err_intgs));
+    });
+  }
+
+  let mut chunk_sums = vec::from_elem(num_chunks, 0 as R);
+  for _ in range(0, num_chunks) {
+    let (chunk, chunk_sum) = port.recv();
+    chunk_sums[chunk] = chunk_sum;
+  }
+
+  sqrt(chunk_sums.iter().fold(0 as R, |sum, &chunk_sum| sum + chunk_sum))
+}
+
 /*
 # Solution evaluation functions
 
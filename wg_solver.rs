@@ -10,6 +10,7 @@ use variational_bilinear_form::VariationalBilinearForm;
 use la;
 
 use std::hashmap::HashMap;
+use std::vec;
 
 /* METHOD
  * Let {b_i}_i be a basis for V_h^0(Omega), and vbf the bilinear form for
@@ -32,23 +33,47 @@ use std::hashmap::HashMap;
  */
 
 
+/// Solve the WG problem for the given variational bilinear form, right hand side function f and boundary value
+/// function g. The Euclidean norm of the residual of the solved linear system is returned with the solution.
 pub fn solve<'a, Mon: Monomial, MeshT: Mesh<Mon>, VBF: VariationalBilinearForm<Mon, MeshT>>
-       (vbf: &'a VBF, f: |&[R]| -> R, g: |&[R]| -> R) -> WGSolution<'a,Mon,MeshT> {
+       (vbf: &'a VBF, f: |&[R]| -> R, g: |&[R]| -> R) -> (WGSolution<'a,Mon,MeshT>, R) {
   let basis = vbf.basis();
 
   let bnd_projs = boundary_projections(g, basis);
 
-  let sys_m = vbf.basis_els_vs_basis_els_transpose();
+  let load = assemble_wg_load(basis, |x|f(x));
 
   let sys_rhs = DenseMatrix::from_fn(basis.num_els(), 1, |i,_| 
-    ip_on_ints(|x|f(x), BasisElNum(i), basis)
+    load[i]
     - 
     vbf_bnd_projs_vs_bel(vbf, &bnd_projs, BasisElNum(i), basis)
   );
 
-  let sol_coefs = la::solve_sparse(&sys_m, &sys_rhs);
+  let (sol_coefs, residual_norm) = assemble_and_solve(vbf, &sys_rhs);
+
+  (WGSolution::new(sol_coefs, basis, bnd_projs), residual_norm)
+}
+
+/// Assemble the system matrix for the given variational bilinear form and solve the system with the given single
+/// column right hand side, returning the solution basis coefficients and the Euclidean norm of the system residual.
+pub fn assemble_and_solve<Mon: Monomial, MeshT: Mesh<Mon>, VBF: VariationalBilinearForm<Mon, MeshT>>
+       (vbf: &VBF, sys_rhs: &DenseMatrix) -> (~[R], R) {
+  let sys_m = vbf.basis_els_vs_basis_els_transpose();
+
+  let sol_coefs = la::solve_sparse(&sys_m, sys_rhs);
+
+  let residual_norm = la::residual_norm(&sys_m, sol_coefs, sys_rhs);
+
+  (sol_coefs, residual_norm)
+}
 
-  WGSolution::new(sol_coefs, basis, bnd_projs)
+/// Assemble the load vector for the source function f, with entry i the integral (f, (b_i)_0) of f against the
+/// interior part of basis element i, as in the right hand side of (sys) without the boundary value term. The entries
+/// of side supported basis elements are zero, since their interior parts are zero. Neumann data would contribute
+/// only through boundary sides, which support no basis elements (the basis is for Dirichlet problems), so it has
+/// no place in the load vector for this basis.
+pub fn assemble_wg_load<Mon:Monomial, MeshT: Mesh<Mon>>(basis: &WGBasis<Mon,MeshT>, f: |&[R]| -> R) -> ~[R] {
+  vec::from_fn(basis.num_els(), |i| ip_on_ints(|x|f(x), BasisElNum(i), basis))
 }
 
 fn ip_on_ints<Mon:Monomial, MeshT: Mesh<Mon>>
@@ -19,15 +19,18 @@ pub mod sparse_matrix;
 pub mod storage_by_ints;
 pub mod mesh;
 pub mod rectangle_mesh;
+pub mod rectangle_mesh_builder;
 pub mod triangle_mesh;
 pub mod triangle_mesh_builder;
 pub mod weak_gradient;
 pub mod wg_basis;
+pub mod rectangle_mesh_wg_basis;
 pub mod projection;
 pub mod variational_bilinear_form;
 pub mod vbf_laplace;
 pub mod wg_solution;
 pub mod wg_solver;
+pub mod static_condensation;
 pub mod wg_error_estimates;
 pub mod main;
 
@@ -50,5 +53,7 @@ mod tests {
   mod test_projection;
   mod test_variational_bilinear_form;
   mod test_vbf_laplace;
+  mod test_wg_solver;
+  mod test_static_condensation;
 }
 